@@ -25,7 +25,16 @@ mod skia;
 #[cfg(feature = "rgb")]
 mod rgb;
 
+#[cfg(feature = "serde")]
+mod serde;
+
+// Only used by the serde tests
+#[cfg(all(test, not(feature = "serde")))]
+use serde_json as _;
+
 use std::fmt::{Display, LowerHex, UpperHex};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use std::str::FromStr;
 
 use isclose::IsClose;
 use saturate::SaturatingInto;
@@ -36,6 +45,30 @@ use saturate::SaturatingInto;
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Color([f32; 3]); // r, g, b in that order
 
+/// A colour channel, used to access a [`Color`]'s components generically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// The red channel.
+    R,
+    /// The green channel.
+    G,
+    /// The blue channel.
+    B,
+}
+
+impl Channel {
+    /// All channels in the order they are stored in a [`Color`].
+    pub const ALL: [Self; 3] = [Self::R, Self::G, Self::B];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::R => 0,
+            Self::G => 1,
+            Self::B => 2,
+        }
+    }
+}
+
 impl Color {
     /// Creates a new [`Color`] value with the given red, green, and blue component values.
     ///
@@ -68,6 +101,22 @@ impl Color {
         self.0[2]
     }
 
+    /// Returns the component for the given channel.
+    #[inline]
+    #[must_use]
+    pub const fn channel(&self, channel: Channel) -> f32 {
+        self.0[channel.index()]
+    }
+
+    /// Sets the component for the given channel.
+    ///
+    /// The component should be in the range `0.0..1.0` for a semantically valid colour, although
+    /// this function does not perform any range checks.
+    #[inline]
+    pub fn set_channel(&mut self, channel: Channel, value: f32) {
+        self.0[channel.index()] = value;
+    }
+
     /// Returns an iterator over the colour's components.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &f32> {
@@ -115,6 +164,37 @@ impl Color {
         <[u8; 3]>::from(rgb).map(|c| f32::from(c) / 255.0).into()
     }
 
+    /// Creates a new [`Color`] from the red, green, blue, and alpha components as [`u8`], for
+    /// example from an RGBA pixel buffer.
+    ///
+    /// [`Color`] has no alpha channel, so the alpha component is ignored.
+    #[inline]
+    #[must_use]
+    pub fn from_rgba8(r: u8, g: u8, b: u8, _a: u8) -> Self {
+        Self::from_rgb8((r, g, b))
+    }
+
+    /// Creates a new [`Color`] from a slice of [`u8`] components, for example a single pixel of
+    /// an image buffer.
+    ///
+    /// The slice can contain either RGB or RGBA components, with any alpha component ignored.
+    /// Returns [`None`] if the slice has any other length.
+    #[inline]
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match *bytes {
+            [r, g, b] | [r, g, b, _] => Some(Self::from_rgb8((r, g, b))),
+            _ => None,
+        }
+    }
+
+    /// Returns an array containing the red, green, and blue components as [`u8`].
+    #[inline]
+    #[must_use]
+    pub fn to_rgb8_array(&self) -> [u8; 3] {
+        self.as_rgb8().into()
+    }
+
     /// Creates a new [`Color`] from a tuple containing the red, green, and blue
     /// components as [`u16`].
     #[inline]
@@ -202,6 +282,62 @@ impl UpperHex for Color {
     }
 }
 
+/// An error returned when parsing a [`Color`] from a string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl Display for ParseColorError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid colour string")
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a colour from either a hex string or the `rgb(...)` form.
+    ///
+    /// The accepted forms are:
+    ///
+    /// * `#rrggbb` or `#rgb` hex strings. The leading `#` is optional and hex digits are case
+    ///   insensitive.
+    /// * `rgb(r,g,b)` with each component as a float in the range `0.0..1.0`, as produced by this
+    ///   type's [`Display`] implementation. Whitespace around components is ignored.
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(components) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+            let mut iter = components.split(',').map(|c| c.trim().parse::<f32>());
+            match (iter.next(), iter.next(), iter.next(), iter.next()) {
+                (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Ok(Self::new(r, g, b)),
+                _ => Err(ParseColorError),
+            }
+        } else {
+            let hex = s.strip_prefix('#').unwrap_or(s);
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(ParseColorError);
+            }
+            let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).map_err(|_| ParseColorError);
+            match hex.len() {
+                3 => Ok(Self::from_rgb8((
+                    digit(0)? * 0x11,
+                    digit(1)? * 0x11,
+                    digit(2)? * 0x11,
+                ))),
+                6 => Ok(Self::from_rgb8((
+                    digit(0)? << 4 | digit(1)?,
+                    digit(2)? << 4 | digit(3)?,
+                    digit(4)? << 4 | digit(5)?,
+                ))),
+                _ => Err(ParseColorError),
+            }
+        }
+    }
+}
+
 impl From<[f32; 3]> for Color {
     #[inline]
     fn from(value: [f32; 3]) -> Self {
@@ -230,6 +366,96 @@ impl From<Color> for (f32, f32, f32) {
     }
 }
 
+impl Add for Color {
+    type Output = Self;
+
+    /// Adds the components of two colours.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        let [r, g, b] = rhs.0;
+        Self::new(self.r() + r, self.g() + g, self.b() + b)
+    }
+}
+
+impl AddAssign for Color {
+    /// Adds the components of another colour to this colour.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Color {
+    type Output = Self;
+
+    /// Subtracts the components of two colours.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        let [r, g, b] = rhs.0;
+        Self::new(self.r() - r, self.g() - g, self.b() - b)
+    }
+}
+
+impl SubAssign for Color {
+    /// Subtracts the components of another colour from this colour.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Self;
+
+    /// Multiplies each component of the colour by a scalar.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn mul(self, rhs: f32) -> Self::Output {
+        self.map(|c| c * rhs)
+    }
+}
+
+impl MulAssign<f32> for Color {
+    /// Multiplies each component of the colour by a scalar.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<f32> for Color {
+    type Output = Self;
+
+    /// Divides each component of the colour by a scalar.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn div(self, rhs: f32) -> Self::Output {
+        self.map(|c| c / rhs)
+    }
+}
+
+impl DivAssign<f32> for Color {
+    /// Divides each component of the colour by a scalar.
+    ///
+    /// The result is not clamped to the range `0.0..1.0`.
+    #[inline]
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
+    }
+}
+
 impl Color {
     /// Lightens the colour by a given amount.
     ///
@@ -276,6 +502,60 @@ impl Color {
             self.lighter(val)
         }
     }
+
+    /// Returns the relative luminance of the colour as defined by [WCAG 2], in the range
+    /// `0.0..1.0`.
+    ///
+    /// [WCAG 2]: https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+    #[inline]
+    #[must_use]
+    pub fn relative_luminance(&self) -> f32 {
+        let [r, g, b] = self.0.map(|c| {
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        0.0722_f32.mul_add(b, 0.2126_f32.mul_add(r, 0.7152 * g))
+    }
+
+    /// Returns the contrast ratio between this colour and `other` as defined by [WCAG 2], in the
+    /// range `1.0..21.0`. The result is the same regardless of the order of the colours.
+    ///
+    /// [WCAG 2]: https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+    #[inline]
+    #[must_use]
+    pub fn contrast_ratio(&self, other: Self) -> f32 {
+        let (l1, l2) = (self.relative_luminance(), other.relative_luminance());
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    /// Returns the colour in `palette` which is perceptually closest to this colour, or this
+    /// colour unchanged if `palette` is empty.
+    ///
+    /// The distance between colours is measured using the "redmean" weighted Euclidean distance
+    /// in sRGB space, which weights each component according to the average red of the two
+    /// colours. This is a cheap approximation of perceptual colour difference which is more
+    /// accurate than an unweighted RGB distance.
+    #[inline]
+    #[must_use]
+    pub fn nearest_in(&self, palette: &[Self]) -> Self {
+        palette
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                self.redmean_dist_sq(*a)
+                    .total_cmp(&self.redmean_dist_sq(*b))
+            })
+            .unwrap_or(*self)
+    }
+
+    fn redmean_dist_sq(self, other: Self) -> f32 {
+        let r_mean = (self.r() + other.r()) / 2.0;
+        let [dr, dg, db] = (self - other).0;
+        (2.0 + r_mean) * dr * dr + 4.0 * dg * dg + (3.0 - r_mean) * db * db
+    }
 }
 
 impl IsClose<f32> for Color {
@@ -329,6 +609,29 @@ mod tests {
         assert_is_close!(color.b(), 0.6);
     }
 
+    #[test]
+    fn channel() {
+        let color = Color::new(0.2, 0.4, 0.6);
+        assert_is_close!(color.channel(Channel::R), 0.2);
+        assert_is_close!(color.channel(Channel::G), 0.4);
+        assert_is_close!(color.channel(Channel::B), 0.6);
+
+        let components: Vec<_> = Channel::ALL.map(|ch| color.channel(ch)).into();
+        assert_eq!(components, color.as_slice());
+    }
+
+    #[test]
+    fn set_channel() {
+        let mut color = Color::new(0.2, 0.4, 0.6);
+        color.set_channel(Channel::G, 0.5);
+        assert_is_close!(color, Color::new(0.2, 0.5, 0.6));
+
+        for channel in Channel::ALL {
+            color.set_channel(channel, color.channel(channel).powi(2));
+        }
+        assert_is_close!(color, Color::new(0.04, 0.25, 0.36));
+    }
+
     #[test]
     fn iter() {
         let color = Color::new(0.2, 0.4, 0.6);
@@ -390,6 +693,38 @@ mod tests {
         assert_is_close!(color.0[2], 0.6);
     }
 
+    #[test]
+    fn from_rgba8() {
+        let color = Color::from_rgba8(0x33, 0x66, 0x99, 0x00);
+
+        assert_is_close!(color, Color::new(0.2, 0.4, 0.6));
+    }
+
+    #[test]
+    fn from_bytes() {
+        let rgba = [0x33, 0x66, 0x99, 0xff, 0xcc, 0xcc, 0xcc, 0xff];
+        let colors: Vec<_> = rgba.chunks_exact(4).map(Color::from_bytes).collect();
+
+        assert_eq!(colors.len(), 2);
+        assert_is_close!(colors[0].unwrap(), Color::new(0.2, 0.4, 0.6));
+        assert_is_close!(colors[1].unwrap(), Color::new(0.8, 0.8, 0.8));
+
+        assert_is_close!(
+            Color::from_bytes(&rgba[..3]).unwrap(),
+            Color::new(0.2, 0.4, 0.6)
+        );
+        assert!(Color::from_bytes(&rgba[..2]).is_none());
+        assert!(Color::from_bytes(&rgba[..5]).is_none());
+    }
+
+    #[test]
+    fn to_rgb8_array() {
+        let color = Color::new(0.2, 0.4, 0.6);
+
+        assert_eq!(color.to_rgb8_array(), [0x33, 0x66, 0x99]);
+        assert_eq!(Color::from_bytes(&color.to_rgb8_array()), Some(color));
+    }
+
     #[test]
     fn from_rgb16() {
         let rgb = (0x3333, 0x6666, 0x9999);
@@ -498,6 +833,27 @@ mod tests {
         assert_eq!(format!("{color:#X}"), "0x99CCFF");
     }
 
+    #[test]
+    fn from_str() {
+        let expected = Color::new(0.6, 0.8, 1.0);
+
+        assert_is_close!("#99ccff".parse::<Color>().unwrap(), expected);
+        assert_is_close!("#99CCFF".parse::<Color>().unwrap(), expected);
+        assert_is_close!("99ccff".parse::<Color>().unwrap(), expected);
+        assert_is_close!("#9cf".parse::<Color>().unwrap(), expected);
+        assert_is_close!("rgb(0.6,0.8,1)".parse::<Color>().unwrap(), expected);
+        assert_is_close!("rgb( 0.6, 0.8, 1.0 )".parse::<Color>().unwrap(), expected);
+        assert_is_close!(expected.to_string().parse::<Color>().unwrap(), expected);
+
+        assert_eq!("#99ccf".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("#99ccfg".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("#+9ccff".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("rgb(0.6,0.8)".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("rgb(0.6,0.8,1,1)".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("rgb(a,b,c)".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("".parse::<Color>(), Err(ParseColorError));
+    }
+
     #[test]
     fn from_array() {
         let array = [0.2, 0.4, 0.6];
@@ -539,6 +895,66 @@ mod tests {
         assert_is_close!(tuple.2, 0.6);
     }
 
+    #[test]
+    fn add() {
+        let mut color = Color::new(0.2, 0.4, 0.6) + Color::new(0.1, 0.2, 0.3);
+
+        assert_is_close!(color.0[0], 0.3);
+        assert_is_close!(color.0[1], 0.6);
+        assert_is_close!(color.0[2], 0.9);
+
+        color += Color::new(0.1, 0.2, 0.3);
+
+        assert_is_close!(color.0[0], 0.4);
+        assert_is_close!(color.0[1], 0.8);
+        assert_is_close!(color.0[2], 1.2);
+    }
+
+    #[test]
+    fn sub() {
+        let mut color = Color::new(0.2, 0.4, 0.6) - Color::new(0.1, 0.2, 0.3);
+
+        assert_is_close!(color.0[0], 0.1);
+        assert_is_close!(color.0[1], 0.2);
+        assert_is_close!(color.0[2], 0.3);
+
+        color -= Color::new(0.1, 0.2, 0.3);
+
+        assert_is_close!(color.0[0], 0.0);
+        assert_is_close!(color.0[1], 0.0);
+        assert_is_close!(color.0[2], 0.0);
+    }
+
+    #[test]
+    fn mul() {
+        let mut color = Color::new(0.2, 0.4, 0.6) * 0.5;
+
+        assert_is_close!(color.0[0], 0.1);
+        assert_is_close!(color.0[1], 0.2);
+        assert_is_close!(color.0[2], 0.3);
+
+        color *= 2.0;
+
+        assert_is_close!(color.0[0], 0.2);
+        assert_is_close!(color.0[1], 0.4);
+        assert_is_close!(color.0[2], 0.6);
+    }
+
+    #[test]
+    fn div() {
+        let mut color = Color::new(0.2, 0.4, 0.6) / 2.0;
+
+        assert_is_close!(color.0[0], 0.1);
+        assert_is_close!(color.0[1], 0.2);
+        assert_is_close!(color.0[2], 0.3);
+
+        color /= 0.5;
+
+        assert_is_close!(color.0[0], 0.2);
+        assert_is_close!(color.0[1], 0.4);
+        assert_is_close!(color.0[2], 0.6);
+    }
+
     #[test]
     fn lighter() {
         let color = Color::new(0.2, 0.4, 0.6).lighter(0.5);
@@ -557,6 +973,24 @@ mod tests {
         assert_is_close!(color.0[2], 0.5);
     }
 
+    #[test]
+    fn relative_luminance() {
+        assert_is_close!(Color::new(0.0, 0.0, 0.0).relative_luminance(), 0.0);
+        assert_is_close!(Color::new(1.0, 1.0, 1.0).relative_luminance(), 1.0);
+        assert_is_close!(Color::new(1.0, 0.0, 0.0).relative_luminance(), 0.2126);
+        assert_is_close!(Color::new(0.5, 0.5, 0.5).relative_luminance(), 0.214_041_14);
+    }
+
+    #[test]
+    fn contrast_ratio() {
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        assert_is_close!(black.contrast_ratio(white), 21.0);
+        assert_is_close!(white.contrast_ratio(black), 21.0);
+        assert_is_close!(white.contrast_ratio(white), 1.0);
+    }
+
     #[test]
     fn highlight() {
         let color = Color::new(0.6, 0.8, 1.0).highlight(0.5);
@@ -571,4 +1005,20 @@ mod tests {
         assert_is_close!(color.0[1], 0.7);
         assert_is_close!(color.0[2], 0.8);
     }
+
+    #[test]
+    fn nearest_in() {
+        let palette = [
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(0.8, 0.1, 0.1),
+        ];
+
+        assert_is_close!(Color::new(0.1, 0.1, 0.1).nearest_in(&palette), palette[0]);
+        assert_is_close!(Color::new(0.9, 0.8, 0.9).nearest_in(&palette), palette[1]);
+        assert_is_close!(Color::new(0.6, 0.2, 0.1).nearest_in(&palette), palette[2]);
+
+        let color = Color::new(0.2, 0.4, 0.6);
+        assert_is_close!(color.nearest_in(&[]), color);
+    }
 }
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use ::serde::de::{Error, SeqAccess, Unexpected, Visitor};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Color;
+
+/// Serializes the colour as a `#rrggbb` hex string.
+impl Serialize for Color {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&format_args!("{self:x}"))
+    }
+}
+
+/// Deserializes a colour from any of the following forms:
+///
+/// * A `#rrggbb` or `#rgb` hex string.
+/// * An `rgb(r,g,b)` string with float components in the range `0.0..1.0`.
+/// * An array of three float components `[r, g, b]` in the range `0.0..1.0`.
+impl<'de> Deserialize<'de> for Color {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a hex colour string, an rgb(...) string, or an array of 3 floats"
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        v.parse()
+            .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let r = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let g = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let b = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+        if seq.next_element::<f32>()?.is_some() {
+            return Err(A::Error::invalid_length(4, &self));
+        }
+        Ok(Color::new(r, g, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        let color = Color::new(0.6, 0.8, 1.0);
+        assert_eq!(serde_json::to_string(&color).unwrap(), r##""#99ccff""##);
+    }
+
+    #[test]
+    fn deserialize() {
+        let expected = Color::new(0.6, 0.8, 1.0);
+
+        let color: Color = serde_json::from_str(r##""#99ccff""##).unwrap();
+        assert_is_close!(color, expected);
+
+        let color: Color = serde_json::from_str(r##""#9CF""##).unwrap();
+        assert_is_close!(color, expected);
+
+        let color: Color = serde_json::from_str(r#""rgb(0.6,0.8,1)""#).unwrap();
+        assert_is_close!(color, expected);
+
+        let color: Color = serde_json::from_str("[0.6, 0.8, 1.0]").unwrap();
+        assert_is_close!(color, expected);
+
+        assert!(serde_json::from_str::<Color>(r#""invalid""#).is_err());
+        assert!(serde_json::from_str::<Color>("[0.6, 0.8]").is_err());
+        assert!(serde_json::from_str::<Color>("[0.6, 0.8, 1.0, 1.0]").is_err());
+        assert!(serde_json::from_str::<Color>("1.0").is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let color = Color::from_rgb8((0x12, 0x34, 0x56));
+        let json = serde_json::to_string(&color).unwrap();
+        assert_is_close!(serde_json::from_str::<Color>(&json).unwrap(), color);
+    }
+}
@@ -0,0 +1,88 @@
+use geom::{Scale, INCH_PER_UNIT};
+use saturate::SaturatingFrom;
+
+use crate::png::{self, Pixel};
+use crate::{Drawing, PngOptions};
+
+// Each braille character is a 2x4 grid of dots
+const DOTS_PER_CHAR_X: usize = 2;
+const DOTS_PER_CHAR_Y: usize = 4;
+
+// Bit for each dot in a braille character, indexed by [y][x]. See the Unicode braille patterns
+// block for the somewhat irregular numbering
+const DOT_BITS: [[u32; DOTS_PER_CHAR_X]; DOTS_PER_CHAR_Y] =
+    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+const BRAILLE_BLANK: u32 = 0x2800;
+
+// Alpha threshold for a pixel to be considered filled
+const ALPHA_THRESHOLD: u8 = 0x80;
+
+pub fn draw(drawing: &Drawing, width: usize) -> String {
+    let Some(width_px) = width.checked_mul(DOTS_PER_CHAR_X) else {
+        return String::new();
+    };
+    let width_in = drawing.bounds.width() * INCH_PER_UNIT.get() * drawing.scale;
+    let ppi = Scale::<_, Pixel>::new(f32::saturating_from(width_px) / width_in);
+
+    let Ok(pixmap) = png::render(drawing, ppi, PngOptions::default()) else {
+        return String::new();
+    };
+
+    let (pixmap_width, pixmap_height) = (
+        usize::saturating_from(pixmap.width()),
+        usize::saturating_from(pixmap.height()),
+    );
+    let is_filled = |x: usize, y: usize| {
+        (x < pixmap_width && y < pixmap_height)
+            && pixmap.pixels()[y * pixmap_width + x].alpha() >= ALPHA_THRESHOLD
+    };
+
+    let height = pixmap_height.div_ceil(DOTS_PER_CHAR_Y);
+    (0..height)
+        .map(|row| {
+            (0..width)
+                .map(|col| {
+                    let bits = DOT_BITS
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(dy, bits)| {
+                            bits.iter().enumerate().map(move |(dx, &bit)| (dx, dy, bit))
+                        })
+                        .filter(|&(dx, dy, _)| {
+                            is_filled(col * DOTS_PER_CHAR_X + dx, row * DOTS_PER_CHAR_Y + dy)
+                        })
+                        .fold(0, |acc, (_, _, bit)| acc | bit);
+                    char::from_u32(BRAILLE_BLANK | bits)
+                        .unwrap_or_else(|| unreachable!("all braille patterns are valid chars"))
+                })
+                .chain(['\n'])
+                .collect::<String>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use key::Key;
+
+    use crate::{Drawing, Options};
+
+    #[test]
+    fn test_to_ascii() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let ascii = drawing.to_ascii(4);
+        let lines: Vec<_> = ascii.lines().collect();
+
+        // 1u square => 8x8 dots => 4x2 chars
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.chars().count() == 4));
+        // The centre of the key is filled
+        assert_eq!(lines[0].chars().nth(1), Some('\u{28ff}'));
+
+        assert_eq!(drawing.to_ascii(0), "");
+        assert_eq!(drawing.to_ascii(usize::MAX), "");
+    }
+}
@@ -9,6 +9,8 @@ use crate::png::Pixel;
 #[derive(Debug, Clone, Copy)]
 #[non_exhaustive]
 pub enum Error {
+    /// The layout has no keys to draw
+    EmptyLayout,
     /// The drawing is larger than the maximum PNG dimensions
     #[cfg(feature = "png")]
     PngDimensionsError(Size<Pixel>),
@@ -18,6 +20,7 @@ impl fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
+            Self::EmptyLayout => write!(f, "layout contains no keys"),
             #[cfg(feature = "png")]
             Self::PngDimensionsError(dims) => write!(f, "invalid PNG dimensions {dims:?}"),
         }
@@ -30,7 +33,15 @@ impl std::error::Error for Error {}
 mod tests {
     use geom::Point;
 
-    use crate::{Drawing, Options};
+    use crate::{Drawing, Error, Options};
+
+    #[test]
+    fn error_fmt_empty_layout() {
+        let error = Drawing::try_new(&[], &Options::default()).unwrap_err();
+
+        assert_eq!(format!("{}", Error::EmptyLayout), "layout contains no keys");
+        assert_eq!(format!("{error}"), "layout contains no keys");
+    }
 
     #[cfg(feature = "png")]
     #[test]
@@ -0,0 +1,106 @@
+use font::Font;
+use geom::{Point, Rect, Size, ToTransform, Unit, Vector, DOT_PER_UNIT};
+
+use crate::{Caption, CaptionPosition};
+
+use super::{KeyDrawing, KeyPath, PathLayer, TextRun};
+
+pub fn draw(caption: &Caption, font: &Font, bounds: Rect<Unit>) -> (KeyDrawing, Rect<Unit>) {
+    // Get transform to correct height & flip y-axis
+    let text_scale = caption.font_size / font.cap_height();
+    let text_xform = text_scale.to_transform().then_scale(1.0, -1.0);
+
+    // Use the font's line height for the caption so descenders are not clipped
+    let ascender = font.ascender() * text_scale;
+    let height = (font.line_height() * text_scale) / DOT_PER_UNIT;
+
+    let text_xform = text_xform.then_translate(Vector::new(0.0, ascender.get()));
+    let glyphs = font.shape_string(&caption.text);
+    super::stats::count_glyphs(glyphs.len());
+    let path = font.render_glyphs(&glyphs) * text_xform;
+    let width = path.bounds.max.x / DOT_PER_UNIT.get();
+
+    let (origin, bounds) = match caption.position {
+        CaptionPosition::Below => {
+            let origin = Point::new(bounds.min.x, bounds.max.y);
+            let max = Point::new(
+                bounds.max.x.max(origin.x + width),
+                bounds.max.y + height.get(),
+            );
+            (origin, Rect::new(bounds.min, max))
+        }
+        CaptionPosition::Above => {
+            let origin = Point::new(bounds.min.x, bounds.min.y - height.get());
+            let max = Point::new(bounds.max.x.max(origin.x + width), bounds.max.y);
+            (origin, Rect::new(origin, max))
+        }
+    };
+
+    let anchor = path.bounds.min;
+    let drawing = KeyDrawing {
+        origin,
+        paths: Box::new([KeyPath {
+            data: path,
+            outline: None,
+            fill: Some(caption.color),
+            texture: None,
+            text: Some(Box::new([TextRun {
+                glyphs: glyphs.into_boxed_slice(),
+                transform: text_xform,
+            }])),
+            snap_anchor: Some(anchor),
+            layer: PathLayer::Other,
+        }]),
+        bounds: Rect::from_size(Size::new(width, height.get())),
+        name: Some("caption".to_owned()),
+        index: None,
+        homing: None,
+    };
+
+    (drawing, bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use color::Color;
+    use geom::{Dot, Length};
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_caption_draw() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let bounds = Rect::from_size(Size::new(2.0, 1.0));
+        let caption = Caption {
+            text: "AV".to_owned(),
+            font_size: Length::<Dot>::new(65.0),
+            color: Color::new(0.0, 0.0, 0.0),
+            position: CaptionPosition::Below,
+            ..Caption::default()
+        };
+
+        let (drawing, new_bounds) = draw(&caption, &font, bounds);
+        let height = font.line_height().get() / 10.0 / DOT_PER_UNIT.get();
+
+        assert_is_close!(drawing.origin, Point::new(0.0, 1.0));
+        assert_eq!(drawing.paths.len(), 1);
+        assert_is_close!(
+            new_bounds,
+            Rect::new(Point::zero(), Point::new(2.0, 1.0 + height))
+        );
+
+        let caption = Caption {
+            position: CaptionPosition::Above,
+            ..caption
+        };
+
+        let (drawing, new_bounds) = draw(&caption, &font, bounds);
+
+        assert_is_close!(drawing.origin, Point::new(0.0, -height));
+        assert_is_close!(
+            new_bounds,
+            Rect::new(Point::new(0.0, -height), Point::new(2.0, 1.0))
+        );
+    }
+}
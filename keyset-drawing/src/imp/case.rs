@@ -0,0 +1,101 @@
+use geom::{Length, Rect, RoundRect, ToPath, Unit, DOT_PER_UNIT};
+
+use crate::CaseParams;
+
+use super::{KeyDrawing, KeyPath, PathLayer};
+
+// The amount the recessed area inside the case's bezel is darkened by relative to the case colour
+const BEZEL_DARKEN: f32 = 0.3;
+
+pub fn draw(case: &CaseParams, bounds: Rect<Unit>) -> (KeyDrawing, Rect<Unit>) {
+    let rect = (bounds * DOT_PER_UNIT).inflate(case.margin.get(), case.margin.get());
+    let origin = rect.min / DOT_PER_UNIT;
+    let rect = rect.translate(-rect.min.to_vector());
+
+    let shell = KeyPath {
+        data: RoundRect::from_rect(rect, case.radius).to_path(),
+        outline: None,
+        fill: Some(case.color),
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Other,
+    };
+
+    // The bezel is the rim of the case, drawn by filling the area it surrounds in a darker colour
+    let recess = (case.bezel.get() > 0.0).then(|| {
+        let inner = rect.inflate(-case.bezel.get(), -case.bezel.get());
+        let radius = Length::new((case.radius - case.bezel).get().max(0.0));
+        KeyPath {
+            data: RoundRect::from_rect(inner, radius).to_path(),
+            outline: None,
+            fill: Some(case.color.darker(BEZEL_DARKEN)),
+            texture: None,
+            text: None,
+            snap_anchor: None,
+            layer: PathLayer::Other,
+        }
+    });
+
+    let drawing = KeyDrawing {
+        origin,
+        paths: std::iter::once(shell).chain(recess).collect(),
+        bounds: rect / DOT_PER_UNIT,
+        name: Some("case".to_owned()),
+        index: None,
+        homing: None,
+    };
+    let bounds = Rect::new(origin, origin + drawing.bounds.size());
+
+    (drawing, bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use color::Color;
+    use geom::{Point, Size};
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_case_draw() {
+        let bounds = Rect::from_size(Size::new(2.0, 1.0));
+        let case = CaseParams {
+            margin: Length::new(250.0),
+            radius: Length::new(100.0),
+            color: Color::new(0.2, 0.2, 0.2),
+            bezel: Length::new(0.0),
+            ..CaseParams::default()
+        };
+
+        let (drawing, new_bounds) = draw(&case, bounds);
+
+        assert_is_close!(drawing.origin, Point::new(-0.25, -0.25));
+        assert_eq!(drawing.paths.len(), 1);
+        assert_is_close!(drawing.paths[0].fill.unwrap(), case.color);
+        assert_is_close!(drawing.bounds, Rect::from_size(Size::new(2.5, 1.5)));
+        assert_is_close!(drawing.paths[0].data.bounds.width(), 2500.0);
+        assert_is_close!(drawing.paths[0].data.bounds.height(), 1500.0);
+        assert_is_close!(
+            new_bounds,
+            Rect::new(Point::new(-0.25, -0.25), Point::new(2.25, 1.25))
+        );
+
+        let case = CaseParams {
+            bezel: Length::new(100.0),
+            ..case
+        };
+
+        let (drawing, new_bounds) = draw(&case, bounds);
+
+        assert_eq!(drawing.paths.len(), 2);
+        assert_is_close!(drawing.paths[1].fill.unwrap(), case.color.darker(0.3));
+        assert_is_close!(drawing.paths[1].data.bounds.width(), 2300.0);
+        assert_is_close!(drawing.paths[1].data.bounds.height(), 1300.0);
+        assert_is_close!(
+            new_bounds,
+            Rect::new(Point::new(-0.25, -0.25), Point::new(2.25, 1.25))
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use color::Color;
+use geom::{Dot, ExtRect, Length, Mm, Path, Point, Rect, Size, ToPath, Unit, Vector, DOT_PER_MM};
+
+use crate::Options;
+
+use super::{KeyPath, Outline, PathLayer};
+
+// Size of a standard MX-style switch cutout
+const SWITCH_SIZE: Size<Mm> = Size::new(14.0, 14.0);
+
+// Approximate size of a plate-mounted stabilizer housing cutout
+const STAB_SIZE: Size<Mm> = Size::new(6.75, 14.0);
+
+// Distance between stabilizer centres for each key length. The spacing for the largest length not
+// greater than the key's length is used
+const STAB_SPACING: [(f32, Length<Mm>); 6] = [
+    (2.0, Length::new(23.8)),
+    (3.0, Length::new(38.1)),
+    (6.0, Length::new(95.25)),
+    (6.25, Length::new(100.0)),
+    (7.0, Length::new(114.3)),
+    (8.0, Length::new(133.35)),
+];
+
+// Tolerance used when comparing key lengths
+const TOLERANCE: f32 = 1e-3;
+
+const SWITCH_COLOR: Color = Color::new(0.0, 0.0, 1.0);
+const STAB_COLOR: Color = Color::new(0.0, 0.5, 0.0);
+const OUTLINE_WIDTH: Length<Dot> = Length::new(5.0);
+
+pub fn switch(key: &key::Key, options: &Options<'_>) -> Option<KeyPath> {
+    if !options.show_switch_cutouts {
+        return None;
+    }
+
+    let center = switch_center(key.shape)? * options.profile.dot_per_unit();
+
+    Some(KeyPath {
+        data: Rect::from_center_and_size(center, SWITCH_SIZE * DOT_PER_MM).to_path(),
+        outline: Some(Outline {
+            color: SWITCH_COLOR,
+            width: OUTLINE_WIDTH,
+        }),
+        fill: None,
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Other,
+    })
+}
+
+pub fn stab(key: &key::Key, options: &Options<'_>) -> Option<KeyPath> {
+    if !options.show_stab_cutouts {
+        return None;
+    }
+
+    let center = switch_center(key.shape)? * options.profile.dot_per_unit();
+
+    // Vertical keys (e.g. ISO enter or numpad plus) have vertically-spaced stabilizers
+    let (length, vertical) = match key.shape {
+        key::Shape::Normal(size) | key::Shape::Space(size) => {
+            if size.height > size.width {
+                (size.height, true)
+            } else {
+                (size.width, false)
+            }
+        }
+        key::Shape::IsoVertical | key::Shape::IsoHorizontal => (2.0, true),
+        key::Shape::None(..) | key::Shape::Homing(..) | key::Shape::SteppedCaps => return None,
+    };
+
+    let spacing = STAB_SPACING
+        .iter()
+        .rev()
+        .find(|&&(min_length, _)| length + TOLERANCE >= min_length)
+        .map(|&(_, spacing)| spacing * DOT_PER_MM)?;
+
+    let (offset, size) = if vertical {
+        (
+            Vector::new(0.0, spacing.get() / 2.0),
+            Size::new(STAB_SIZE.height, STAB_SIZE.width) * DOT_PER_MM,
+        )
+    } else {
+        (
+            Vector::new(spacing.get() / 2.0, 0.0),
+            STAB_SIZE * DOT_PER_MM,
+        )
+    };
+
+    let paths = [center - offset, center + offset]
+        .map(|center| Rect::from_center_and_size(center, size).to_path());
+
+    Some(KeyPath {
+        data: Path::from_slice(&paths),
+        outline: Some(Outline {
+            color: STAB_COLOR,
+            width: OUTLINE_WIDTH,
+        }),
+        fill: None,
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Other,
+    })
+}
+
+fn switch_center(shape: key::Shape) -> Option<Point<Unit>> {
+    match shape {
+        key::Shape::None(..) => None,
+        // The switch is centred under the vertical section of an ISO enter
+        key::Shape::IsoVertical | key::Shape::IsoHorizontal => Some(Point::new(0.875, 1.0)),
+        // The switch is centred under the raised section of a stepped caps lock
+        key::Shape::SteppedCaps => Some(shape.inner_rect().center()),
+        key::Shape::Normal(..) | key::Shape::Space(..) | key::Shape::Homing(..) => {
+            Some(shape.outer_rect().center())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+    use key::Key;
+
+    use super::*;
+
+    #[test]
+    fn test_switch() {
+        let key = Key::example();
+        assert!(switch(&key, &Options::default()).is_none());
+
+        let options = Options {
+            show_switch_cutouts: true,
+            ..Options::default()
+        };
+        let path = switch(&key, &options).unwrap();
+        assert_is_close!(
+            path.data.bounds,
+            Rect::from_center_and_size(Point::splat(500.0), SWITCH_SIZE * DOT_PER_MM)
+        );
+
+        let key = Key {
+            shape: key::Shape::IsoVertical,
+            ..Key::example()
+        };
+        let path = switch(&key, &options).unwrap();
+        assert_is_close!(path.data.bounds.center(), Point::new(875.0, 1000.0));
+
+        let key = Key {
+            shape: key::Shape::SteppedCaps,
+            ..Key::example()
+        };
+        let path = switch(&key, &options).unwrap();
+        assert_is_close!(path.data.bounds.center(), Point::new(625.0, 500.0));
+
+        let key = Key {
+            shape: key::Shape::None(Size::splat(1.0)),
+            ..Key::example()
+        };
+        assert!(switch(&key, &options).is_none());
+    }
+
+    #[test]
+    fn test_stab() {
+        let options = Options {
+            show_stab_cutouts: true,
+            ..Options::default()
+        };
+        let stab_width = STAB_SIZE.width * DOT_PER_MM.get();
+
+        let key = Key::example();
+        assert!(stab(&key, &options).is_none());
+
+        let key = Key {
+            shape: key::Shape::Normal(Size::new(2.25, 1.0)),
+            ..Key::example()
+        };
+        assert!(stab(&key, &Options::default()).is_none());
+        let path = stab(&key, &options).unwrap();
+        assert_is_close!(
+            path.data.bounds.width(),
+            23.8 * DOT_PER_MM.get() + stab_width
+        );
+        assert_is_close!(path.data.bounds.center(), Point::new(1125.0, 500.0));
+
+        let key = Key {
+            shape: key::Shape::Space(Size::new(6.25, 1.0)),
+            ..Key::example()
+        };
+        let path = stab(&key, &options).unwrap();
+        assert_is_close!(
+            path.data.bounds.width(),
+            100.0 * DOT_PER_MM.get() + stab_width
+        );
+
+        let key = Key {
+            shape: key::Shape::IsoVertical,
+            ..Key::example()
+        };
+        let path = stab(&key, &options).unwrap();
+        assert_is_close!(
+            path.data.bounds.height(),
+            23.8 * DOT_PER_MM.get() + stab_width
+        );
+        assert_is_close!(path.data.bounds.center(), Point::new(875.0, 1000.0));
+    }
+}
@@ -1,34 +1,68 @@
+use color::Color;
 use geom::{
-    Angle, Circle, Dot, ExtRect, ExtVec, Length, Path, Point, Rect, RoundRect, Size, ToPath,
-    Vector, DOT_PER_UNIT,
+    Angle, Circle, Dot, EllipticalRoundRect, ExtRect, ExtVec, Length, Path, Point, Rect, Scale,
+    Size, ToPath, Unit, Vector,
 };
-use profile::Profile;
+use profile::{BarProps, Profile, ScoopShape, TopSurface};
 
 use crate::Options;
 
-use super::{KeyPath, Outline};
+use super::{KeyPath, Outline, PathLayer};
 
-pub fn top(key: &key::Key, options: &Options<'_>) -> KeyPath {
+// The number of concentric rings used to approximate the radial gradient of a shaded dish
+const DISH_SHADE_STEPS: u8 = 8;
+
+// The amount key outlines are highlighted by relative to the key colour
+const OUTLINE_HIGHLIGHT: f32 = 0.15;
+
+// With Options::auto_outline_contrast, keys with a contrast ratio below this against the assumed
+// white background get a stronger outline. 3:1 is the WCAG minimum for graphical objects
+const AUTO_OUTLINE_BACKGROUND: Color = Color::new(1.0, 1.0, 1.0);
+const AUTO_OUTLINE_MIN_CONTRAST: f32 = 3.0;
+const AUTO_OUTLINE_HIGHLIGHT: f32 = 0.5;
+
+pub fn top(key: &key::Key, options: &Options<'_>, surface: &TopSurface) -> KeyPath {
     let path = match key.shape {
         key::Shape::None(..) => Path::empty(),
-        key::Shape::Normal(size) | key::Shape::Space(size) => {
-            options.profile.top_with_size(size).to_path()
+        key::Shape::Normal(size) | key::Shape::Space(size) => options
+            .profile
+            .top_surface_with_size_elliptical(surface, size)
+            .to_path(),
+        key::Shape::Homing(homing) => {
+            let top = options
+                .profile
+                .top_surface_with_size_elliptical(surface, Size::new(1.0, 1.0));
+            let homing = options.profile.resolve_homing(homing);
+            if options.show_homing_features && matches!(homing, key::Homing::Scoop) {
+                scoop_top(options.profile, top).to_path()
+            } else {
+                top.to_path()
+            }
         }
-        key::Shape::Homing(..) => options.profile.top_with_size(Size::new(1.0, 1.0)).to_path(),
         key::Shape::SteppedCaps => options
             .profile
-            .top_with_size(Size::new(1.25, 1.0))
+            .top_surface_with_size_elliptical(surface, Size::new(1.25, 1.0))
             .to_path(),
-        key::Shape::IsoHorizontal | key::Shape::IsoVertical => iso_top_path(options.profile),
+        key::Shape::IsoHorizontal | key::Shape::IsoVertical => {
+            let inner_radii = options
+                .inner_corner_radius
+                .map_or(surface.radius, |radius| Vector::splat(radius.get()));
+            iso_top_path(options.profile, surface, inner_radii)
+        }
     };
 
+    let color = key.top_color.unwrap_or(key.color);
     KeyPath {
         data: path,
-        fill: Some(key.color),
+        fill: Some(color),
         outline: Some(Outline {
-            color: key.color.highlight(0.15),
+            color: outline_color(color, options),
             width: options.outline_width,
         }),
+        texture: options.key_texture,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Key,
     }
 }
 
@@ -46,40 +80,44 @@ pub fn bottom(key: &key::Key, options: &Options<'_>) -> KeyPath {
             .profile
             .bottom_with_size(Size::new(1.75, 1.0))
             .to_path(),
-        key::Shape::IsoHorizontal | key::Shape::IsoVertical => iso_bottom_path(options.profile),
+        key::Shape::IsoHorizontal | key::Shape::IsoVertical => {
+            let inner_radius = options
+                .inner_corner_radius
+                .unwrap_or(options.profile.bottom.radius);
+            iso_bottom_path(options.profile, Vector::splat(inner_radius.get()))
+        }
     };
 
     KeyPath {
         data: path,
         fill: Some(key.color),
         outline: Some(Outline {
-            color: key.color.highlight(0.15),
+            color: outline_color(key.color, options),
             width: options.outline_width,
         }),
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Key,
     }
 }
 
-pub fn homing(key: &key::Key, options: &Options<'_>) -> Option<KeyPath> {
+pub fn homing(key: &key::Key, options: &Options<'_>, surface: &TopSurface) -> Option<KeyPath> {
     let profile = &options.profile;
 
     let key::Shape::Homing(homing) = key.shape else {
         return None;
     };
-    let homing = homing.unwrap_or(profile.homing.default);
+    if !options.show_homing_features {
+        return None;
+    }
+    let homing = profile.resolve_homing(homing);
 
-    let center = profile
-        .top_with_size(key.shape.inner_rect().size())
-        .center();
+    let center = profile.dish_center_with_surface(surface, key.shape.inner_rect().size());
 
     let bez_path = match homing {
-        key::Homing::Scoop => None,
-        key::Homing::Bar => Some(
-            Rect::from_center_and_size(
-                center + Size::new(0.0, profile.homing.bar.y_offset.get()),
-                profile.homing.bar.size,
-            )
-            .to_path(),
-        ),
+        key::Homing::Scoop => None, // Drawn as part of the top surface
+        key::Homing::Bar => Some(bar_path(center, profile.homing.bar)),
         key::Homing::Bump => Some(
             Circle::from_center_and_diameter(
                 center + Size::new(0.0, profile.homing.bump.y_offset.get()),
@@ -89,52 +127,190 @@ pub fn homing(key: &key::Key, options: &Options<'_>) -> Option<KeyPath> {
         ),
     };
 
+    let color = key.top_color.unwrap_or(key.color);
     bez_path.map(|path| KeyPath {
         data: path,
-        fill: Some(key.color),
+        fill: Some(color),
         outline: Some(Outline {
-            color: key.color.highlight(0.15),
+            color: outline_color(color, options),
             width: options.outline_width,
         }),
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Key,
     })
 }
 
-pub fn step(key: &key::Key, options: &Options<'_>) -> Option<KeyPath> {
+pub fn space_indicator(
+    key: &key::Key,
+    options: &Options<'_>,
+    surface: &TopSurface,
+) -> Option<KeyPath> {
+    let key::Shape::Space(size) = key.shape else {
+        return None;
+    };
+    let bar = options.profile.space_indicator?;
+
+    let center = options
+        .profile
+        .top_surface_with_size_elliptical(surface, size)
+        .center();
+    let color = key.top_color.unwrap_or(key.color);
+    Some(KeyPath {
+        data: bar_path(center, bar),
+        fill: Some(color),
+        outline: Some(Outline {
+            color: outline_color(color, options),
+            width: options.outline_width,
+        }),
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Key,
+    })
+}
+
+pub fn dish_shading(key: &key::Key, options: &Options<'_>, surface: &TopSurface) -> Vec<KeyPath> {
+    let profile = &options.profile;
+
+    let key::Shape::Homing(homing) = key.shape else {
+        return Vec::new();
+    };
+    let homing = profile.resolve_homing(homing);
+    if !(options.show_homing_features
+        && options.shade_dishes
+        && matches!(homing, key::Homing::Scoop))
+    {
+        return Vec::new();
+    }
+
+    let top = scoop_top(
+        profile,
+        profile.top_surface_with_size_elliptical(surface, Size::new(1.0, 1.0)),
+    );
+    let center = profile.dish_center_with_surface(surface, Size::new(1.0, 1.0));
+    // The dish can be offset from the centre of the top, so shrink the shading to keep it inside
+    let offset = center - top.center();
+    let size = (top.size() - Size::new(offset.x.abs(), offset.y.abs()) * 2.0).max(Size::zero());
+    let diameter = size.width.min(size.height);
+    let shape = profile.homing.scoop.shape;
+    let color = key.top_color.unwrap_or(key.color);
+    // Deeper scoops get stronger shading
+    let strength = (profile.homing.scoop.depth.get() / diameter).clamp(0.0, 1.0);
+
+    // There are no gradient fills, so approximate one by stacking progressively smaller and more
+    // strongly shaded shapes, darkest at the deepest part of the dish
+    (0..DISH_SHADE_STEPS)
+        .map(|i| {
+            let frac = f32::from(i) / f32::from(DISH_SHADE_STEPS);
+            let data = match shape {
+                // A cylindrical dish only curves across the key, so shade it in vertical bands
+                ScoopShape::Cylindrical => Rect::from_center_and_size(
+                    center,
+                    Size::new(size.width * (1.0 - frac), size.height),
+                )
+                .to_path(),
+                ScoopShape::Spherical => {
+                    Circle::from_center_and_diameter(center, Length::new(diameter * (1.0 - frac)))
+                        .to_path()
+                }
+                // The depth of a paraboloid is proportional to the square of the distance from its
+                // centre, so space the rings for equal steps in depth
+                ScoopShape::Parabolic => Circle::from_center_and_diameter(
+                    center,
+                    Length::new(diameter * (1.0 - frac).sqrt()),
+                )
+                .to_path(),
+            };
+            KeyPath {
+                data,
+                fill: Some(color.highlight(strength * (frac + 1.0 / f32::from(DISH_SHADE_STEPS)))),
+                outline: None,
+                texture: None,
+                text: None,
+                snap_anchor: None,
+                layer: PathLayer::Key,
+            }
+        })
+        .collect()
+}
+
+pub fn step(key: &key::Key, options: &Options<'_>, surface: &TopSurface) -> Option<KeyPath> {
     matches!(key.shape, key::Shape::SteppedCaps).then(|| {
         let profile = &options.profile;
 
         // Take average dimensions of top and bottom
         let rect = {
             let frac = 0.5;
-            let top = profile.top_with_size(Size::new(1.0, 1.0));
+            let top = profile.top_surface_with_size_elliptical(surface, Size::new(1.0, 1.0));
             let btm = profile.bottom_with_size(Size::new(1.0, 1.0));
-            RoundRect::new(
-                Point::lerp(top.min, btm.min, frac),
-                Point::lerp(top.max, btm.max, frac),
-                Length::lerp(top.radius, btm.radius, frac),
-            )
+            EllipticalRoundRect {
+                min: Point::lerp(top.min, btm.min, frac),
+                max: Point::lerp(top.max, btm.max, frac),
+                radii: top.radii.lerp(Vector::splat(btm.radius.get()), frac),
+            }
         };
+        let inner_radii = options
+            .inner_corner_radius
+            .map_or(rect.radii, |radius| Vector::splat(radius.get()));
 
         KeyPath {
-            data: step_path(rect),
+            data: step_path(rect, inner_radii, profile.dot_per_unit()),
             fill: Some(key.color),
             outline: Some(Outline {
-                color: key.color.highlight(0.15),
+                color: outline_color(key.color, options),
                 width: options.outline_width,
             }),
+            texture: None,
+            text: None,
+            snap_anchor: None,
+            layer: PathLayer::Key,
         }
     })
 }
 
-fn iso_bottom_path(profile: &Profile) -> Path<Dot> {
+fn outline_color(color: Color, options: &Options<'_>) -> Color {
+    if options.auto_outline_contrast
+        && color.contrast_ratio(AUTO_OUTLINE_BACKGROUND) < AUTO_OUTLINE_MIN_CONTRAST
+    {
+        color.highlight(AUTO_OUTLINE_HIGHLIGHT)
+    } else {
+        color.highlight(OUTLINE_HIGHLIGHT)
+    }
+}
+
+// The homing bar, offset vertically from the centre of the key's dish
+fn bar_path(center: Point<Dot>, bar: BarProps) -> Path<Dot> {
+    Rect::from_center_and_size(center + Size::new(0.0, bar.y_offset.get()), bar.size).to_path()
+}
+
+// A scooped key's dish is deeper than the profile's regular dish, so its steeper sides leave less
+// of the top surface visible from above. Shrink the top by the extra depth to show this
+fn scoop_top(profile: &Profile, top: EllipticalRoundRect<Dot>) -> EllipticalRoundRect<Dot> {
+    let extra_depth = (profile.homing.scoop.depth - profile.typ.depth())
+        .get()
+        .max(0.0);
+    let inset = extra_depth.min(top.width() / 2.0).min(top.height() / 2.0);
+    let rect = top.rect().inflate(-inset, -inset);
+    let radii = (top.radii() - Vector::splat(inset)).max(Vector::zero());
+
+    EllipticalRoundRect::from_rect_and_radii(rect, radii)
+}
+
+fn iso_bottom_path(profile: &Profile, inner_radii: Vector<Dot>) -> Path<Dot> {
     let rect150 = profile.bottom_with_size(Size::new(1.5, 1.0)).rect();
     let rect125 = profile
         .bottom_with_rect(Rect::new(Point::new(0.25, 0.0), Point::new(1.5, 2.0)))
         .rect();
     let radii = Vector::splat(profile.bottom.radius.get());
+    let inner_radii = clamp_inner_radii(
+        inner_radii,
+        Vector::new(rect125.min.x - rect150.min.x, rect125.max.y - rect150.max.y) - radii,
+    );
 
     let mut path = Path::builder();
-    path.abs_move(rect150.min + Size::new(0.0, radii.x));
+    path.abs_move(rect150.min + Size::new(0.0, radii.y));
     path.rel_arc(radii, Angle::zero(), false, true, radii.neg_y());
     path.abs_horiz_line(Length::new(rect150.max.x - radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, radii);
@@ -142,8 +318,8 @@ fn iso_bottom_path(profile: &Profile) -> Path<Dot> {
     path.rel_arc(radii, Angle::zero(), false, true, radii.neg_x());
     path.abs_horiz_line(Length::new(rect125.min.x + radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, -radii);
-    path.abs_vert_line(Length::new(rect150.max.y + radii.y));
-    path.rel_arc(radii, Angle::zero(), false, false, -radii);
+    path.abs_vert_line(Length::new(rect150.max.y + inner_radii.y));
+    path.rel_arc(inner_radii, Angle::zero(), false, false, -inner_radii);
     path.abs_horiz_line(Length::new(rect150.min.x + radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, -radii);
     path.close();
@@ -151,15 +327,24 @@ fn iso_bottom_path(profile: &Profile) -> Path<Dot> {
     path.build()
 }
 
-fn iso_top_path(profile: &Profile) -> Path<Dot> {
-    let rect150 = profile.top_with_size(Size::new(1.5, 1.0)).rect();
+fn iso_top_path(profile: &Profile, surface: &TopSurface, inner_radii: Vector<Dot>) -> Path<Dot> {
+    let rect150 = profile
+        .top_surface_with_size_elliptical(surface, Size::new(1.5, 1.0))
+        .rect();
     let rect125 = profile
-        .top_with_rect(Rect::new(Point::new(0.25, 0.0), Point::new(1.5, 2.0)))
+        .top_surface_with_rect_elliptical(
+            surface,
+            Rect::new(Point::new(0.25, 0.0), Point::new(1.5, 2.0)),
+        )
         .rect();
-    let radii = Vector::splat(profile.top.radius.get());
+    let radii = surface.radius;
+    let inner_radii = clamp_inner_radii(
+        inner_radii,
+        Vector::new(rect125.min.x - rect150.min.x, rect125.max.y - rect150.max.y) - radii,
+    );
 
     let mut path = Path::builder();
-    path.abs_move(rect150.min + Size::new(0.0, radii.x));
+    path.abs_move(rect150.min + Size::new(0.0, radii.y));
     path.rel_arc(radii, Angle::zero(), false, true, radii.neg_y());
     path.abs_horiz_line(Length::new(rect150.max.x - radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, radii);
@@ -167,8 +352,8 @@ fn iso_top_path(profile: &Profile) -> Path<Dot> {
     path.rel_arc(radii, Angle::zero(), false, true, radii.neg_x());
     path.abs_horiz_line(Length::new(rect125.min.x + radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, -radii);
-    path.abs_vert_line(Length::new(rect150.max.y + radii.y));
-    path.rel_arc(radii, Angle::zero(), false, false, -radii);
+    path.abs_vert_line(Length::new(rect150.max.y + inner_radii.y));
+    path.rel_arc(inner_radii, Angle::zero(), false, false, -inner_radii);
     path.abs_horiz_line(Length::new(rect150.min.x + radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, -radii);
     path.close();
@@ -176,30 +361,52 @@ fn iso_top_path(profile: &Profile) -> Path<Dot> {
     path.build()
 }
 
-fn step_path(rect: RoundRect<Dot>) -> Path<Dot> {
-    let radii = Vector::splat(rect.radius.get());
+fn step_path(
+    rect: EllipticalRoundRect<Dot>,
+    inner_radii: Vector<Dot>,
+    dot_per_unit: Scale<Unit, Dot>,
+) -> Path<Dot> {
+    let radii = rect.radii;
     let rect = Rect::from_origin_and_size(
-        Point::new(1.25 * DOT_PER_UNIT.get() - rect.min.x, rect.min.y),
-        Size::new(0.5 * DOT_PER_UNIT.get(), rect.height()),
+        Point::new(1.25 * dot_per_unit.get() - rect.min.x, rect.min.y),
+        Size::new(0.5 * dot_per_unit.get(), rect.height()),
+    );
+    // The inner corners share the left edge of the step, and the width with the outer corners
+    let inner_radii = clamp_inner_radii(
+        inner_radii,
+        Vector::new(rect.width() - radii.x, rect.height() / 2.0),
     );
 
     let mut path = Path::builder();
-    path.abs_move(rect.min + Size::new(0.0, radii.y));
-    path.rel_arc(radii, Angle::zero(), false, false, -radii);
+    path.abs_move(rect.min + Size::new(0.0, inner_radii.y));
+    path.rel_arc(inner_radii, Angle::zero(), false, false, -inner_radii);
     path.abs_horiz_line(Length::new(rect.max.x - radii.x));
     path.rel_arc(radii, Angle::zero(), false, true, radii);
     path.abs_vert_line(Length::new(rect.max.y - radii.y));
     path.rel_arc(radii, Angle::zero(), false, true, radii.neg_x());
-    path.abs_horiz_line(Length::new(rect.min.x - radii.x));
-    path.rel_arc(radii, Angle::zero(), false, false, radii.neg_y());
+    path.abs_horiz_line(Length::new(rect.min.x - inner_radii.x));
+    path.rel_arc(
+        inner_radii,
+        Angle::zero(),
+        false,
+        false,
+        inner_radii.neg_y(),
+    );
     path.close();
 
     path.build()
 }
 
+// Limit the radii of concave corners to the space left beside the adjoining convex corners so
+// oversized radii don't make the outline overlap itself
+fn clamp_inner_radii(inner_radii: Vector<Dot>, max: Vector<Dot>) -> Vector<Dot> {
+    inner_radii.min(max).max(Vector::zero())
+}
+
 #[cfg(test)]
 mod tests {
-    use isclose::assert_is_close;
+    use geom::DOT_PER_UNIT;
+    use isclose::{assert_is_close, IsClose};
     use key::Key;
 
     use super::*;
@@ -210,13 +417,15 @@ mod tests {
 
         // Regular 1u key
         let key = Key::example();
-        let path = top(&key, &options);
+        let path = top(&key, &options, &options.profile.top);
         let bounds = path.data.bounds;
 
         assert_is_close!(path.fill.unwrap(), key.color);
         assert_is_close!(path.outline.unwrap().color, key.color.highlight(0.15));
         assert_is_close!(path.outline.unwrap().width, options.outline_width);
-        let top_rect = options.profile.top_with_size(Size::new(1.0, 1.0));
+        let top_rect = options
+            .profile
+            .top_with_size_elliptical(Size::new(1.0, 1.0));
         assert_is_close!(bounds, top_rect.rect());
 
         // None
@@ -225,7 +434,7 @@ mod tests {
             key.shape = key::Shape::None(Size::splat(1.0));
             key
         };
-        let path = top(&key, &options);
+        let path = top(&key, &options, &options.profile.top);
         let bounds = path.data.bounds;
         assert_is_close!(bounds, Rect::zero());
 
@@ -235,20 +444,45 @@ mod tests {
             key.shape = key::Shape::Homing(None);
             key
         };
-        let path = top(&key, &options);
+        let path = top(&key, &options, &options.profile.top);
         let bounds = path.data.bounds;
-        let top_rect = options.profile.top_with_size(Size::splat(1.0));
+        let top_rect = options.profile.top_with_size_elliptical(Size::splat(1.0));
         assert_is_close!(bounds, top_rect.rect());
 
+        // Scooped homing keys have a smaller top surface due to their deeper dish
+        let key = {
+            let mut key = Key::example();
+            key.shape = key::Shape::Homing(Some(key::Homing::Scoop));
+            key
+        };
+        let path = top(&key, &options, &options.profile.top);
+        let bounds = path.data.bounds;
+        let extra_depth = options.profile.homing.scoop.depth - options.profile.typ.depth();
+        assert_is_close!(
+            bounds,
+            top_rect
+                .rect()
+                .inflate(-extra_depth.get(), -extra_depth.get())
+        );
+
+        let no_homing_options = Options {
+            show_homing_features: false,
+            ..Options::default()
+        };
+        let path = top(&key, &no_homing_options, &no_homing_options.profile.top);
+        assert_is_close!(path.data.bounds, top_rect.rect());
+
         // Stepped caps
         let key = {
             let mut key = Key::example();
             key.shape = key::Shape::SteppedCaps;
             key
         };
-        let path = top(&key, &options);
+        let path = top(&key, &options, &options.profile.top);
         let bounds = path.data.bounds;
-        let top_rect = options.profile.top_with_size(Size::new(1.25, 1.0));
+        let top_rect = options
+            .profile
+            .top_with_size_elliptical(Size::new(1.25, 1.0));
         assert_is_close!(bounds, top_rect.rect());
 
         // ISO enter
@@ -257,12 +491,52 @@ mod tests {
             key.shape = key::Shape::IsoVertical;
             key
         };
-        let path = top(&key, &options);
+        let path = top(&key, &options, &options.profile.top);
         let bounds = path.data.bounds;
-        let top_rect = options.profile.top_with_size(Size::new(1.5, 2.0));
+        let top_rect = options
+            .profile
+            .top_with_size_elliptical(Size::new(1.5, 2.0));
         assert_is_close!(bounds, top_rect.rect());
     }
 
+    #[test]
+    fn test_top_color() {
+        let options = Options::default();
+        let top_color = Color::new(0.2, 0.4, 0.6);
+        let key = Key {
+            top_color: Some(top_color),
+            ..Key::example()
+        };
+
+        let path = top(&key, &options, &options.profile.top);
+        assert_is_close!(path.fill.unwrap(), top_color);
+        assert_is_close!(path.outline.unwrap().color, top_color.highlight(0.15));
+
+        let path = bottom(&key, &options);
+        assert_is_close!(path.fill.unwrap(), key.color);
+        assert_is_close!(path.outline.unwrap().color, key.color.highlight(0.15));
+    }
+
+    #[test]
+    fn test_outline_color() {
+        let options = Options {
+            auto_outline_contrast: true,
+            ..Options::default()
+        };
+
+        // Light keys are hard to see on a white background so get a stronger outline
+        let light = Color::new(0.9, 0.9, 0.9);
+        assert_is_close!(outline_color(light, &options), light.highlight(0.5));
+        assert_is_close!(
+            outline_color(light, &Options::default()),
+            light.highlight(0.15)
+        );
+
+        // Dark keys already contrast with the background
+        let dark = Color::new(0.2, 0.2, 0.2);
+        assert_is_close!(outline_color(dark, &options), dark.highlight(0.15));
+    }
+
     #[test]
     fn test_bottom() {
         let options = Options::default();
@@ -333,7 +607,7 @@ mod tests {
             key
         };
 
-        let path = homing(&scoop, &options);
+        let path = homing(&scoop, &options, &options.profile.top);
         assert!(path.is_none()); // Top is already scooped; no additional feature to draw
 
         // Bar
@@ -343,7 +617,7 @@ mod tests {
             key
         };
 
-        let path = homing(&bar, &options);
+        let path = homing(&bar, &options, &options.profile.top);
         assert!(path.is_some());
         let path = path.unwrap();
         let bounds = path.data.bounds;
@@ -352,7 +626,10 @@ mod tests {
         assert_is_close!(path.outline.unwrap().color, bar.color.highlight(0.15));
         assert_is_close!(path.outline.unwrap().width, options.outline_width);
         let expected = Rect::from_center_and_size(
-            options.profile.top_with_size(Size::splat(1.0)).center(),
+            options
+                .profile
+                .top_with_size_elliptical(Size::splat(1.0))
+                .center(),
             options.profile.homing.bar.size,
         )
         .translate(Vector::new(0.0, options.profile.homing.bar.y_offset.get()));
@@ -365,7 +642,7 @@ mod tests {
             key
         };
 
-        let path = homing(&bump, &options);
+        let path = homing(&bump, &options, &options.profile.top);
         assert!(path.is_some());
         let path = path.unwrap();
         let bounds = path.data.bounds;
@@ -374,7 +651,10 @@ mod tests {
         assert_is_close!(path.outline.unwrap().color, bump.color.highlight(0.15));
         assert_is_close!(path.outline.unwrap().width, options.outline_width);
         let expected = Rect::from_center_and_size(
-            options.profile.top_with_size(Size::splat(1.0)).center(),
+            options
+                .profile
+                .top_with_size_elliptical(Size::splat(1.0))
+                .center(),
             Size::splat(options.profile.homing.bump.diameter.get()),
         )
         .translate(Vector::new(0.0, options.profile.homing.bump.y_offset.get()));
@@ -383,8 +663,208 @@ mod tests {
         // Non-homing key
         let none = Key::example();
 
-        let path = homing(&none, &options);
+        let path = homing(&none, &options, &options.profile.top);
         assert!(path.is_none()); // No additional feature to draw
+
+        // Homing features disabled
+        let options = Options {
+            show_homing_features: false,
+            ..options
+        };
+        let path = homing(&bar, &options, &options.profile.top);
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn test_homing_dish_offset() {
+        let dish_offset = Vector::new(0.0, -50.0);
+        let profile = Profile {
+            top: TopSurface {
+                dish_offset,
+                ..TopSurface::default()
+            },
+            ..Profile::default()
+        };
+        let options = Options {
+            profile: &profile,
+            shade_dishes: true,
+            ..Options::default()
+        };
+        let center = profile.top_with_size_elliptical(Size::splat(1.0)).center();
+
+        let bar = Key {
+            shape: key::Shape::Homing(Some(key::Homing::Bar)),
+            ..Key::example()
+        };
+        let path = homing(&bar, &options, &options.profile.top).unwrap();
+        let bar_offset = Vector::new(0.0, profile.homing.bar.y_offset.get());
+        assert_is_close!(path.data.bounds.center(), center + dish_offset + bar_offset);
+
+        let scoop = Key {
+            shape: key::Shape::Homing(Some(key::Homing::Scoop)),
+            ..Key::example()
+        };
+        // Allow a small tolerance for rounding errors where the shading touches the edge of the top
+        let top = scoop_top(&profile, profile.top_with_size_elliptical(Size::splat(1.0)))
+            .rect()
+            .inflate(0.01, 0.01);
+        for shape in [
+            ScoopShape::Spherical,
+            ScoopShape::Cylindrical,
+            ScoopShape::Parabolic,
+        ] {
+            let mut profile = profile.clone();
+            profile.homing.scoop.shape = shape;
+            let options = Options {
+                profile: &profile,
+                ..options.clone()
+            };
+            for path in dish_shading(&scoop, &options, &options.profile.top) {
+                assert_is_close!(path.data.bounds.center(), center + dish_offset);
+                assert!(top.contains_box(&path.data.bounds));
+            }
+        }
+    }
+
+    #[test]
+    fn test_space_indicator() {
+        let space = Key {
+            shape: key::Shape::Space(Size::new(6.25, 1.0)),
+            ..Key::example()
+        };
+
+        // No indicator by default
+        assert!(space_indicator(&space, &Options::default(), &TopSurface::default()).is_none());
+
+        let profile = Profile {
+            space_indicator: Some(BarProps {
+                size: Size::new(2000.0, 20.0),
+                y_offset: Length::new(100.0),
+            }),
+            ..Profile::default()
+        };
+        let options = Options {
+            profile: &profile,
+            ..Options::default()
+        };
+
+        let path = space_indicator(&space, &options, &options.profile.top).unwrap();
+        assert_is_close!(path.fill.unwrap(), space.color);
+        assert_is_close!(path.outline.unwrap().color, space.color.highlight(0.15));
+        let expected = Rect::from_center_and_size(
+            profile
+                .top_with_size_elliptical(Size::new(6.25, 1.0))
+                .center(),
+            Size::new(2000.0, 20.0),
+        )
+        .translate(Vector::new(0.0, 100.0));
+        assert_is_close!(path.data.bounds, expected);
+
+        // Only spacebars get an indicator
+        assert!(space_indicator(&Key::example(), &options, &options.profile.top).is_none());
+    }
+
+    #[test]
+    fn test_dish_shading() {
+        let scoop = {
+            let mut key = Key::example();
+            key.shape = key::Shape::Homing(Some(key::Homing::Scoop));
+            key
+        };
+
+        // Disabled by default
+        let options = Options::default();
+        assert!(dish_shading(&scoop, &options, &options.profile.top).is_empty());
+
+        let options = Options {
+            shade_dishes: true,
+            ..options
+        };
+        let paths = dish_shading(&scoop, &options, &options.profile.top);
+        assert_eq!(paths.len(), usize::from(DISH_SHADE_STEPS));
+
+        let top = top(&scoop, &options, &options.profile.top);
+        let center = top.data.bounds.center();
+        for pair in paths.windows(2) {
+            let (outer, inner) = (&pair[0], &pair[1]);
+            // Allow for rounding errors in the curves' bounds
+            assert!(top
+                .data
+                .bounds
+                .inflate(1.0, 1.0)
+                .contains_box(&outer.data.bounds));
+            assert!(outer.data.bounds.contains_box(&inner.data.bounds));
+            assert_is_close!(inner.data.bounds.center(), center);
+            assert!(outer.outline.is_none());
+        }
+        // Shading gets stronger towards the centre of the dish
+        let shade = |path: &KeyPath| (path.fill.unwrap().r() - scoop.color.r()).abs();
+        assert!(paths
+            .windows(2)
+            .all(|pair| shade(&pair[1]) > shade(&pair[0])));
+
+        // Deeper scoops are shaded more strongly
+        let mut profile = options.profile.clone();
+        profile.homing.scoop.depth *= 2.0;
+        let deep = dish_shading(
+            &scoop,
+            &Options {
+                profile: &profile,
+                ..options.clone()
+            },
+            &profile.top,
+        );
+        assert!(shade(&deep[7]) > shade(&paths[7]));
+
+        // Other homing types and non-homing keys aren't shaded
+        let bar = {
+            let mut key = Key::example();
+            key.shape = key::Shape::Homing(Some(key::Homing::Bar));
+            key
+        };
+        assert!(dish_shading(&bar, &options, &options.profile.top).is_empty());
+        assert!(dish_shading(&Key::example(), &options, &options.profile.top).is_empty());
+    }
+
+    #[test]
+    fn test_dish_shading_shape() {
+        let scoop = {
+            let mut key = Key::example();
+            key.shape = key::Shape::Homing(Some(key::Homing::Scoop));
+            key
+        };
+        let shading = |shape| {
+            let mut profile = Profile::default();
+            profile.homing.scoop.shape = shape;
+            let options = Options {
+                profile: &profile,
+                shade_dishes: true,
+                ..Options::default()
+            };
+            dish_shading(&scoop, &options, &options.profile.top)
+        };
+
+        let spherical = shading(ScoopShape::Spherical);
+        let cylindrical = shading(ScoopShape::Cylindrical);
+        let parabolic = shading(ScoopShape::Parabolic);
+
+        // Cylindrical dishes are shaded in full height bands which get narrower
+        for (sph, cyl) in spherical.iter().zip(&cylindrical).skip(1) {
+            assert!(cyl.data.bounds.height() > sph.data.bounds.height());
+            assert_is_close!(
+                cyl.data.bounds.height(),
+                cylindrical[0].data.bounds.height()
+            );
+        }
+        assert!(cylindrical
+            .windows(2)
+            .all(|pair| pair[1].data.bounds.width() < pair[0].data.bounds.width()));
+
+        // Parabolic dishes have the same outer ring, but inner rings are larger
+        assert_is_close!(parabolic[0].data.bounds, spherical[0].data.bounds);
+        for (sph, par) in spherical.iter().zip(&parabolic).skip(1) {
+            assert!(par.data.bounds.width() > sph.data.bounds.width());
+        }
     }
 
     #[test]
@@ -396,7 +876,7 @@ mod tests {
         };
         let options = Options::default();
 
-        let path = step(&key, &options);
+        let path = step(&key, &options, &options.profile.top);
         assert!(path.is_some());
         let path = path.unwrap();
         let bounds = path.data.bounds;
@@ -405,21 +885,92 @@ mod tests {
         assert_is_close!(path.outline.unwrap().color, key.color.highlight(0.15));
         assert_is_close!(path.outline.unwrap().width, options.outline_width);
 
-        let top_rect = options.profile.top_with_size(Size::splat(1.0));
+        let top_rect = options.profile.top_with_size_elliptical(Size::splat(1.0));
         let bottom_rect = options.profile.bottom_with_size(Size::splat(1.0));
-        let rect = RoundRect::new(
-            (top_rect.min + bottom_rect.min.to_vector()) / 2.0,
-            (top_rect.max + bottom_rect.max.to_vector()) / 2.0,
-            (top_rect.radius + bottom_rect.radius) / 2.0,
+        let rect = EllipticalRoundRect::from_rect_and_radii(
+            Rect::new(
+                (top_rect.min + bottom_rect.min.to_vector()) / 2.0,
+                (top_rect.max + bottom_rect.max.to_vector()) / 2.0,
+            ),
+            (top_rect.radii + Vector::splat(bottom_rect.radius.get())) / 2.0,
         );
         let rect = Rect::new(
             Point::new(
-                1.25 * DOT_PER_UNIT.0 - rect.min.x - rect.radius.get(),
+                1.25 * DOT_PER_UNIT.0 - rect.min.x - rect.radii.x,
                 rect.min.y,
             ),
             Point::new(1.75 * DOT_PER_UNIT.0 - rect.min.x, rect.max.y),
         );
 
         assert_is_close!(bounds, rect);
+
+        // Sharp inner corners
+        let options = Options {
+            inner_corner_radius: Some(Length::new(0.0)),
+            ..Options::default()
+        };
+        let path = step(&key, &options, &options.profile.top).unwrap();
+        let rect = Rect::new(
+            rect.min + Vector::new(top_rect.radii.x + bottom_rect.radius.get(), 0.0) / 2.0,
+            rect.max,
+        );
+
+        assert_is_close!(path.data.bounds, rect);
+    }
+
+    #[test]
+    fn test_iso_inner_corner_radius() {
+        let key = Key {
+            shape: key::Shape::IsoVertical,
+            ..Key::example()
+        };
+        let options = Options::default();
+        let default = top(&key, &options, &options.profile.top).data;
+
+        let options = Options {
+            inner_corner_radius: Some(Length::new(options.profile.top.radius.x)),
+            ..Options::default()
+        };
+        let same = top(&key, &options, &options.profile.top).data;
+
+        let options = Options {
+            inner_corner_radius: Some(Length::new(0.0)),
+            ..Options::default()
+        };
+        let sharp = top(&key, &options, &options.profile.top).data;
+
+        assert_eq!(default.len(), same.len());
+        assert!(default.iter().zip(same.iter()).all(|(a, b)| a.is_close(b)));
+        assert!(!default.iter().zip(sharp.iter()).all(|(a, b)| a.is_close(b)));
+        assert_is_close!(default.bounds, sharp.bounds);
+
+        // Oversized radii are clamped to the space beside the outer corners
+        let options = Options {
+            inner_corner_radius: Some(Length::new(1e4)),
+            ..Options::default()
+        };
+        let oversized = top(&key, &options, &options.profile.top).data;
+
+        assert_is_close!(default.bounds, oversized.bounds);
+
+        let bottom = bottom(&key, &options).data;
+        assert_is_close!(
+            bottom.bounds,
+            Options::default()
+                .profile
+                .bottom_with_rect(Rect::new(Point::origin(), Point::new(1.5, 2.0)))
+                .rect()
+        );
+
+        let key = Key {
+            shape: key::Shape::SteppedCaps,
+            ..Key::example()
+        };
+        let step = step(&key, &options, &options.profile.top).unwrap().data;
+        let bottom = options
+            .profile
+            .bottom_with_size(Size::new(1.75, 1.0))
+            .rect();
+        assert!(bottom.contains_box(&step.bounds));
     }
 }
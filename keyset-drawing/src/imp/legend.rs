@@ -1,81 +1,346 @@
-use font::Font;
-use geom::{Dot, Path, Point, Rect, ToTransform, Vector};
+use std::borrow::Cow;
+
+use font::{Font, FontUnit, LineMetrics, ShapedGlyph};
+use geom::{
+    Dot, ExtRect, Length, Path, Point, Rect, RoundRect, SideOffsets, Size, ToPath, ToTransform,
+    Transform, Vector,
+};
 use log::warn;
-use profile::Profile;
 use saturate::SaturatingFrom;
 
-use super::KeyPath;
+use crate::{MissingGlyphPolicy, Options};
+
+use super::{KeyPath, Outline, PathLayer, TextRun};
+
+// The legend size used to calculate the shared baseline when aligning to a baseline grid. This is
+// the default legend size in KLE
+const BASELINE_GRID_SIZE_IDX: usize = 3;
+
+// The minimum size legends are shrunk to when autosizing, relative to their original size. Legends
+// which still don't fit are squished horizontally as usual
+const MIN_AUTOSIZE_SCALE: f32 = 0.5;
 
 pub fn draw(
     legend: &::key::Legend,
-    font: &Font,
-    profile: &Profile,
+    options: &Options<'_>,
     top_rect: Rect<Dot>,
     align: Vector<()>,
-) -> KeyPath {
-    // Get transform to correct height & flip y-axis
-    let text_height = profile.text_height.get(legend.size_idx);
-    let text_scale = text_height / font.cap_height();
-    let text_xform = text_scale.to_transform().then_scale(1.0, -1.0);
+) -> Option<KeyPath> {
+    let profile = options.profile;
+
+    let margin = slot_margin(legend, options, top_rect, align);
+
+    // A pre-rendered path takes precedence over the legend's text, and doesn't use the font
+    if let Some(ref path) = legend.path {
+        return Some(draw_path(path, legend, margin, align));
+    }
+    // Without a font there is nothing to draw the text with, so the legend is skipped. Legends
+    // with missing glyphs are also skipped with the blank missing glyph policy
+    let font = options
+        .font
+        .filter(|font| !is_blanked(font, &legend.text, options.missing_glyph))?;
 
     // Dimensions used to position text
-    let line_height = font.line_height() * text_scale;
-    let n_lines = f32::saturating_from(legend.text.lines().count());
-    let margin = top_rect.inner_box(profile.text_margin.get(legend.size_idx));
-
-    let text_path: Path<_> = legend
-        .text
-        .lines()
-        .enumerate()
-        .map(|(i, text)| {
-            let line_offset = n_lines - f32::saturating_from(i) - 1.0;
-
-            let path = font.render_string(text) * text_xform;
+    let lines: Vec<_> = legend.text.lines().collect();
+    let metrics = font.line_metrics(&lines.join("\n"));
+    let quarter_turns = legend.quarter_turns.rem_euclid(4);
+    // Sideways legends are fitted to the height of the margin rather than its width
+    let max_size = if quarter_turns % 2 == 0 {
+        margin.size()
+    } else {
+        Size::new(margin.height(), margin.width())
+    };
+
+    // Get transform to correct height & flip y-axis. An explicit legend height takes precedence
+    // over the profile's height for the legend's size
+    let text_height = legend
+        .height
+        .unwrap_or_else(|| profile.text_height.get(legend.size_idx));
+    let lines: Vec<_> = lines
+        .iter()
+        .map(|line| ShapedLine::new(font, line, options.missing_glyph))
+        .collect();
+    let text_height = if options.autosize_legends {
+        autosize(font, &lines, &metrics, text_height, max_size)
+    } else {
+        text_height
+    };
+    let text_scale = text_height / font.cap_height();
+    // Positive oblique angles lean to the right, but our y-axis is now pointing down
+    let shear = (-options.synthetic_oblique).radians.tan();
+    let skew = Transform::new(1.0, 0.0, shear, 1.0, 0.0, 0.0);
+    let line_xform = text_scale.to_transform().then_scale(1.0, -1.0).then(&skew);
+
+    // Distance from the first line's baseline to the last line's baseline
+    let last_baseline = metrics
+        .baselines
+        .last()
+        .map_or(0.0, |&b| (b * text_scale).get());
+
+    let (paths, runs): (Vec<_>, Vec<_>) = lines
+        .into_iter()
+        .zip(metrics.baselines.iter())
+        .map(|(ShapedLine { text, glyphs, path }, &baseline)| {
+            let line_offset = last_baseline - (baseline * text_scale).get();
+            let path = path * line_xform;
             let width = path.bounds.width();
 
             // Check to ensure our legend fits
-            let h_scale = if width > margin.width() {
-                let percent = 100.0 * (width / margin.width() - 1.0);
+            let h_scale = if width > max_size.width {
+                let percent = 100.0 * (width / max_size.width - 1.0);
                 warn!(r#"legend "{text}" is {percent}% too wide; squishing legend to fit"#);
-                margin.width() / width
+                max_size.width / width
             } else {
                 1.0
             };
 
-            path.translate(Vector::new(
-                -width * align.x,
-                -line_offset * line_height.get(),
-            ))
-            .scale(h_scale, 1.0)
+            let translate = Vector::new(-width * align.x, -line_offset);
+            let run = TextRun {
+                glyphs: glyphs.into_boxed_slice(),
+                transform: line_xform
+                    .then_translate(translate)
+                    .then_scale(h_scale, 1.0),
+            };
+            (path.translate(translate).scale(h_scale, 1.0), run)
         })
-        .collect();
+        .unzip();
+    let text_path: Path<_> = paths.into_iter().collect();
 
     // Calculate legend bounds. For x this is based on actual size while for y we use the base line
     // and text height so each character (especially symbols) are still aligned across keys
-    let height = text_height + line_height * (n_lines - 1.0);
+    let height = text_height + Length::new(last_baseline);
     let bounds = Rect::new(
         Point::new(text_path.bounds.min.x, -height.get()),
         Point::new(text_path.bounds.max.x, 0.0),
     );
 
-    // Align the legend within the margins
+    // Rotate the legend about its centre, then align the rotated bounds within the margins
+    let rotate = quarter_turn_transform(quarter_turns, bounds.center());
+    let bounds = rotate.outer_transformed_box(&bounds);
     let size = margin.size() - bounds.size();
     let point = margin.min + Vector::new(align.x * size.width, align.y * size.height);
-    let text_path = text_path.translate(point - bounds.min);
+    let mut offset = point - bounds.min;
 
-    KeyPath {
+    // For centre legends on a baseline grid, use a baseline based on the profile rather than this
+    // legend's size so baselines line up across keys. Multiline legends are centred on this
+    if options.legend_baseline_grid && quarter_turns == 0 && (align.y - 0.5).abs() < 1e-3 {
+        let grid_height = profile.text_height.get(BASELINE_GRID_SIZE_IDX);
+        let baseline = top_rect.center().y + grid_height.get() / 2.0;
+        offset.y = baseline + last_baseline / 2.0;
+    }
+    let transform = rotate.then_translate(offset);
+    let text_path = text_path * transform;
+    let runs = runs
+        .into_iter()
+        .map(|run| TextRun {
+            transform: run.transform.then(&transform),
+            ..run
+        })
+        .collect();
+
+    let anchor = text_path.bounds.min;
+    Some(KeyPath {
         data: text_path,
+        outline: synthetic_bold_outline(legend, options),
+        fill: Some(legend.color),
+        texture: None,
+        text: Some(runs),
+        snap_anchor: Some(anchor),
+        layer: PathLayer::Legend,
+    })
+}
+
+// A line of legend text with missing glyphs replaced. Each line is only shaped once, and the glyphs
+// are used for both the line's outline and its text run
+struct ShapedLine<'a> {
+    text: Cow<'a, str>,
+    glyphs: Vec<ShapedGlyph>,
+    path: Path<FontUnit>,
+}
+
+impl<'a> ShapedLine<'a> {
+    fn new(font: &Font, line: &'a str, policy: MissingGlyphPolicy) -> Self {
+        let text = replace_missing_glyphs(font, line, policy);
+        let glyphs = font.shape_string(&text);
+        super::stats::count_glyphs(glyphs.len());
+        let path = font.render_glyphs(&glyphs);
+        Self { text, glyphs, path }
+    }
+}
+
+// Returns the margins for the legend's slot. Without any legend slot padding this is the whole text
+// margin. Otherwise the legend is limited to its third of the margin in each direction, with the
+// sides which face neighbouring slots inset by the padding
+fn slot_margin(
+    legend: &::key::Legend,
+    options: &Options<'_>,
+    top_rect: Rect<Dot>,
+    align: Vector<()>,
+) -> Rect<Dot> {
+    let margin = top_rect.inner_box(options.profile.text_margin.get(legend.size_idx));
+    let padding = options.legend_slot_padding.get();
+    if padding <= 0.0 {
+        return margin;
+    }
+
+    let col = usize::saturating_from((align.x * 2.0).round());
+    let row = usize::saturating_from((align.y * 2.0).round());
+    let slot = margin.split_grid(3, 3)[(row * 3 + col).min(8)];
+    let inset = |has_neighbour: bool| if has_neighbour { padding } else { 0.0 };
+    slot.inner_box(SideOffsets::new(
+        inset(align.y > 1e-3),
+        inset(align.x < 1.0 - 1e-3),
+        inset(align.y < 1.0 - 1e-3),
+        inset(align.x > 1e-3),
+    ))
+}
+
+// Returns the text height reduced so the legend fits within the given size, but no smaller than
+// MIN_AUTOSIZE_SCALE times the original height. Text size scales linearly, so the fitting height
+// can be calculated directly from the legend's size at the original height
+fn autosize(
+    font: &Font,
+    lines: &[ShapedLine<'_>],
+    metrics: &LineMetrics,
+    text_height: Length<Dot>,
+    max_size: Size<Dot>,
+) -> Length<Dot> {
+    let text_scale = text_height / font.cap_height();
+    let width = lines
+        .iter()
+        .map(|line| line.path.bounds.width())
+        .fold(0.0, f32::max)
+        * text_scale.get();
+    let last_baseline = metrics.baselines.last().copied().unwrap_or_default();
+    let height = ((font.cap_height() + last_baseline) * text_scale).get();
+
+    // Empty legends have zero width, giving an infinite scale which is clamped to 1
+    let fit = (max_size.width / width)
+        .min(max_size.height / height)
+        .clamp(MIN_AUTOSIZE_SCALE, 1.0);
+    text_height * fit
+}
+
+// Draws the legend's chip, if it has one, as a rounded rectangle around the drawn legend's bounds
+pub fn chip(legend: &::key::Legend, path: &KeyPath) -> Option<KeyPath> {
+    let chip = legend.chip?;
+    if path.data.is_empty() {
+        return None;
+    }
+
+    let rect = path
+        .data
+        .bounds
+        .inflate(chip.padding.get(), chip.padding.get());
+    let radius = chip
+        .radius
+        .min(Length::new(rect.width().min(rect.height()) / 2.0));
+
+    Some(KeyPath {
+        data: RoundRect::from_rect(rect, radius).to_path(),
+        outline: None,
+        fill: Some(chip.color),
+        texture: None,
+        text: None,
+        snap_anchor: path.snap_anchor,
+        layer: PathLayer::Legend,
+    })
+}
+
+// Draws a legend's pre-rendered path rotated about its centre and aligned within the margins. The
+// path is used as is, so it is neither scaled to the legend's height nor squished to fit
+fn draw_path(
+    path: &Path<Dot>,
+    legend: &::key::Legend,
+    margin: Rect<Dot>,
+    align: Vector<()>,
+) -> KeyPath {
+    let rotate = quarter_turn_transform(legend.quarter_turns, path.bounds.center());
+    let bounds = rotate.outer_transformed_box(&path.bounds);
+    let size = margin.size() - bounds.size();
+    let point = margin.min + Vector::new(align.x * size.width, align.y * size.height);
+    let transform = rotate.then_translate(point - bounds.min);
+
+    KeyPath {
+        data: path.clone() * transform,
         outline: None,
         fill: Some(legend.color),
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Legend,
+    }
+}
+
+// Synthesise bold by stroking the legend in the same colour. Half of the stroke lies inside the
+// outline, so we use double the width
+fn synthetic_bold_outline(legend: &::key::Legend, options: &Options<'_>) -> Option<Outline> {
+    (options.synthetic_bold.get() > 0.0).then(|| Outline {
+        color: legend.color,
+        width: options.synthetic_bold * 2.0,
+    })
+}
+
+// Returns an exact rotation by a number of clockwise quarter turns about the given point
+fn quarter_turn_transform(quarter_turns: i8, center: Point<Dot>) -> Transform<Dot, Dot> {
+    let (cos, sin) = match quarter_turns.rem_euclid(4) {
+        0 => (1.0, 0.0),
+        1 => (0.0, 1.0),
+        2 => (-1.0, 0.0),
+        _ => (0.0, -1.0),
+    };
+
+    Transform::<Dot, Dot>::translation(-center.x, -center.y)
+        .then(&Transform::new(cos, sin, -sin, cos, 0.0, 0.0))
+        .then_translate(center.to_vector())
+}
+
+// Whether the legend is omitted entirely, i.e. if it has any missing glyphs with the blank policy
+fn is_blanked(font: &Font, text: &::key::Text, policy: MissingGlyphPolicy) -> bool {
+    policy == MissingGlyphPolicy::Blank
+        && text
+            .lines()
+            .flat_map(str::chars)
+            .any(|char| !font.has_glyph(char))
+}
+
+fn replace_missing_glyphs<'a>(
+    font: &Font,
+    text: &'a str,
+    policy: MissingGlyphPolicy,
+) -> Cow<'a, str> {
+    let replacement = match policy {
+        MissingGlyphPolicy::Replace(char) if font.has_glyph(char) => char,
+        // Fall back to .notdef if the replacement is also missing. Legends with missing glyphs are
+        // skipped before getting here with the blank policy
+        MissingGlyphPolicy::Notdef | MissingGlyphPolicy::Blank | MissingGlyphPolicy::Replace(_) => {
+            return Cow::Borrowed(text)
+        }
+    };
+
+    if text.chars().all(|char| font.has_glyph(char)) {
+        Cow::Borrowed(text)
+    } else {
+        text.chars()
+            .map(|char| {
+                if font.has_glyph(char) {
+                    char
+                } else {
+                    replacement
+                }
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use color::Color;
-    use geom::{PathSegment, Size};
+    use font::Font;
+    use geom::{Angle, Length, PathSegment, Size, ToPath};
     use isclose::assert_is_close;
     use key::Text;
+    use profile::Profile;
 
     use super::*;
 
@@ -84,12 +349,21 @@ mod tests {
         let legend = ::key::Legend {
             text: Text::parse_from("AV"),
             size_idx: 5,
+            height: None,
             color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         };
         let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
         let profile = Profile::default();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            ..Options::default()
+        };
         let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
-        let path = draw(&legend, &font, &profile, top_rect, Vector::zero());
+        let path = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
 
         assert_eq!(
             path.data
@@ -102,18 +376,26 @@ mod tests {
         let legend = ::key::Legend {
             text: Text::parse_from("😎"),
             size_idx: 5,
+            height: None,
             color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         };
-        let path = draw(&legend, &font, &profile, top_rect, Vector::new(1.0, 1.0));
+        let path = draw(&legend, &options, top_rect, Vector::new(1.0, 1.0)).unwrap();
 
         assert_eq!(path.data.len(), 12); // == .notdef length
 
         let legend = ::key::Legend {
             text: Text::parse_from("Some really long legend that will totally need to be squished"),
             size_idx: 5,
+            height: None,
             color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         };
-        let path = draw(&legend, &font, &profile, top_rect, Vector::new(1.0, 1.0));
+        let path = draw(&legend, &options, top_rect, Vector::new(1.0, 1.0)).unwrap();
 
         assert_is_close!(
             path.data.bounds.width(),
@@ -127,10 +409,375 @@ mod tests {
         let legend = ::key::Legend {
             text: Text::parse_from("Two<br>lines!"),
             size_idx: 5,
+            height: None,
             color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         };
-        let path = draw(&legend, &font, &profile, top_rect, Vector::new(1.0, 1.0));
+        let path = draw(&legend, &options, top_rect, Vector::new(1.0, 1.0)).unwrap();
 
         assert!(path.data.bounds.height() > profile.text_height.get(legend.size_idx).get() * 2.0);
+        assert!(path.outline.is_none());
+    }
+
+    #[test]
+    fn test_legend_draw_height() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            ..Options::default()
+        };
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+
+        let legend = ::key::Legend {
+            text: Text::parse_from("A"),
+            size_idx: 3,
+            height: None,
+            color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
+        };
+        let default = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
+
+        // An explicit height overrides the profile's height for the size index
+        let height = profile.text_height.get(3) * 1.5;
+        let legend = ::key::Legend {
+            height: Some(height),
+            ..legend
+        };
+        let path = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
+
+        assert_is_close!(
+            path.data.bounds.height(),
+            default.data.bounds.height() * 1.5
+        );
+        assert_is_close!(path.data.bounds.width(), default.data.bounds.width() * 1.5);
+    }
+
+    #[test]
+    fn test_legend_draw_slot_padding() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let padding = Length::new(20.0);
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            legend_slot_padding: padding,
+            ..Options::default()
+        };
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+        let margin = top_rect.inner_box(profile.text_margin.get(5));
+
+        // Adjacent top left and top centre legends, both long enough to be squished
+        let legend = ::key::Legend::new(
+            "Some really long legend that will need to be squished",
+            5,
+            Color::new(0.0, 0.0, 0.0),
+        );
+        let left = draw(&legend, &options, top_rect, Vector::new(0.0, 0.0))
+            .unwrap()
+            .data;
+        let center = draw(&legend, &options, top_rect, Vector::new(0.5, 0.0))
+            .unwrap()
+            .data;
+        let right = draw(&legend, &options, top_rect, Vector::new(1.0, 0.0))
+            .unwrap()
+            .data;
+
+        // Adjacent legends don't overlap, and are separated by the padding on both sides
+        assert!(left.bounds.max.x < center.bounds.min.x);
+        assert!(center.bounds.max.x < right.bounds.min.x);
+        assert_is_close!(center.bounds.min.x - left.bounds.max.x, 2.0 * padding.get());
+        assert_is_close!(
+            right.bounds.min.x - center.bounds.max.x,
+            2.0 * padding.get()
+        );
+
+        // Each legend is limited to its third of the margin, and only the sides facing
+        // neighbouring slots are inset
+        let third = margin.width() / 3.0;
+        assert_is_close!(left.bounds.min.x, margin.min.x);
+        assert_is_close!(left.bounds.max.x, margin.min.x + third - padding.get());
+        assert_is_close!(center.bounds.min.x, margin.min.x + third + padding.get());
+        assert_is_close!(center.bounds.max.x, margin.max.x - third - padding.get());
+        assert_is_close!(right.bounds.max.x, margin.max.x);
+
+        // No padding leaves the whole margin available
+        let options = Options {
+            legend_slot_padding: Length::new(0.0),
+            ..options
+        };
+        let center = draw(&legend, &options, top_rect, Vector::new(0.5, 0.0))
+            .unwrap()
+            .data;
+        assert_is_close!(center.bounds.width(), margin.width());
+    }
+
+    #[test]
+    fn test_legend_draw_autosize() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            autosize_legends: true,
+            ..Options::default()
+        };
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+        let margin = top_rect.inner_box(profile.text_margin.get(5));
+        // Large enough that no legend needs to be shrunk or squished
+        let wide_rect = profile.top_with_size(Size::new(20.0, 1.0)).rect();
+
+        // A legend which is too wide is shrunk uniformly until it fits
+        let legend = ::key::Legend::new("Wider", 5, Color::new(0.0, 0.0, 0.0));
+        let natural = draw(&legend, &options, wide_rect, Vector::zero())
+            .unwrap()
+            .data
+            .bounds;
+        assert!(natural.width() > margin.width());
+        assert!(natural.width() < margin.width() * 2.0);
+
+        let path = draw(&legend, &options, top_rect, Vector::zero())
+            .unwrap()
+            .data;
+        let fit = margin.width() / natural.width();
+        assert_is_close!(path.bounds.width(), margin.width());
+        assert_is_close!(path.bounds.height(), natural.height() * fit);
+
+        // Legends which still don't fit at the minimum size are squished
+        let legend = ::key::Legend::new(
+            "Some really long legend that will need to be squished",
+            5,
+            Color::new(0.0, 0.0, 0.0),
+        );
+        let natural = draw(&legend, &options, wide_rect, Vector::zero())
+            .unwrap()
+            .data
+            .bounds;
+        let path = draw(&legend, &options, top_rect, Vector::zero())
+            .unwrap()
+            .data;
+        assert_is_close!(path.bounds.width(), margin.width());
+        assert_is_close!(path.bounds.height(), natural.height() * MIN_AUTOSIZE_SCALE);
+
+        // Legends which fit are unchanged
+        let legend = ::key::Legend::new("A", 5, Color::new(0.0, 0.0, 0.0));
+        let natural = draw(&legend, &options, wide_rect, Vector::zero())
+            .unwrap()
+            .data
+            .bounds;
+        let path = draw(&legend, &options, top_rect, Vector::zero())
+            .unwrap()
+            .data;
+        assert_is_close!(path.bounds.size(), natural.size());
+    }
+
+    #[test]
+    fn test_legend_draw_path() {
+        let profile = Profile::default();
+        let options = Options {
+            profile: &profile,
+            ..Options::default()
+        };
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+        let margin = top_rect.inner_box(profile.text_margin.get(5));
+
+        let path = Rect::new(Point::new(0.0, 0.0), Point::new(200.0, 100.0)).to_path();
+        let mut legend = ::key::Legend {
+            path: Some(path.clone()),
+            chip: None,
+            ..::key::Legend::new("ignored", 5, Color::new(0.0, 0.0, 0.0))
+        };
+
+        // The path is used as is instead of the text, aligned within the margins
+        let drawn = draw(&legend, &options, top_rect, Vector::new(1.0, 1.0)).unwrap();
+        assert_eq!(drawn.data.len(), path.len());
+        assert!(drawn.text.is_none());
+        assert_is_close!(drawn.data.bounds.size(), path.bounds.size());
+        assert_is_close!(drawn.data.bounds.max, margin.max);
+
+        legend.quarter_turns = 1;
+        let drawn = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
+        assert_is_close!(drawn.data.bounds.width(), path.bounds.height());
+        assert_is_close!(drawn.data.bounds.height(), path.bounds.width());
+        assert_is_close!(drawn.data.bounds.min, margin.min);
+    }
+
+    #[test]
+    fn test_legend_draw_no_font() {
+        let options = Options {
+            font: None,
+            ..Options::default()
+        };
+        let top_rect = options.profile.top_with_size(Size::new(1.0, 1.0)).rect();
+
+        // Text legends are skipped without a font, but pre-rendered paths are still drawn
+        let legend = ::key::Legend::new("A", 5, Color::new(0.0, 0.0, 0.0));
+        assert!(draw(&legend, &options, top_rect, Vector::zero()).is_none());
+
+        let legend = ::key::Legend {
+            path: Some(Rect::new(Point::new(0.0, 0.0), Point::new(200.0, 100.0)).to_path()),
+            ..legend
+        };
+        assert!(draw(&legend, &options, top_rect, Vector::zero()).is_some());
+    }
+
+    #[test]
+    fn test_legend_draw_rotated() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            ..Options::default()
+        };
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+        let margin = top_rect.inner_box(profile.text_margin.get(5));
+
+        let mut legend = ::key::Legend::new("AV", 5, Color::new(0.0, 0.0, 0.0));
+        let upright = draw(&legend, &options, top_rect, Vector::new(0.5, 0.5))
+            .unwrap()
+            .data;
+
+        for quarter_turns in [1, -1, 3] {
+            legend.quarter_turns = quarter_turns;
+            let sideways = draw(&legend, &options, top_rect, Vector::new(0.5, 0.5))
+                .unwrap()
+                .data;
+
+            assert_eq!(sideways.len(), upright.len());
+            assert_is_close!(sideways.bounds.width(), upright.bounds.height());
+            assert_is_close!(sideways.bounds.height(), upright.bounds.width());
+            assert!(margin.contains_box(&sideways.bounds));
+        }
+
+        legend.quarter_turns = 2;
+        let upside_down = draw(&legend, &options, top_rect, Vector::zero())
+            .unwrap()
+            .data;
+        assert_is_close!(upside_down.bounds.size(), upright.bounds.size());
+        assert_is_close!(upside_down.bounds.min.x, margin.min.x);
+
+        // Long legends are squished to fit the height of the margin when sideways
+        legend.text = Text::parse_from("Some really long legend that will need to be squished");
+        legend.quarter_turns = 1;
+        let path = draw(&legend, &options, top_rect, Vector::zero())
+            .unwrap()
+            .data;
+        assert_is_close!(path.bounds.height(), margin.height());
+    }
+
+    #[test]
+    fn test_legend_draw_baseline_grid() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            legend_baseline_grid: true,
+            ..Options::default()
+        };
+
+        let baseline = top_rect.center().y + profile.text_height.get(3).get() / 2.0;
+        for size_idx in [3, 5] {
+            let legend = ::key::Legend {
+                text: Text::parse_from("AV"),
+                size_idx,
+                height: None,
+                color: Color::new(0.0, 0.0, 0.0),
+                quarter_turns: 0,
+                path: None,
+                chip: None,
+            };
+            let path = draw(&legend, &options, top_rect, Vector::new(0.5, 0.5)).unwrap();
+            assert_is_close!(path.data.bounds.max.y, baseline);
+
+            // Only centre legends are aligned to the grid
+            let path = draw(&legend, &options, top_rect, Vector::new(0.5, 0.0)).unwrap();
+            let margin = top_rect.inner_box(profile.text_margin.get(size_idx));
+            assert_is_close!(
+                path.data.bounds.max.y,
+                margin.min.y + profile.text_height.get(size_idx).get()
+            );
+        }
+    }
+
+    #[test]
+    fn test_legend_draw_missing_glyph() {
+        let legend = ::key::Legend {
+            text: Text::parse_from("A😎"),
+            size_idx: 5,
+            height: None,
+            color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
+        };
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+
+        let count_moves = |policy| {
+            let options = Options {
+                profile: &profile,
+                font: Some(&font),
+                missing_glyph: policy,
+                ..Options::default()
+            };
+            draw(&legend, &options, top_rect, Vector::zero()).map(|path| {
+                path.data
+                    .into_iter()
+                    .filter(|el| matches!(*el, PathSegment::Move(..)))
+                    .count()
+            })
+        };
+
+        // A and .notdef have 2 contours each, V has 1
+        assert_eq!(count_moves(MissingGlyphPolicy::Notdef), Some(4));
+        assert_eq!(count_moves(MissingGlyphPolicy::Blank), None);
+        assert_eq!(count_moves(MissingGlyphPolicy::Replace('V')), Some(3));
+        assert_eq!(count_moves(MissingGlyphPolicy::Replace('🙃')), Some(4));
+    }
+
+    #[test]
+    fn test_legend_draw_synthetic() {
+        let legend = ::key::Legend {
+            text: Text::parse_from("AV"),
+            size_idx: 5,
+            height: None,
+            color: Color::new(0.0, 0.0, 0.0),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
+        };
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let profile = Profile::default();
+        let top_rect = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            ..Options::default()
+        };
+        let upright = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
+
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            synthetic_bold: Length::new(5.0),
+            synthetic_oblique: Angle::degrees(15.0),
+            ..Options::default()
+        };
+        let synthetic = draw(&legend, &options, top_rect, Vector::zero()).unwrap();
+
+        assert!(synthetic.data.bounds.width() > upright.data.bounds.width());
+        let outline = synthetic.outline.unwrap();
+        assert_is_close!(outline.color, legend.color);
+        assert_is_close!(outline.width, Length::new(10.0));
     }
 }
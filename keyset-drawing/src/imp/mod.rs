@@ -1,16 +1,23 @@
+pub mod caption;
+pub mod case;
+mod cutout;
 mod key;
 mod legend;
+pub mod stats;
 
+use std::borrow::Borrow;
 use std::collections::HashSet;
 
 use ::key::Key;
 use ::key::Shape as KeyShape;
 use color::Color;
-use geom::{Dot, Length, ToPath, Unit, Vector};
-use geom::{Path, Point};
+use font::{FontUnit, ShapedGlyph};
+use geom::{Dot, Homography, Length, Scale, ToPath, Transform, Unit, Vector, DOT_PER_UNIT};
+use geom::{Path, Point, Rect};
+use isclose::IsClose;
 use saturate::SaturatingFrom;
 
-use crate::Options;
+use crate::{Options, TextureParams};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Outline {
@@ -18,62 +25,231 @@ pub struct Outline {
     pub width: Length<Dot>,
 }
 
+impl IsClose<f32> for Outline {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.color.is_close_tol(other.color, rel_tol, abs_tol)
+            && self.width.is_close_tol(other.width, rel_tol, abs_tol)
+    }
+}
+
+// A line of legend text, kept alongside its outlines so output formats which can embed fonts can
+// draw real text
+#[derive(Debug, Clone)]
+pub struct TextRun {
+    pub glyphs: Box<[ShapedGlyph]>,
+    pub transform: Transform<FontUnit, Dot>,
+}
+
+impl IsClose<f32> for TextRun {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.transform
+            .is_close_tol(other.transform, rel_tol, abs_tol)
+            && self.glyphs.len() == other.glyphs.len()
+            && self.glyphs.iter().zip(other.glyphs.iter()).all(|(s, o)| {
+                s.glyph_id == o.glyph_id
+                    && s.text == o.text
+                    && s.position.is_close_tol(o.position, rel_tol, abs_tol)
+            })
+    }
+}
+
+// The part of the drawing a path belongs to, used to export legends and key bodies separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathLayer {
+    // The keycap itself, including homing features and dish shading
+    Key,
+    // A legend on the key
+    Legend,
+    // Anything else, such as the case, caption, cutouts, and debug outlines
+    Other,
+}
+
 #[derive(Debug, Clone)]
 pub struct KeyPath {
     pub data: Path<Dot>,
     pub outline: Option<Outline>,
     pub fill: Option<Color>,
+    pub texture: Option<TextureParams>,
+    pub text: Option<Box<[TextRun]>>,
+    // The point moved onto a whole pixel when snapping legends to pixels. Every path drawn for the
+    // same legend shares an anchor, so they all move by the same offset
+    pub snap_anchor: Option<Point<Dot>>,
+    pub layer: PathLayer,
+}
+
+impl IsClose<f32> for KeyPath {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        let outline = match (self.outline, other.outline) {
+            (Some(s), Some(o)) => s.is_close_tol(o, rel_tol, abs_tol),
+            (None, None) => true,
+            _ => false,
+        };
+        let fill = match (self.fill, other.fill) {
+            (Some(s), Some(o)) => s.is_close_tol(o, rel_tol, abs_tol),
+            (None, None) => true,
+            _ => false,
+        };
+        let texture = match (self.texture, other.texture) {
+            (Some(s), Some(o)) => s.is_close_tol(o, rel_tol, abs_tol),
+            (None, None) => true,
+            _ => false,
+        };
+        let text = match (self.text.as_deref(), other.text.as_deref()) {
+            (Some(s), Some(o)) => {
+                s.len() == o.len()
+                    && s.iter()
+                        .zip(o.iter())
+                        .all(|(s, o)| s.is_close_tol(o, rel_tol, abs_tol))
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        let snap_anchor = match (self.snap_anchor, other.snap_anchor) {
+            (Some(s), Some(o)) => s.is_close_tol(o, rel_tol, abs_tol),
+            (None, None) => true,
+            _ => false,
+        };
+        outline
+            && fill
+            && texture
+            && text
+            && snap_anchor
+            && self.layer == other.layer
+            && self.data.len() == other.data.len()
+            && self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .all(|(s, o)| s.is_close_tol(o, rel_tol, abs_tol))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct KeyDrawing {
     pub origin: Point<Unit>,
     pub paths: Box<[KeyPath]>,
+    // Outer bounds of the key relative to its origin, and a name used to identify it
+    pub bounds: Rect<Unit>,
+    pub name: Option<String>,
+    // Index of the key in the layout, or None for drawings which aren't keys such as the caption
+    pub index: Option<usize>,
+    // The type of homing used to draw the key, if it is a homing key
+    pub homing: Option<::key::Homing>,
+}
+
+impl IsClose<f32> for KeyDrawing {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.origin.is_close_tol(other.origin, rel_tol, abs_tol)
+            && self.bounds.is_close_tol(other.bounds, rel_tol, abs_tol)
+            && self.name == other.name
+            && self.index == other.index
+            && self.homing == other.homing
+            && self.paths.len() == other.paths.len()
+            && self
+                .paths
+                .iter()
+                .zip(other.paths.iter())
+                .all(|(s, o)| s.is_close_tol(o, rel_tol, abs_tol))
+    }
 }
 
 impl KeyDrawing {
     pub fn new(key: &Key, options: &Options<'_>) -> Self {
-        let show_key = options.show_keys && !matches!(key.shape, KeyShape::None(..));
+        // Use the spacebar top surface for spacebars if the profile has one, otherwise use the top
+        // surface for the key's row if the profile is sculpted
+        let surface = match key.shape {
+            KeyShape::Space(..) => options.profile.spacebar_top(key.row),
+            _ => options.profile.row_top(key.row),
+        };
+
+        let show_key = options.show_keys && !key.is_decal();
 
         let bottom = show_key.then(|| key::bottom(key, options));
-        let top = show_key.then(|| key::top(key, options));
-        let step = show_key.then(|| key::step(key, options)).flatten();
-        let homing = show_key.then(|| key::homing(key, options)).flatten();
-
-        let top_rect = options.profile.top_with_rect(key.shape.inner_rect()).rect();
-
-        let margin = options.show_margin.then(|| {
-            // Cann't get unique margins because SideOffsets: !Hash, use unique size_idx's instead
-            let sizes: HashSet<_> = key.legends.iter().flatten().map(|l| l.size_idx).collect();
-            let paths: Vec<_> = sizes
-                .into_iter()
-                .map(|s| {
-                    top_rect
-                        .inner_box(options.profile.text_margin.get(s))
-                        .to_path()
-                })
-                .collect();
-            let path = Path::from_slice(&paths);
-
-            KeyPath {
-                data: path,
-                outline: Some(Outline {
-                    color: Color::new(1.0, 0.0, 0.0),
-                    width: Length::new(5.0),
-                }),
-                fill: None,
-            }
-        });
+        let top = show_key.then(|| key::top(key, options, surface));
+        let step = show_key.then(|| key::step(key, options, surface)).flatten();
+        let homing = show_key
+            .then(|| key::homing(key, options, surface))
+            .flatten();
+        let space_indicator = show_key
+            .then(|| key::space_indicator(key, options, surface))
+            .flatten();
+        let dish_shading = if show_key {
+            key::dish_shading(key, options, surface)
+        } else {
+            Vec::new()
+        };
+        let switch_cutout = cutout::switch(key, options);
+        let stab_cutout = cutout::stab(key, options);
+
+        let top_rect = options
+            .profile
+            .top_surface_with_rect(surface, key.shape.inner_rect())
+            .rect();
+
+        let margin = options
+            .show_margin
+            .then(|| margin(key, options.profile, top_rect));
 
-        let legends = key.legends.iter().enumerate().filter_map(|(i, l)| {
-            l.as_ref().map(|legend| {
+        let (chips, legends): (Vec<_>, Vec<_>) = key
+            .legends
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| {
+                let legend = l.as_ref()?;
                 let align = Vector::new(
                     f32::saturating_from(i % 3) / 2.0,
                     f32::saturating_from(i / 3) / 2.0,
                 );
-                legend::draw(legend, options.font, options.profile, top_rect, align)
+                let path = stats::time_legend(|| legend::draw(legend, options, top_rect, align))?;
+                Some((legend::chip(legend, &path), path))
             })
-        });
+            .unzip();
+
+        let legend_bounds = if options.show_legend_bounds {
+            legend_bounds(&legends)
+        } else {
+            Vec::new()
+        };
 
         // Do a bunch of chaining here rather than using [...].iter().filter_map(|it| it). This
         // gives iterator a known size so it will allocate the required size when collecting to a
@@ -83,14 +259,110 @@ impl KeyDrawing {
             .chain(top)
             .chain(step)
             .chain(homing)
+            .chain(space_indicator)
+            .chain(dish_shading)
             .chain(margin)
-            .chain(legends);
+            .chain(chips.into_iter().flatten())
+            .chain(legends)
+            .chain(legend_bounds)
+            .chain(switch_cutout)
+            .chain(stab_cutout);
+
+        let homing = match key.shape {
+            KeyShape::Homing(homing) => Some(options.profile.resolve_homing(homing)),
+            _ => None,
+        };
+
+        // Name the key after its first legend, joining multiple lines with a space
+        let name = key
+            .legends
+            .iter()
+            .flatten()
+            .next()
+            .map(|legend| legend.text.lines().collect::<Vec<_>>().join(" "));
 
         Self {
-            origin: key.position,
+            origin: key.position * pitch_scale(options.profile),
             paths: paths.collect(),
+            bounds: key.shape.outer_rect() * pitch_scale(options.profile),
+            name,
+            index: None,
+            homing,
+        }
+    }
+
+    pub fn map_colors(&mut self, mut f: impl FnMut(Color) -> Color) {
+        for path in &mut self.paths {
+            path.fill = path.fill.map(&mut f);
+            if let Some(ref mut outline) = path.outline {
+                outline.color = f(outline.color);
+            }
         }
     }
+
+    pub fn project(&mut self, homography: &Homography<Dot>) {
+        let offset = self.origin.to_vector() * DOT_PER_UNIT;
+        for path in &mut self.paths {
+            let data = std::mem::take(&mut path.data);
+            path.data = data
+                .translate(offset)
+                .project(homography)
+                .translate(-offset);
+            // Text can't be projected, so fall back to drawing the outlines. Projected paths can't
+            // be aligned to pixels either
+            path.text = None;
+            path.snap_anchor = None;
+        }
+    }
+}
+
+// The scale from key units to drawing units for the profile's unit pitch. The drawing always uses
+// DOT_PER_UNIT, so keys are spaced further apart or closer together for a non-standard pitch
+pub fn pitch_scale(profile: &profile::Profile) -> Scale<Unit, Unit> {
+    Scale::new(profile.dot_per_unit().get() / DOT_PER_UNIT.get())
+}
+
+// Outlines the text margin for each legend size used on the key
+fn margin(key: &Key, profile: &profile::Profile, top_rect: Rect<Dot>) -> KeyPath {
+    // Can't get unique margins because SideOffsets: !Hash, use unique size_idx's instead
+    let sizes: HashSet<_> = key.legends.iter().flatten().map(|l| l.size_idx).collect();
+    let paths: Vec<_> = sizes
+        .into_iter()
+        .map(|s| top_rect.inner_box(profile.text_margin.get(s)).to_path())
+        .collect();
+
+    KeyPath {
+        data: Path::from_slice(&paths),
+        outline: Some(Outline {
+            color: Color::new(1.0, 0.0, 0.0),
+            width: Length::new(5.0),
+        }),
+        fill: None,
+        texture: None,
+        text: None,
+        snap_anchor: None,
+        layer: PathLayer::Other,
+    }
+}
+
+// Outlines of the bounding box of each legend's glyphs, used for debugging
+fn legend_bounds(legends: &[KeyPath]) -> Vec<KeyPath> {
+    legends
+        .iter()
+        .filter(|legend| !legend.data.is_empty())
+        .map(|legend| KeyPath {
+            data: legend.data.bounds.to_path(),
+            outline: Some(Outline {
+                color: Color::new(0.0, 0.0, 1.0),
+                width: Length::new(5.0),
+            }),
+            fill: None,
+            texture: None,
+            text: None,
+            snap_anchor: legend.snap_anchor,
+            layer: PathLayer::Other,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -109,6 +381,7 @@ mod tests {
 
         assert_is_close!(drawing.origin, key.position);
         assert_eq!(drawing.paths.len(), 6); // top, bottom, 4x legends
+        assert_eq!(drawing.name.as_deref(), Some("!"));
 
         // Stepped caps
         let key = {
@@ -169,4 +442,186 @@ mod tests {
             .inner_box(options.profile.text_margin.get(font_size));
         assert_is_close!(bounding_box, margin_rect);
     }
+
+    #[test]
+    fn test_key_drawing_is_close() {
+        let options = Options::default();
+        let drawing = KeyDrawing::new(&Key::example(), &options);
+        assert!(drawing.is_close(&drawing));
+
+        let mut other = drawing.clone();
+        other.index = Some(1);
+        assert!(!drawing.is_close(&other));
+
+        let mut other = drawing.clone();
+        other.bounds = other.bounds.translate(Vector::new(1.0, 0.0));
+        assert!(!drawing.is_close(&other));
+
+        let mut other = drawing.clone();
+        other.name = None;
+        assert!(!drawing.is_close(&other));
+
+        let mut other = drawing.clone();
+        other.homing = Some(::key::Homing::Bar);
+        assert!(!drawing.is_close(&other));
+
+        let path = &drawing.paths[0];
+        assert!(path.is_close(path));
+
+        let mut other = path.clone();
+        other.layer = PathLayer::Other;
+        assert!(!path.is_close(&other));
+
+        let mut other = path.clone();
+        other.texture = Some(TextureParams::default());
+        assert!(!path.is_close(&other));
+
+        let mut other = path.clone();
+        other.text = Some(Box::new([]));
+        assert!(!path.is_close(&other));
+    }
+
+    #[test]
+    fn test_key_drawing_new_legend_bounds() {
+        let key = Key::example();
+        let options = Options {
+            show_legend_bounds: true,
+            ..Options::default()
+        };
+        let drawing = KeyDrawing::new(&key, &options);
+
+        assert_eq!(drawing.paths.len(), 10); // top, bottom, 4x legends, 4x legend bounds
+        for (legend, bounds) in drawing.paths[2..6].iter().zip(&drawing.paths[6..]) {
+            assert_is_close!(bounds.data.bounds, legend.data.bounds);
+            assert!(bounds.fill.is_none());
+            assert_is_close!(bounds.outline.unwrap().color, Color::new(0.0, 0.0, 1.0));
+
+            // The bounds are snapped to pixels along with their legend
+            assert!(legend.snap_anchor.is_some());
+            assert_eq!(bounds.snap_anchor, legend.snap_anchor);
+        }
+    }
+
+    #[test]
+    fn test_key_drawing_new_legend_chip() {
+        let chip = ::key::ChipParams {
+            color: Color::new(1.0, 0.0, 0.0),
+            radius: Length::new(1000.0),
+            padding: Length::new(20.0),
+        };
+        let mut key = Key::example();
+        if let Some(ref mut legend) = key.legends[0] {
+            legend.chip = Some(chip);
+        }
+        let drawing = KeyDrawing::new(&key, &Options::default());
+
+        assert_eq!(drawing.paths.len(), 7); // top, bottom, chip, 4x legends
+        let (chip_path, legend) = (&drawing.paths[2], &drawing.paths[3]);
+        assert_is_close!(
+            chip_path.data.bounds,
+            legend.data.bounds.inflate(20.0, 20.0)
+        );
+        assert_is_close!(chip_path.fill.unwrap(), chip.color);
+        assert_eq!(chip_path.layer, PathLayer::Legend);
+        assert!(legend.snap_anchor.is_some());
+        assert_eq!(chip_path.snap_anchor, legend.snap_anchor);
+
+        // The radius is limited so a large radius gives a pill shape. Rounded rectangle paths
+        // start at the end of the top left corner
+        let rect = chip_path.data.bounds;
+        let radius = rect.width().min(rect.height()) / 2.0;
+        let start = Point::new(rect.min.x, rect.min.y + radius);
+        assert!(matches!(
+            chip_path.data.iter().next(),
+            Some(&geom::PathSegment::Move(p)) if p.is_close(start)
+        ));
+    }
+
+    #[test]
+    fn test_key_drawing_new_with_row() {
+        let row_top = profile::TopSurface {
+            y_offset: Length::new(-100.0),
+            dish_offset: Vector::new(0.0, 20.0),
+            ..profile::TopSurface::default()
+        };
+        let profile = profile::Profile {
+            rows: Some([row_top; profile::Profile::NUM_ROWS]),
+            ..profile::Profile::default()
+        };
+        let options = Options {
+            profile: &profile,
+            ..Options::default()
+        };
+
+        let key = Key::new();
+        let drawing = KeyDrawing::new(&key, &options);
+        assert_is_close!(
+            drawing.paths[1].data.bounds,
+            profile.top_with_size(Size::splat(1.0)).rect()
+        );
+
+        let key = Key {
+            row: Some(2),
+            ..Key::new()
+        };
+        let drawing = KeyDrawing::new(&key, &options);
+        assert_is_close!(
+            drawing.paths[1].data.bounds,
+            profile
+                .top_surface_with_size(profile.row_top(Some(2)), Size::splat(1.0))
+                .rect()
+        );
+
+        // Homing features are positioned using the row's dish offset
+        let key = Key {
+            shape: KeyShape::Homing(Some(::key::Homing::Bar)),
+            row: Some(2),
+            ..Key::new()
+        };
+        let drawing = KeyDrawing::new(&key, &options);
+        let bar_offset = Vector::new(0.0, profile.homing.bar.y_offset.get());
+        assert_is_close!(
+            drawing.paths[2].data.bounds.center(),
+            profile.dish_center_with_surface(profile.row_top(Some(2)), Size::splat(1.0))
+                + bar_offset
+        );
+    }
+
+    #[test]
+    fn test_key_drawing_new_space() {
+        let space_top = profile::TopSurface {
+            y_offset: Length::new(50.0),
+            ..profile::TopSurface::default()
+        };
+        let profile = profile::Profile {
+            space_top: Some(space_top),
+            ..profile::Profile::default()
+        };
+        let options = Options {
+            profile: &profile,
+            ..Options::default()
+        };
+        let size = Size::new(6.25, 1.0);
+
+        let key = Key {
+            shape: KeyShape::Space(size),
+            ..Key::new()
+        };
+        let drawing = KeyDrawing::new(&key, &options);
+        assert_is_close!(
+            drawing.paths[1].data.bounds,
+            profile.top_surface_with_size(&space_top, size).rect()
+        );
+
+        // Other keys of the same size use the regular top surface
+        let key = Key {
+            shape: KeyShape::Normal(size),
+            ..Key::new()
+        };
+        let drawing = KeyDrawing::new(&key, &options);
+        assert_is_close!(
+            drawing.paths[1].data.bounds,
+            profile.top_with_size(size).rect()
+        );
+    }
 }
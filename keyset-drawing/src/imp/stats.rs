@@ -0,0 +1,43 @@
+// Statistics recorded while drawing legends, which happens deep within drawing each key. These are
+// recorded per thread and read back by Drawing::new_profiled. Without the profiling feature these
+// functions do nothing, so there is no overhead
+
+#[cfg(feature = "profiling")]
+use std::cell::Cell;
+#[cfg(feature = "profiling")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "profiling")]
+thread_local! {
+    static LEGEND_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    static GLYPHS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[cfg(feature = "profiling")]
+pub fn time_legend<T>(f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    LEGEND_TIME.set(LEGEND_TIME.get() + start.elapsed());
+    result
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline]
+pub fn time_legend<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+#[cfg(feature = "profiling")]
+pub fn count_glyphs(count: usize) {
+    GLYPHS.set(GLYPHS.get() + count);
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline]
+pub const fn count_glyphs(_count: usize) {}
+
+// Returns the time spent drawing legends and the number of glyphs shaped, resetting both
+#[cfg(feature = "profiling")]
+pub fn take() -> (Duration, usize) {
+    (LEGEND_TIME.take(), GLYPHS.take())
+}
@@ -3,6 +3,8 @@
 //!
 //! [keyset]: https://crates.io/crates/keyset
 
+#[cfg(feature = "png")]
+mod ascii;
 mod error;
 mod imp;
 #[cfg(feature = "pdf")]
@@ -14,16 +16,22 @@ mod svg;
 #[cfg(not(any(feature = "pdf", feature = "png", feature = "svg")))]
 compile_error!("no output format is enabled");
 
+use std::borrow::Borrow;
 use std::fmt;
 
+use color::Color;
 use font::Font;
-use geom::{Dot, Length, Point, Rect, Size, Unit, DOT_PER_UNIT};
-use key::Key;
+use geom::{Angle, Dot, Homography, Length, Point, Rect, SideOffsets, Unit, DOT_PER_UNIT};
+use isclose::IsClose;
+use key::{Homing, Key};
+use log::warn;
 use profile::Profile;
 
 pub use error::Error;
+#[cfg(feature = "png")]
+pub use tiny_skia::Pixmap;
 
-pub(crate) use imp::{KeyDrawing, KeyPath};
+pub(crate) use imp::{KeyDrawing, KeyPath, PathLayer};
 
 /// A drawing
 #[derive(Debug, Clone)]
@@ -31,38 +39,425 @@ pub struct Drawing {
     bounds: Rect<Unit>,
     keys: Box<[KeyDrawing]>,
     scale: f32,
+    font: Option<Font>,
+    edge_keys: EdgeKeys,
 }
 
 impl Drawing {
     /// Create a new drawing using the given options
+    ///
+    /// The drawing's bounds always include the 1u square at the origin, so an empty layout
+    /// results in a blank 1u drawing and a logged warning. Use [`Drawing::try_new`] to treat this
+    /// as an error instead
+    ///
+    /// `keys` can be anything which can be viewed as a slice of keys, such as a [`key::Layout`]
+    #[must_use]
+    pub fn new(keys: impl AsRef<[Key]>, options: &Options<'_>) -> Self {
+        Self::from_key_iter(keys.as_ref(), options)
+    }
+
+    /// Create a new drawing using the given options, returning an error if there are no keys
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::EmptyLayout`] if `keys` is empty
+    #[inline]
+    pub fn try_new(keys: impl AsRef<[Key]>, options: &Options<'_>) -> Result<Self, Error> {
+        let keys = keys.as_ref();
+        if keys.is_empty() {
+            Err(Error::EmptyLayout)
+        } else {
+            Ok(Self::new(keys, options))
+        }
+    }
+
+    /// Create a new drawing from an iterator of keys using the given options
+    ///
+    /// This accepts any iterator yielding either owned keys or references to keys, which is useful
+    /// for drawing layouts which are built or filtered lazily
+    ///
+    /// Keys are drawn in order of their [`Key::z_order`]. Keys with equal z-order are drawn in the
+    /// order they are yielded by the iterator
+    ///
+    /// As with [`Drawing::new`], an empty iterator results in a blank 1u drawing. Use
+    /// [`Drawing::try_new`] to treat an empty layout as an error instead
+    #[must_use]
+    pub fn from_key_iter<K: Borrow<Key>>(
+        keys: impl IntoIterator<Item = K>,
+        options: &Options<'_>,
+    ) -> Self {
+        let (keys, rects) = Self::draw_keys(keys, options);
+        Self::finish(keys, &rects, options)
+    }
+
+    /// Create a new drawing using the given options, also returning statistics about how long
+    /// each phase of drawing took and how much was drawn
+    ///
+    /// This is intended for finding bottlenecks when drawing large layouts, and is only available
+    /// with the `profiling` feature
+    #[cfg(feature = "profiling")]
     #[must_use]
-    pub fn new(keys: &[Key], options: &Options<'_>) -> Self {
-        let bounds = keys
+    pub fn new_profiled(keys: impl AsRef<[Key]>, options: &Options<'_>) -> (Self, DrawStats) {
+        use std::time::Instant;
+
+        let keys = keys.as_ref();
+
+        let start = Instant::now();
+        let rects: Vec<_> = keys
             .iter()
-            .map(|k| k.shape.outer_rect().translate(k.position.to_vector()))
-            .fold(
-                Rect::from_origin_and_size(Point::origin(), Size::new(1.0, 1.0)),
-                |rect, key| Rect::new(rect.min.min(key.min), rect.max.max(key.max)),
-            );
+            .map(|key| Self::key_rect(key, options))
+            .collect();
+        let bounds_time = start.elapsed();
 
-        let keys = keys
+        // Discard anything recorded by previous drawings on this thread
+        let _ = imp::stats::take();
+        let start = Instant::now();
+        let drawings = keys
             .iter()
-            .map(|key| KeyDrawing::new(key, options))
+            .enumerate()
+            .map(|(index, key)| Self::draw_key(index, key, options))
             .collect();
+        let keys = Self::sort_keys(drawings);
+        let key_time = start.elapsed();
+        let (legend_time, legend_glyphs) = imp::stats::take();
+
+        let start = Instant::now();
+        let drawing = Self::finish(keys, &rects, options);
+        let finish_time = start.elapsed();
+        let (_, caption_glyphs) = imp::stats::take();
+
+        let paths = drawing.keys.iter().flat_map(|key| key.paths.iter());
+        let stats = DrawStats {
+            bounds_time,
+            key_time: key_time.saturating_sub(legend_time),
+            legend_time,
+            finish_time,
+            keys: drawing
+                .keys
+                .iter()
+                .filter(|key| key.index.is_some())
+                .count(),
+            paths: paths.clone().count(),
+            segments: paths.map(|path| path.data.len()).sum(),
+            glyphs: legend_glyphs + caption_glyphs,
+        };
+
+        (drawing, stats)
+    }
+
+    // The bounds of the layout always include at least a single unit at the origin
+    const MIN_BOUNDS: Rect<Unit> = Rect::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+
+    // Draw each key, returning the drawings sorted by z-order and each key's outer rectangle in
+    // layout order
+    fn draw_keys<K: Borrow<Key>>(
+        keys: impl IntoIterator<Item = K>,
+        options: &Options<'_>,
+    ) -> (Vec<KeyDrawing>, Vec<Rect<Unit>>) {
+        let (keys, rects): (Vec<_>, _) = keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let key = key.borrow();
+                (
+                    Self::draw_key(index, key, options),
+                    Self::key_rect(key, options),
+                )
+            })
+            .unzip();
+
+        (Self::sort_keys(keys), rects)
+    }
+
+    // The outer rectangle of the key in the layout
+    fn key_rect(key: &Key, options: &Options<'_>) -> Rect<Unit> {
+        key.shape.outer_rect().translate(key.position.to_vector())
+            * imp::pitch_scale(options.profile)
+    }
+
+    // Draw a key at the given index in the layout, returning it along with its z-order
+    fn draw_key(index: usize, key: &Key, options: &Options<'_>) -> (i32, KeyDrawing) {
+        let mut drawing = KeyDrawing::new(key, options);
+        drawing.index = Some(index);
+        (key.z_order, drawing)
+    }
+
+    // Sort drawn keys by z-order. This is a stable sort so keys with equal z-order keep their
+    // original order
+    fn sort_keys(mut keys: Vec<(i32, KeyDrawing)>) -> Vec<KeyDrawing> {
+        keys.sort_by_key(|&(z_order, _)| z_order);
+        keys.into_iter().map(|(_, key)| key).collect()
+    }
+
+    // Add the case and caption and apply any whole-drawing effects to the drawn keys, where rects
+    // are the keys' outer rectangles in layout order
+    fn finish(mut keys: Vec<KeyDrawing>, rects: &[Rect<Unit>], options: &Options<'_>) -> Self {
+        let mut bounds = options.bounds_override.map_or_else(
+            || {
+                if rects.is_empty() {
+                    warn!("drawing an empty layout; using the minimum 1u bounds");
+                }
+                rects.iter().fold(Self::MIN_BOUNDS, |bounds, rect| {
+                    Rect::new(bounds.min.min(rect.min), bounds.max.max(rect.max))
+                })
+            },
+            |bounds_override| bounds_override * imp::pitch_scale(options.profile),
+        );
+
+        // The case is drawn behind the keys, and around them but not the caption
+        if let Some(ref case) = options.case {
+            let drawing;
+            (drawing, bounds) = imp::case::draw(case, bounds);
+            keys.insert(0, drawing);
+        }
+
+        // The caption is text, so it's skipped without a font
+        if let (Some(caption), Some(font)) = (options.caption.as_ref(), options.font) {
+            let drawing;
+            (drawing, bounds) = imp::caption::draw(caption, font, bounds);
+            keys.push(drawing);
+        }
+
+        if let Some(perspective) = options.perspective {
+            perspective.apply(&mut keys, bounds);
+        }
+
+        let bounds = bounds.outer_box(options.padding);
+
+        if let Some(palette) = options.palette {
+            for key in &mut keys {
+                key.map_colors(|color| color.nearest_in(palette));
+            }
+        }
 
         Self {
             bounds,
-            keys,
+            keys: keys.into_boxed_slice(),
+            scale: options.scale,
+            font: options.font.cloned(),
+            edge_keys: EdgeKeys::from_rects(rects),
+        }
+    }
+
+    /// Create a new drawing containing only a single key
+    ///
+    /// The key is drawn at the origin regardless of its position, and the drawing's bounds are
+    /// set to the key's outer bounding rectangle plus any [`Options::padding`]. This is useful for
+    /// creating a tight image of a single keycap
+    #[must_use]
+    pub fn from_key(key: &Key, options: &Options<'_>) -> Self {
+        let mut key_drawing = KeyDrawing::new(key, options);
+        key_drawing.origin = Point::origin();
+        key_drawing.index = Some(0);
+        let key_rect = key.shape.outer_rect() * imp::pitch_scale(options.profile);
+        if let Some(perspective) = options.perspective {
+            perspective.apply(std::slice::from_mut(&mut key_drawing), key_rect);
+        }
+        let bounds = key_rect.outer_box(options.padding);
+        if let Some(palette) = options.palette {
+            key_drawing.map_colors(|color| color.nearest_in(palette));
+        }
+
+        Self {
+            bounds,
+            keys: Box::new([key_drawing]),
             scale: options.scale,
+            font: options.font.cloned(),
+            edge_keys: EdgeKeys::from_rects(&[key_rect]),
+        }
+    }
+
+    /// Change the colours of the drawing's keys in place without redrawing them
+    ///
+    /// `f` is called with the index of the key in the layout and each fill and outline colour
+    /// used to draw it, and returns the new colour. Only colours are changed; the geometry and
+    /// legends are untouched, and [`Options::palette`] is not reapplied. The caption is not
+    /// recoloured
+    pub fn recolor(&mut self, mut f: impl FnMut(usize, Color) -> Color) {
+        for key in &mut self.keys {
+            if let Some(index) = key.index {
+                key.map_colors(|color| f(index, color));
+            }
         }
     }
 
+    /// Get the index in the layout and the homing type used to draw each homing key
+    ///
+    /// For keys with [`key::Shape::Homing(None)`](key::Shape::Homing) this is the profile's
+    /// default homing type, as resolved by [`Profile::resolve_homing`]
+    #[must_use]
+    pub fn homing_keys(&self) -> Vec<(usize, Homing)> {
+        let mut homing_keys: Vec<_> = self
+            .keys
+            .iter()
+            .filter_map(|key| key.index.zip(key.homing))
+            .collect();
+        homing_keys.sort_by_key(|&(index, _)| index);
+        homing_keys
+    }
+
+    /// Get the indices in the layout of the keys which touch each edge of the layout's bounds
+    ///
+    /// The layout's bounds are the union of the keys' outer bounding rectangles, not including
+    /// the case, caption, or padding. This is useful for annotations or for debugging unexpected
+    /// bounds
+    #[inline]
+    #[must_use]
+    pub const fn edge_keys(&self) -> &EdgeKeys {
+        &self.edge_keys
+    }
+
     /// Encode the drawing as an SVG
     #[cfg(feature = "svg")]
     #[inline]
     #[must_use]
     pub fn to_svg(&self) -> String {
-        svg::draw(self)
+        self.to_svg_with_options(&SvgOptions::default())
+    }
+
+    /// Encode the drawing as an SVG using the given SVG-specific options
+    #[cfg(feature = "svg")]
+    #[inline]
+    #[must_use]
+    pub fn to_svg_with_options(&self, options: &SvgOptions) -> String {
+        svg::draw(self, *options)
+    }
+
+    /// Encode only the drawing's legends as an SVG with a transparent background, for example for
+    /// a separate legend plate when screen printing or to overlay onto a photo of a real keyboard
+    ///
+    /// The SVG has the same size and view box as [`Drawing::to_svg`], so it lines up exactly with
+    /// the full render and with [`Drawing::key_layer_svg`]. Anything other than legends is omitted.
+    /// Unlike rendering with [`Options::show_keys`] disabled, this doesn't require re-rendering the
+    /// drawing
+    #[cfg(feature = "svg")]
+    #[doc(alias = "legends_only_svg")]
+    #[inline]
+    #[must_use]
+    pub fn legend_layer_svg(&self) -> String {
+        self.layer(PathLayer::Legend).to_svg()
+    }
+
+    /// Encode only the drawing's key bodies as an SVG, without their legends
+    ///
+    /// Key bodies include homing features and dish shading, but not the case, caption, cutouts,
+    /// or debug outlines. The SVG lines up exactly with [`Drawing::legend_layer_svg`]
+    #[cfg(feature = "svg")]
+    #[inline]
+    #[must_use]
+    pub fn key_layer_svg(&self) -> String {
+        self.layer(PathLayer::Key).to_svg()
+    }
+
+    // A copy of the drawing containing only the paths in the given layer. The bounds are kept so
+    // each layer has the same coordinates as the full drawing
+    #[cfg(feature = "svg")]
+    fn layer(&self, layer: PathLayer) -> Self {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| KeyDrawing {
+                paths: key
+                    .paths
+                    .iter()
+                    .filter(|path| path.layer == layer)
+                    .cloned()
+                    .collect(),
+                ..key.clone()
+            })
+            .collect();
+
+        Self {
+            bounds: self.bounds,
+            keys,
+            scale: self.scale,
+            font: self.font.clone(),
+            edge_keys: self.edge_keys.clone(),
+        }
+    }
+
+    /// Encode the drawing as an SVG sprite sheet
+    ///
+    /// The sprite sheet contains one `<symbol>` element for each unique key in the drawing, with
+    /// ids `key-0`, `key-1`, etc. in order of first appearance. Each symbol is preceded by a
+    /// comment listing the indices of the keys it represents. This allows web applications to
+    /// build interactive keyboards by `<use>`-ing the symbols
+    #[cfg(feature = "svg")]
+    #[inline]
+    #[must_use]
+    pub fn to_svg_sprites(&self) -> String {
+        svg::draw_sprites(self)
+    }
+
+    /// Encode each key in the drawing as a separate SVG, for example to create an icon set
+    ///
+    /// Returns a `(filename, svg)` pair for each key, with each SVG cropped tightly to the key.
+    /// Filenames are derived from the key's first legend, falling back to `key-<index>.svg` (where
+    /// `<index>` is the key's index in the layout) if the key has no legend with any alphanumeric
+    /// characters. A number is appended to any duplicate filenames to keep them unique. Parts of
+    /// the drawing which aren't keys such as the case and caption are not exported
+    #[cfg(feature = "svg")]
+    #[must_use]
+    pub fn export_keys_svg(&self) -> Vec<(String, String)> {
+        let mut filenames = std::collections::HashSet::new();
+
+        self.keys
+            .iter()
+            .filter_map(|key| key.index.map(|index| (index, key)))
+            .map(|(index, key)| {
+                let stem = key
+                    .name
+                    .as_deref()
+                    .map(sanitise_filename)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("key-{index}"));
+
+                // Check against the final filenames rather than the stems, since a suffixed name
+                // can itself collide with another key's stem (e.g. "Caps Lock 2")
+                let mut filename = format!("{stem}.svg");
+                let mut count = 1_usize;
+                while filenames.contains(&filename) {
+                    count += 1;
+                    filename = format!("{stem}-{count}.svg");
+                }
+                filenames.insert(filename.clone());
+
+                let drawing = Self {
+                    bounds: key.bounds,
+                    keys: Box::new([KeyDrawing {
+                        origin: Point::origin(),
+                        ..key.clone()
+                    }]),
+                    scale: self.scale,
+                    font: None,
+                    edge_keys: EdgeKeys::from_rects(&[key.bounds]),
+                };
+
+                (filename, drawing.to_svg())
+            })
+            .collect()
+    }
+
+    /// Estimate the size in bytes of the drawing when encoded as an SVG, without encoding it
+    ///
+    /// This is a rough heuristic based on the number of path segments in the drawing, and the
+    /// actual size may differ significantly. It is intended for cheaply enforcing size limits
+    #[cfg(feature = "svg")]
+    #[inline]
+    #[must_use]
+    pub fn estimated_svg_bytes(&self) -> usize {
+        svg::estimate_size(self)
+    }
+
+    /// Estimate the size in bytes of the drawing when encoded as a PNG, without encoding it
+    ///
+    /// This is based on the size of the uncompressed pixel data, so will usually overestimate the
+    /// size of the final compressed PNG. It is intended for cheaply enforcing size limits
+    #[cfg(feature = "png")]
+    #[inline]
+    #[must_use]
+    pub fn estimated_png_bytes(&self, ppi: f32) -> usize {
+        png::estimate_size(self, geom::Scale::new(ppi))
     }
 
     /// Encode the drawing as a PNG
@@ -74,7 +469,48 @@ impl Drawing {
     #[cfg(feature = "png")]
     #[inline]
     pub fn to_png(&self, ppi: f32) -> Result<Vec<u8>, Error> {
-        png::draw(self, geom::Scale::new(ppi))
+        self.to_png_with_options(ppi, &PngOptions::default())
+    }
+
+    /// Encode the drawing as a PNG using the given PNG-specific options
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PngDimensionsError`] if the drawing is too large or too small to be
+    /// encoded as a PNG.
+    #[cfg(feature = "png")]
+    #[inline]
+    pub fn to_png_with_options(
+        &self,
+        ppi: f32,
+        options: &PngOptions<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        png::draw(self, geom::Scale::new(ppi), *options)
+    }
+
+    /// Encode the drawing as a PNG suitable for printing, i.e. at [`Ppi::PRINT`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::PngDimensionsError`] if the drawing is too large or too small to be
+    /// encoded as a PNG.
+    #[cfg(feature = "png")]
+    #[inline]
+    pub fn to_png_print(&self) -> Result<Vec<u8>, Error> {
+        self.to_png(Ppi::PRINT)
+    }
+
+    /// Render a coarse preview of the drawing as text using Unicode braille characters, for
+    /// example for displaying in a terminal
+    ///
+    /// The preview is `width` characters wide, with each character representing a 2×4 grid of
+    /// pixels which are set wherever a key or legend is drawn. Each line ends with a newline. An
+    /// empty string is returned if the drawing cannot be rendered at the requested size
+    #[cfg(feature = "png")]
+    #[inline]
+    #[must_use]
+    pub fn to_ascii(&self, width: usize) -> String {
+        ascii::draw(self, width)
     }
 
     /// Encode the drawing as a PDF
@@ -82,7 +518,15 @@ impl Drawing {
     #[inline]
     #[must_use]
     pub fn to_pdf(&self) -> Vec<u8> {
-        pdf::draw(self)
+        self.to_pdf_with_options(&PdfOptions::default())
+    }
+
+    /// Encode the drawing as a PDF using the given PDF-specific options
+    #[cfg(feature = "pdf")]
+    #[inline]
+    #[must_use]
+    pub fn to_pdf_with_options(&self, options: &PdfOptions) -> Vec<u8> {
+        pdf::draw(self, *options)
     }
 
     /// Encode the drawing as an Illustrator file
@@ -104,28 +548,83 @@ impl Drawing {
     #[inline]
     #[must_use]
     pub fn to_ai(&self) -> Vec<u8> {
-        pdf::draw(self)
+        pdf::draw(self, PdfOptions::default())
     }
 }
 
-#[derive(Clone, Copy)]
-struct NonExhaustive;
+// Convert a key's name into a lowercase filename stem, replacing runs of any characters other than
+// ASCII letters and digits with a single '-'
+#[cfg(feature = "svg")]
+fn sanitise_filename(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
 
-/// Options for generating a drawing
-#[derive(Clone)]
-pub struct Options<'a> {
-    /// The keycap profile used for drawing keys
-    pub profile: &'a Profile,
-    /// The font used for drawing legends
-    pub font: &'a Font,
-    /// The scale used for the drawing
-    pub scale: f32,
-    /// The outline width for drawing key edges
-    pub outline_width: Length<Dot>,
-    /// Whether to show the keys in the drawing. Does not affect legends
-    pub show_keys: bool,
-    /// Show the margin used for legend alignment. Useful for debug purposes
-    pub show_margin: bool,
+impl IsClose<f32> for Drawing {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    /// Compares the drawings' bounds, scale, and edge keys, and the paths and properties of each
+    /// key. Path coordinates are in units of 1/1000 of a key unit, so `abs_tol` should be scaled
+    /// accordingly
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.bounds.is_close_tol(other.bounds, rel_tol, abs_tol)
+            && self.scale.is_close_tol(other.scale, rel_tol, abs_tol)
+            && self.edge_keys == other.edge_keys
+            && self.keys.len() == other.keys.len()
+            && self
+                .keys
+                .iter()
+                .zip(other.keys.iter())
+                .all(|(s, o)| s.is_close_tol(o, rel_tol, abs_tol))
+    }
+}
+
+/// How legend characters which are missing from the font are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingGlyphPolicy {
+    /// Draw the font's `.notdef` glyph, usually a box
+    #[default]
+    Notdef,
+    /// Omit the whole legend if any of its characters are missing
+    Blank,
+    /// Draw the given character instead. The `.notdef` glyph is drawn if the font does not
+    /// contain this character either
+    Replace(char),
+}
+
+/// The position of a [`Caption`] relative to the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptionPosition {
+    /// Draw the caption above the layout
+    Above,
+    /// Draw the caption below the layout
+    Below,
+}
+
+/// The indices in the layout of the keys touching each edge of the layout's bounds, as returned
+/// by [`Drawing::edge_keys`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct EdgeKeys {
+    /// The keys touching the top edge
+    pub top: Vec<usize>,
+    /// The keys touching the bottom edge
+    pub bottom: Vec<usize>,
+    /// The keys touching the left edge
+    pub left: Vec<usize>,
+    /// The keys touching the right edge
+    pub right: Vec<usize>,
     /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
     /// `..Default::default()` functional update syntax
     #[allow(private_interfaces)]
@@ -133,30 +632,53 @@ pub struct Options<'a> {
     pub __non_exhaustive: NonExhaustive,
 }
 
-impl Default for Options<'_> {
+impl EdgeKeys {
+    // Find the keys touching each edge of the union of the given key rectangles, where each
+    // rectangle's index is the index of the key in the layout
+    fn from_rects(rects: &[Rect<Unit>]) -> Self {
+        let Some(bounds) = rects.iter().copied().reduce(|a, b| a.union(&b)) else {
+            return Self::default();
+        };
+
+        let touching = |edge: fn(&Rect<Unit>) -> f32| {
+            rects
+                .iter()
+                .enumerate()
+                .filter(|&(_, rect)| edge(rect).is_close(edge(&bounds)))
+                .map(|(index, _)| index)
+                .collect()
+        };
+
+        Self {
+            top: touching(|rect| rect.min.y),
+            bottom: touching(|rect| rect.max.y),
+            left: touching(|rect| rect.min.x),
+            right: touching(|rect| rect.max.x),
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+impl Default for EdgeKeys {
     #[inline]
     fn default() -> Self {
         Self {
-            profile: Profile::default_ref(),
-            font: Font::default_ref(),
-            scale: 1.0,
-            outline_width: Length::new(0.01) * DOT_PER_UNIT,
-            show_keys: true,
-            show_margin: false,
+            top: Vec::new(),
+            bottom: Vec::new(),
+            left: Vec::new(),
+            right: Vec::new(),
             __non_exhaustive: NonExhaustive,
         }
     }
 }
 
-impl fmt::Debug for Options<'_> {
+impl fmt::Debug for EdgeKeys {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut dbg = f.debug_struct("Options");
-        dbg.field("profile", &self.profile)
-            .field("font", &self.font)
-            .field("scale", &self.scale)
-            .field("outline_width", &self.outline_width)
-            .field("show_keys", &self.show_keys)
-            .field("show_margin", &self.show_margin);
+        let mut dbg = f.debug_struct("EdgeKeys");
+        dbg.field("top", &self.top)
+            .field("bottom", &self.bottom)
+            .field("left", &self.left)
+            .field("right", &self.right);
 
         #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
         dbg.field("__non_exhaustive", &"NonExhaustive");
@@ -165,56 +687,687 @@ impl fmt::Debug for Options<'_> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use geom::{Mm, DOT_PER_MM};
-    use isclose::assert_is_close;
-    use profile::Profile;
+/// Statistics about drawing a layout, as returned by [`Drawing::new_profiled`]
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrawStats {
+    /// Time taken to calculate the bounds of the layout
+    pub bounds_time: std::time::Duration,
+    /// Time taken to generate the paths for each key, excluding their legends
+    pub key_time: std::time::Duration,
+    /// Time taken to shape and render the keys' legends
+    pub legend_time: std::time::Duration,
+    /// Time taken to draw the case and caption and apply whole-drawing effects such as the
+    /// perspective and palette
+    pub finish_time: std::time::Duration,
+    /// The number of keys drawn
+    pub keys: usize,
+    /// The number of paths drawn, including the case and caption
+    pub paths: usize,
+    /// The total number of path segments across all paths
+    pub segments: usize,
+    /// The number of glyphs shaped for legends and the caption
+    pub glyphs: usize,
+}
 
-    use super::*;
+/// A caption drawn alongside the layout, for example the keyboard's name or a URL
+#[derive(Clone)]
+pub struct Caption {
+    /// The caption text
+    pub text: String,
+    /// The height of an uppercase letter in the caption
+    pub font_size: Length<Dot>,
+    /// The caption colour
+    pub color: Color,
+    /// Where the caption is drawn relative to the layout
+    pub position: CaptionPosition,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
 
-    #[test]
-    fn options() {
-        let options = Options::default();
+impl Default for Caption {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            font_size: Length::new(50.0),
+            color: Color::new(0.0, 0.0, 0.0),
+            position: CaptionPosition::Below,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
 
-        assert_is_close!(options.scale, 1.0);
-        assert_eq!(options.font.num_glyphs(), 1); // .notdef
+impl fmt::Debug for Caption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Caption");
+        dbg.field("text", &self.text)
+            .field("font_size", &self.font_size)
+            .field("color", &self.color)
+            .field("position", &self.position);
 
-        let profile = Profile::default();
-        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
-        let options = Options {
-            profile: &profile,
-            font: &font,
-            scale: 2.0,
-            outline_width: Length::new(20.0),
-            show_keys: false,
-            show_margin: true,
-            ..Options::default()
-        };
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
 
-        assert_is_close!(
-            options.profile.typ.depth(),
-            Length::<Mm>::new(1.0) * DOT_PER_MM
-        );
-        assert_eq!(options.font.num_glyphs(), 3); // .notdef, A, V
-        assert_is_close!(options.scale, 2.0);
+        dbg.finish()
     }
+}
 
-    #[test]
-    fn options_debug() {
-        let options = Options::default();
+/// A trapezoidal perspective transformation used to fake a three-quarter view of the layout
+///
+/// The bottom edge of the drawing is kept in place while the top edge is narrowed and lowered,
+/// making the layout appear to recede into the distance. Legends remain readable but are slightly
+/// distorted, and curves are approximated
+#[derive(Clone, Copy)]
+pub struct Perspective {
+    /// The width of the top edge as a fraction of the width of the bottom edge
+    pub top_width: f32,
+    /// The apparent height of the drawing as a fraction of its actual height
+    pub height: f32,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+impl Default for Perspective {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            top_width: 0.8,
+            height: 0.9,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+impl fmt::Debug for Perspective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Perspective");
+        dbg.field("top_width", &self.top_width)
+            .field("height", &self.height);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+impl Perspective {
+    fn apply(self, keys: &mut [KeyDrawing], bounds: Rect<Unit>) {
+        let bounds = bounds * DOT_PER_UNIT;
+        let inset = bounds.width() * (1.0 - self.top_width) / 2.0;
+        let top = bounds.max.y - bounds.height() * self.height;
+        let quad = [
+            Point::new(bounds.min.x + inset, top),
+            Point::new(bounds.max.x - inset, top),
+            bounds.max,
+            Point::new(bounds.min.x, bounds.max.y),
+        ];
+
+        // A degenerate perspective would collapse the drawing, so leave it as is
+        if let Some(homography) = Homography::from_rect_to_quad(bounds, quad) {
+            for key in keys {
+                key.project(&homography);
+            }
+        }
+    }
+}
+
+/// Parameters for the keyboard case drawn when [`Options::case`] is set
+#[derive(Clone, Copy, PartialEq)]
+pub struct CaseParams {
+    /// The distance between the edge of the layout and the outside of the case
+    pub margin: Length<Dot>,
+    /// The corner radius of the outside of the case
+    pub radius: Length<Dot>,
+    /// The case colour
+    pub color: Color,
+    /// The width of the case's bezel, i.e. the rim around the recessed area holding the keys. The
+    /// recessed area is drawn in a darker shade of the case colour. Zero disables the bezel, and
+    /// the bezel should be no wider than [`CaseParams::margin`]
+    pub bezel: Length<Dot>,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+impl Default for CaseParams {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            margin: Length::new(250.0),
+            radius: Length::new(125.0),
+            color: Color::new(0.2, 0.2, 0.2),
+            bezel: Length::new(0.0),
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+impl fmt::Debug for CaseParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("CaseParams");
+        dbg.field("margin", &self.margin)
+            .field("radius", &self.radius)
+            .field("color", &self.color)
+            .field("bezel", &self.bezel);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+/// Parameters for the texture drawn over key tops when [`Options::key_texture`] is set
+#[derive(Clone, Copy, PartialEq)]
+pub struct TextureParams {
+    /// How strongly the texture contrasts with the key colour, in the range `0.0..1.0`
+    pub strength: f32,
+    /// The size of the texture's features, i.e. the size of each noise cell in PNG output or the
+    /// spacing of the hatch lines in SVG output
+    pub scale: Length<Dot>,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+impl Default for TextureParams {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            strength: 0.05,
+            scale: Length::new(10.0),
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+impl fmt::Debug for TextureParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("TextureParams");
+        dbg.field("strength", &self.strength)
+            .field("scale", &self.scale);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+impl IsClose<f32> for TextureParams {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.strength.is_close_tol(other.strength, rel_tol, abs_tol)
+            && self.scale.is_close_tol(other.scale, rel_tol, abs_tol)
+    }
+}
+
+/// Common pixels per inch values for use with [`Drawing::to_png`]
+#[cfg(feature = "png")]
+#[derive(Debug, Clone, Copy)]
+pub struct Ppi;
+
+#[cfg(feature = "png")]
+impl Ppi {
+    /// The conventional resolution of a screen
+    pub const SCREEN: f32 = 96.0;
+    /// A typical resolution for printing
+    pub const PRINT: f32 = 300.0;
+}
+
+/// Options for encoding a drawing as an SVG using [`Drawing::to_svg_with_options`]
+#[cfg(feature = "svg")]
+#[derive(Clone, Copy)]
+pub struct SvgOptions {
+    /// Output coordinates with the origin at the bottom left of the drawing and the y-axis
+    /// pointing up, as expected by some CAD and print tools. Only the coordinate system changes;
+    /// a root transform flips the y-axis back so the drawing looks the same
+    pub y_up: bool,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+#[cfg(feature = "svg")]
+impl Default for SvgOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            y_up: false,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+impl fmt::Debug for SvgOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("SvgOptions");
+        dbg.field("y_up", &self.y_up);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+/// Options for encoding a drawing as a PNG using [`Drawing::to_png_with_options`]
+#[cfg(feature = "png")]
+#[derive(Clone, Copy)]
+pub struct PngOptions<'a> {
+    /// Move legends so they start on a whole pixel, which makes small legends look sharper at the
+    /// cost of exact positioning. Each legend's chip and bounds outline move by the same offset as
+    /// the legend. The caption is also snapped. Legends are not snapped in drawings with a
+    /// [`Perspective`]
+    pub snap_legends: bool,
+    /// An image drawn behind the keys, for example a logo or watermark. The image is scaled
+    /// uniformly to fit within the canvas, preserving its aspect ratio, and centred. Transparent
+    /// parts of the image remain transparent
+    pub background_image: Option<&'a Pixmap>,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+#[cfg(feature = "png")]
+impl Default for PngOptions<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            snap_legends: false,
+            background_image: None,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+impl fmt::Debug for PngOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("PngOptions");
+        dbg.field("snap_legends", &self.snap_legends)
+            .field("background_image", &self.background_image);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+/// Options for encoding a drawing as a PDF using [`Drawing::to_pdf_with_options`]
+#[cfg(feature = "pdf")]
+#[derive(Clone, Copy)]
+pub struct PdfOptions {
+    /// Draw legends as outlines rather than as text. Drawing legends as real text keeps them
+    /// selectable and searchable, and embeds a subset of the font containing only the glyphs used
+    /// in the drawing. Legends are always drawn as outlines if the font has CFF outlines or can't
+    /// be subset, or for drawings with a [`Perspective`]
+    pub text_as_outlines: bool,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+#[cfg(feature = "pdf")]
+impl Default for PdfOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            text_as_outlines: true,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl fmt::Debug for PdfOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("PdfOptions");
+        dbg.field("text_as_outlines", &self.text_as_outlines);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct NonExhaustive;
+
+/// Options for generating a drawing
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)] // These are independent toggles, not a state machine
+pub struct Options<'a> {
+    /// The keycap profile used for drawing keys
+    pub profile: &'a Profile,
+    /// The font used for drawing legends and the caption
+    ///
+    /// A legend's [`path`](key::Legend::path) takes precedence over the font, so legends with a
+    /// pre-rendered path are drawn even without a font. If [`None`], legends without a path and
+    /// the caption are skipped
+    pub font: Option<&'a Font>,
+    /// The scale used for the drawing
+    pub scale: f32,
+    /// The outline width for drawing key edges
+    pub outline_width: Length<Dot>,
+    /// Use a stronger outline for keys which would otherwise be hard to distinguish from a white
+    /// background. Keys with enough contrast against the background use the usual outline colour
+    pub auto_outline_contrast: bool,
+    /// Whether to show the keys in the drawing. Does not affect legends
+    pub show_keys: bool,
+    /// Show the margin used for legend alignment. Useful for debug purposes
+    pub show_margin: bool,
+    /// Show the bounding box of each legend's rendered glyphs. Useful for debugging legend
+    /// alignment and clipping
+    pub show_legend_bounds: bool,
+    /// Show homing features such as bars and bumps. Homing keys are still drawn as normal keys
+    /// when this is disabled
+    pub show_homing_features: bool,
+    /// Shade the dishes of scooped homing keys with a radial gradient to suggest their depth. The
+    /// shading is stronger for deeper scoops. Has no effect unless `show_homing_features` is set
+    pub shade_dishes: bool,
+    /// Show the position of MX-style switch cutouts for each key, useful for previewing plates
+    pub show_switch_cutouts: bool,
+    /// Show the approximate position of stabilizer cutouts for keys 2u or larger, useful for
+    /// previewing plates
+    pub show_stab_cutouts: bool,
+    /// The radius of concave corners where the parts of ISO enter and stepped keys join. If
+    /// [`None`] the corner radius of the profile is used. Use zero for a sharp corner
+    pub inner_corner_radius: Option<Length<Dot>>,
+    /// Align the baselines of centre legends to a shared baseline calculated from the profile,
+    /// rather than centring each legend individually. This keeps legends of different sizes
+    /// aligned across keys
+    pub legend_baseline_grid: bool,
+    /// Padding added between each of a key's nine legend slots. When non-zero, each legend is
+    /// limited to its third of the legend margin, and the sides of each slot which face a
+    /// neighbouring slot are inset by this amount, so wide legends are squished further rather
+    /// than overlapping adjacent legends. Zero leaves legends free to use the whole legend margin
+    pub legend_slot_padding: Length<Dot>,
+    /// Shrink legends which are too large for their slot until they fit, down to half of their
+    /// original size, instead of squishing them horizontally. Legends which still don't fit at
+    /// half size are squished as usual
+    pub autosize_legends: bool,
+    /// Amount to synthetically embolden legends by, useful when only a regular weight of the font
+    /// is available. A real bold font will give better results; zero disables synthetic bold
+    pub synthetic_bold: Length<Dot>,
+    /// Angle to synthetically slant legends by, useful when only an upright font is available. A
+    /// real italic or oblique font will give better results; zero disables synthetic oblique
+    pub synthetic_oblique: Angle,
+    /// How to draw legend characters which are missing from [`Options::font`]
+    pub missing_glyph: MissingGlyphPolicy,
+    /// An optional texture drawn over the top surface of each key for less flat looking renders.
+    /// This is drawn as procedural noise in PNG output and as a hatch pattern in SVG output, and
+    /// is ignored for PDF output. The texture's colours are derived from the key colour
+    pub key_texture: Option<TextureParams>,
+    /// An optional palette to restrict the drawing's colours to. If set, every colour in the
+    /// drawing, including outlines and highlights, is replaced with the nearest colour in the
+    /// palette as given by [`Color::nearest_in`]
+    pub palette: Option<&'a [Color]>,
+    /// An optional perspective transformation applied to the whole drawing, including the caption
+    pub perspective: Option<Perspective>,
+    /// An optional keyboard case drawn behind and around the keys. The drawing's bounds are
+    /// extended to fit the case. Ignored by [`Drawing::from_key`]
+    pub case: Option<CaseParams>,
+    /// An optional fixed area in key units used as the drawing's bounds instead of the extent of
+    /// the keys, for example to give a set of related diagrams the same size. Keys outside the
+    /// area extend beyond the drawing's bounds and may be clipped. Any case, caption, and padding
+    /// are still added around it. Ignored by [`Drawing::from_key`]
+    pub bounds_override: Option<Rect<Unit>>,
+    /// Padding added around the drawing's bounds. This is included in the size of the output
+    /// image or page
+    pub padding: SideOffsets<Unit>,
+    /// An optional caption drawn using [`Options::font`]. The drawing's bounds are extended to
+    /// fit the caption
+    pub caption: Option<Caption>,
+    /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
+    /// `..Default::default()` functional update syntax
+    #[allow(private_interfaces)]
+    #[doc(hidden)]
+    pub __non_exhaustive: NonExhaustive,
+}
+
+impl Default for Options<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            profile: Profile::default_ref(),
+            font: Some(Font::default_ref()),
+            scale: 1.0,
+            outline_width: Length::new(0.01) * DOT_PER_UNIT,
+            auto_outline_contrast: false,
+            show_keys: true,
+            show_margin: false,
+            show_legend_bounds: false,
+            show_homing_features: true,
+            shade_dishes: false,
+            show_switch_cutouts: false,
+            show_stab_cutouts: false,
+            inner_corner_radius: None,
+            legend_baseline_grid: false,
+            legend_slot_padding: Length::new(0.0),
+            autosize_legends: false,
+            synthetic_bold: Length::new(0.0),
+            synthetic_oblique: Angle::zero(),
+            missing_glyph: MissingGlyphPolicy::Notdef,
+            key_texture: None,
+            palette: None,
+            perspective: None,
+            case: None,
+            bounds_override: None,
+            padding: SideOffsets::zero(),
+            caption: None,
+            __non_exhaustive: NonExhaustive,
+        }
+    }
+}
+
+impl<'a> Options<'a> {
+    /// Use a different profile, keeping all other options
+    #[inline]
+    #[must_use]
+    pub fn with_profile(self, profile: &'a Profile) -> Self {
+        Self { profile, ..self }
+    }
+
+    /// Use a different font, keeping all other options
+    #[inline]
+    #[must_use]
+    pub fn with_font(self, font: &'a Font) -> Self {
+        Self {
+            font: Some(font),
+            ..self
+        }
+    }
+
+    /// Set the drawing's scale, keeping all other options
+    #[inline]
+    #[must_use]
+    pub fn with_scale(self, scale: f32) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Set the drawing's scale as a percentage, e.g. `50.0` to draw at half size
+    #[inline]
+    #[must_use]
+    pub fn at_scale_percent(self, percent: f32) -> Self {
+        Self {
+            scale: percent / 100.0,
+            ..self
+        }
+    }
+}
+
+impl fmt::Debug for Options<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dbg = f.debug_struct("Options");
+        dbg.field("profile", &self.profile)
+            .field("font", &self.font)
+            .field("scale", &self.scale)
+            .field("outline_width", &self.outline_width)
+            .field("auto_outline_contrast", &self.auto_outline_contrast)
+            .field("show_keys", &self.show_keys)
+            .field("show_margin", &self.show_margin)
+            .field("show_legend_bounds", &self.show_legend_bounds)
+            .field("show_homing_features", &self.show_homing_features)
+            .field("shade_dishes", &self.shade_dishes)
+            .field("show_switch_cutouts", &self.show_switch_cutouts)
+            .field("show_stab_cutouts", &self.show_stab_cutouts)
+            .field("inner_corner_radius", &self.inner_corner_radius)
+            .field("legend_baseline_grid", &self.legend_baseline_grid)
+            .field("legend_slot_padding", &self.legend_slot_padding)
+            .field("autosize_legends", &self.autosize_legends)
+            .field("synthetic_bold", &self.synthetic_bold)
+            .field("synthetic_oblique", &self.synthetic_oblique)
+            .field("missing_glyph", &self.missing_glyph)
+            .field("key_texture", &self.key_texture)
+            .field("palette", &self.palette)
+            .field("perspective", &self.perspective)
+            .field("case", &self.case)
+            .field("bounds_override", &self.bounds_override)
+            .field("padding", &self.padding)
+            .field("caption", &self.caption);
+
+        #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
+        dbg.field("__non_exhaustive", &"NonExhaustive");
+
+        dbg.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geom::{Mm, Size, DOT_PER_MM};
+    use isclose::assert_is_close;
+    use profile::Profile;
+
+    use super::*;
+
+    #[test]
+    fn options() {
+        let options = Options::default();
+
+        assert_is_close!(options.scale, 1.0);
+        assert_eq!(options.font.unwrap().num_glyphs(), 1); // .notdef
+
+        let profile = Profile::default();
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let options = Options {
+            profile: &profile,
+            font: Some(&font),
+            scale: 2.0,
+            outline_width: Length::new(20.0),
+            show_keys: false,
+            show_margin: true,
+            ..Options::default()
+        };
+
+        assert_is_close!(
+            options.profile.typ.depth(),
+            Length::<Mm>::new(1.0) * DOT_PER_MM
+        );
+        assert_eq!(options.font.unwrap().num_glyphs(), 3); // .notdef, A, V
+        assert_is_close!(options.scale, 2.0);
+    }
+
+    #[test]
+    fn options_with() {
+        let profile = Profile::default();
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let options = Options {
+            show_margin: true,
+            ..Options::default()
+        };
+
+        let options = options
+            .with_profile(&profile)
+            .with_font(&font)
+            .with_scale(2.0);
+
+        assert!(std::ptr::eq(options.profile, &profile));
+        assert!(std::ptr::eq(options.font.unwrap(), &font));
+        assert_is_close!(options.scale, 2.0);
+        assert!(options.show_margin);
+    }
+
+    #[test]
+    fn options_at_scale_percent() {
+        let options = Options::default().at_scale_percent(50.0);
+
+        assert_is_close!(options.scale, 0.5);
+    }
+
+    #[test]
+    fn options_debug() {
+        let options = Options::default();
 
         assert_eq!(
             format!("{options:?}"),
             format!(
                 "Options {{ profile: {:?}, font: {:?}, scale: {:?}, outline_width: {:?}, \
-                    show_keys: {:?}, show_margin: {:?} }}",
+                    auto_outline_contrast: {:?}, show_keys: {:?}, show_margin: {:?}, \
+                    show_legend_bounds: {:?}, show_homing_features: {:?}, shade_dishes: {:?}, \
+                    show_switch_cutouts: {:?}, show_stab_cutouts: {:?}, inner_corner_radius: {:?}, \
+                    legend_baseline_grid: {:?}, legend_slot_padding: {:?}, autosize_legends: {:?}, \
+                    synthetic_bold: {:?}, synthetic_oblique: {:?}, missing_glyph: {:?}, \
+                    key_texture: {:?}, palette: {:?}, perspective: {:?}, case: {:?}, \
+                    bounds_override: {:?}, padding: {:?}, caption: {:?} }}",
                 Profile::default_ref(),
-                Font::default_ref(),
+                Some(Font::default_ref()),
                 1.0,
                 10.0,
+                false,
                 true,
-                false
+                false,
+                false,
+                true,
+                false,
+                false,
+                false,
+                None::<Length<Dot>>,
+                false,
+                Length::<Dot>::new(0.0),
+                false,
+                0.0,
+                Angle::zero(),
+                MissingGlyphPolicy::Notdef,
+                None::<TextureParams>,
+                None::<&[Color]>,
+                None::<Perspective>,
+                None::<CaseParams>,
+                None::<Rect<Unit>>,
+                SideOffsets::<Unit>::zero(),
+                None::<Caption>,
             ),
         );
     }
@@ -231,4 +1384,580 @@ mod tests {
         assert_eq!(drawing.keys.len(), 1);
         assert_is_close!(drawing.scale, options.scale);
     }
+
+    #[test]
+    fn drawing_from_key() {
+        let options = Options::default();
+        let key = {
+            let mut key = Key::example();
+            key.position = Point::new(3.0, 2.0);
+            key.shape = ::key::Shape::SteppedCaps;
+            key
+        };
+
+        let drawing = Drawing::from_key(&key, &options);
+
+        assert_is_close!(drawing.bounds, key.shape.outer_rect());
+        assert_eq!(drawing.keys.len(), 1);
+        assert_is_close!(drawing.keys[0].origin, Point::origin());
+    }
+
+    #[test]
+    fn drawing_recolor() {
+        let keys = [
+            Key {
+                z_order: 1,
+                ..Key::example()
+            },
+            Key::example(),
+        ];
+        let options = Options {
+            caption: Some(Caption {
+                text: "keyset".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Below,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+        let mut drawing = Drawing::new(&keys, &options);
+        let bounds = drawing.keys[0].paths[0].data.bounds;
+
+        let red = Color::new(1.0, 0.0, 0.0);
+        drawing.recolor(|index, color| if index == 0 { red } else { color });
+
+        // Keys are sorted by z-order, so the first key in the layout is drawn second
+        let fills: Vec<_> = drawing.keys[1]
+            .paths
+            .iter()
+            .filter_map(|p| p.fill)
+            .collect();
+        assert!(!fills.is_empty());
+        assert!(fills.iter().all(|&fill| fill.is_close(red)));
+        assert!(drawing.keys[0]
+            .paths
+            .iter()
+            .filter_map(|p| p.fill)
+            .all(|fill| !fill.is_close(red)));
+        assert!(drawing.keys[2].paths[0]
+            .fill
+            .unwrap()
+            .is_close(Color::new(0.0, 0.0, 0.0)));
+        assert_is_close!(drawing.keys[0].paths[0].data.bounds, bounds);
+    }
+
+    #[test]
+    fn drawing_homing_keys() {
+        let keys = [
+            Key {
+                shape: key::Shape::Homing(None),
+                ..Key::example()
+            },
+            Key::example(),
+            Key {
+                shape: key::Shape::Homing(Some(Homing::Bump)),
+                ..Key::example()
+            },
+        ];
+
+        for default in [Homing::Scoop, Homing::Bar, Homing::Bump] {
+            let mut profile = Profile::default();
+            profile.homing.default = default;
+            let options = Options {
+                profile: &profile,
+                ..Options::default()
+            };
+
+            let drawing = Drawing::new(&keys, &options);
+            assert_eq!(drawing.homing_keys(), [(0, default), (2, Homing::Bump)]);
+        }
+    }
+
+    #[test]
+    fn drawing_edge_keys() {
+        let keys = [
+            Key {
+                position: Point::new(0.0, 0.0),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(1.0, 0.0),
+                shape: key::Shape::Normal(Size::new(1.0, 2.0)),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(0.0, 1.0),
+                ..Key::example()
+            },
+        ];
+        let options = Options {
+            caption: Some(Caption {
+                text: "Caption".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Above,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+
+        let drawing = Drawing::new(&keys, &options);
+        assert_eq!(
+            *drawing.edge_keys(),
+            EdgeKeys {
+                top: vec![0, 1],
+                bottom: vec![1, 2],
+                left: vec![0, 2],
+                right: vec![1],
+                ..EdgeKeys::default()
+            }
+        );
+
+        let drawing = Drawing::new([], &options);
+        assert_eq!(*drawing.edge_keys(), EdgeKeys::default());
+
+        let drawing = Drawing::from_key(&keys[1], &options);
+        assert_eq!(
+            *drawing.edge_keys(),
+            EdgeKeys {
+                top: vec![0],
+                bottom: vec![0],
+                left: vec![0],
+                right: vec![0],
+                ..EdgeKeys::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn drawing_new_profiled() {
+        let options = Options::default();
+        let keys = [Key::example(), Key::new()];
+
+        let (drawing, stats) = Drawing::new_profiled(&keys, &options);
+
+        assert_is_close!(drawing, Drawing::new(&keys, &options));
+        assert_eq!(stats.keys, 2);
+        assert_eq!(stats.paths, 8); // 2x top & bottom, 4 legends on the example key
+        assert!(stats.segments > stats.paths);
+        assert_eq!(stats.glyphs, 4);
+
+        // Glyphs are counted when shaped, so are still counted when the text is projected to paths
+        let options = Options {
+            perspective: Some(Perspective {
+                top_width: 0.5,
+                height: 0.5,
+                ..Perspective::default()
+            }),
+            ..options
+        };
+        let (_, stats) = Drawing::new_profiled(&keys, &options);
+        assert_eq!(stats.glyphs, 4);
+    }
+
+    #[test]
+    fn drawing_try_new() {
+        let options = Options::default();
+
+        let drawing = Drawing::try_new(&[Key::example()], &options).unwrap();
+        assert_eq!(drawing.keys.len(), 1);
+
+        let error = Drawing::try_new(&[], &options).unwrap_err();
+        assert!(matches!(error, Error::EmptyLayout));
+    }
+
+    #[test]
+    fn drawing_from_key_iter() {
+        let options = Options::default();
+        let keys = [
+            Key::example(),
+            Key {
+                position: Point::new(2.0, 1.0),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(4.0, 2.0),
+                ..Key::example()
+            },
+        ];
+
+        let drawing = Drawing::from_key_iter(keys.iter().filter(|k| k.position.x < 3.0), &options);
+        assert_eq!(drawing.keys.len(), 2);
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::origin(), Point::new(3.0, 2.0))
+        );
+
+        let drawing = Drawing::from_key_iter(keys, &options);
+        assert_eq!(drawing.keys.len(), 3);
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::origin(), Point::new(5.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn drawing_is_close() {
+        let options = Options::default();
+        let keys = [Key::example(), Key::new()];
+
+        let drawing = Drawing::new(&keys, &options);
+        assert!(drawing.is_close(Drawing::new(&keys, &options)));
+
+        let other = Drawing::new(&keys[..1], &options);
+        assert!(!drawing.is_close(&other));
+
+        let moved = [
+            Key::example(),
+            Key {
+                position: Point::new(1.0, 0.0),
+                ..Key::new()
+            },
+        ];
+        assert!(!drawing.is_close(Drawing::new(&moved, &options)));
+
+        let scaled = Options {
+            scale: 2.0,
+            ..Options::default()
+        };
+        assert!(!drawing.is_close(Drawing::new(&keys, &scaled)));
+    }
+
+    #[test]
+    fn drawing_palette() {
+        let palette = [Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0)];
+        let options = Options {
+            palette: Some(&palette),
+            ..Options::default()
+        };
+        let keys = [Key::example()];
+
+        for drawing in [
+            Drawing::new(&keys, &options),
+            Drawing::from_key(&keys[0], &options),
+        ] {
+            let colors = drawing
+                .keys
+                .iter()
+                .flat_map(|key| key.paths.iter())
+                .flat_map(|path| {
+                    path.fill
+                        .into_iter()
+                        .chain(path.outline.map(|outline| outline.color))
+                });
+            for color in colors {
+                assert!(palette.iter().any(|p| p.is_close(color)));
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn drawing_export_keys_svg() {
+        use key::Legends;
+
+        let keys = [
+            Key::example(),
+            Key {
+                position: Point::new(1.0, 0.0),
+                legends: Legends::from_compact_string("Caps Lock||||||||"),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(2.0, 0.0),
+                legends: Legends::from_compact_string("caps<br>lock||||||||"),
+                ..Key::example()
+            },
+        ];
+        let drawing = Drawing::new(&keys, &Options::default());
+
+        let files = drawing.export_keys_svg();
+        let filenames: Vec<_> = files.iter().map(|file| file.0.as_str()).collect();
+        assert_eq!(filenames, ["key-0.svg", "caps-lock.svg", "caps-lock-2.svg"]);
+
+        let expected = Drawing::from_key(&keys[1], &Options::default()).to_svg();
+        assert_eq!(files[1].1, expected);
+
+        // The case and caption aren't exported, and don't affect the fallback filenames
+        let options = Options {
+            case: Some(CaseParams::default()),
+            caption: Some(Caption {
+                text: "keyset".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Below,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+        let drawing = Drawing::new(&[keys[1].clone(), keys[0].clone()], &options);
+
+        let files = drawing.export_keys_svg();
+        let filenames: Vec<_> = files.iter().map(|file| file.0.as_str()).collect();
+        assert_eq!(filenames, ["caps-lock.svg", "key-1.svg"]);
+
+        // Suffixed names don't collide with another key's legend or fallback filename
+        let keys = [
+            Key {
+                legends: Legends::from_compact_string("Caps Lock||||||||"),
+                ..Key::example()
+            },
+            Key {
+                legends: Legends::from_compact_string("Caps Lock||||||||"),
+                ..Key::example()
+            },
+            Key {
+                legends: Legends::from_compact_string("Caps Lock 2||||||||"),
+                ..Key::example()
+            },
+            Key {
+                legends: Legends::from_compact_string("Key 4||||||||"),
+                ..Key::example()
+            },
+            Key::example(),
+        ];
+        let drawing = Drawing::new(&keys, &Options::default());
+
+        let files = drawing.export_keys_svg();
+        let filenames: Vec<_> = files.iter().map(|file| file.0.as_str()).collect();
+        assert_eq!(
+            filenames,
+            [
+                "caps-lock.svg",
+                "caps-lock-2.svg",
+                "caps-lock-2-2.svg",
+                "key-4.svg",
+                "key-4-2.svg"
+            ]
+        );
+    }
+
+    #[test]
+    fn drawing_padding() {
+        let options = Options {
+            padding: SideOffsets::new(0.5, 0.25, 0.5, 0.25),
+            ..Options::default()
+        };
+        let keys = [Key::example()];
+
+        let drawing = Drawing::new(&keys, &options);
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::new(-0.25, -0.5), Point::new(1.25, 1.5))
+        );
+
+        let drawing = Drawing::from_key(&keys[0], &options);
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::new(-0.25, -0.5), Point::new(1.25, 1.5))
+        );
+    }
+
+    #[test]
+    fn drawing_bounds_override() {
+        let bounds_override = Rect::new(Point::new(-1.0, 0.0), Point::new(4.0, 2.0));
+        let options = Options {
+            bounds_override: Some(bounds_override),
+            ..Options::default()
+        };
+
+        let small = [Key::example()];
+        let large = [
+            Key::example(),
+            Key {
+                position: Point::new(5.0, 3.0),
+                ..Key::example()
+            },
+        ];
+
+        let drawing = Drawing::new(&small, &options);
+        assert_is_close!(drawing.bounds, bounds_override);
+
+        // Keys outside the override don't extend the bounds
+        let drawing = Drawing::new(&large, &options);
+        assert_is_close!(drawing.bounds, bounds_override);
+
+        let drawing = Drawing::new(&large, &Options::default());
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::new(0.0, 0.0), Point::new(6.0, 4.0))
+        );
+
+        let drawing = Drawing::from_key(&small[0], &options);
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn drawing_perspective() {
+        let keys = [Key::example()];
+        let flat = Drawing::new(&keys, &Options::default());
+
+        let options = Options {
+            perspective: Some(Perspective {
+                top_width: 0.5,
+                height: 0.5,
+                ..Perspective::default()
+            }),
+            ..Options::default()
+        };
+        let drawing = Drawing::new(&keys, &options);
+
+        let paths = drawing.keys[0].paths.iter();
+        let flat_paths = flat.keys[0].paths.iter();
+        for (path, flat_path) in paths.zip(flat_paths) {
+            let (bounds, flat_bounds) = (path.data.bounds, flat_path.data.bounds);
+            assert!(bounds.min.y >= 500.0 - 1e-3 && bounds.max.y <= 1000.0 + 1e-3);
+            assert!(bounds.width() <= flat_bounds.width() + 1e-3);
+        }
+
+        // Degenerate perspective leaves the drawing unchanged
+        let options = Options {
+            perspective: Some(Perspective {
+                top_width: 0.5,
+                height: 0.0,
+                ..Perspective::default()
+            }),
+            ..Options::default()
+        };
+        let drawing = Drawing::new(&keys, &options);
+        assert_is_close!(drawing, flat);
+    }
+
+    #[test]
+    fn drawing_z_order() {
+        let options = Options::default();
+        let keys = [
+            Key {
+                z_order: 1,
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(1.0, 0.0),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(2.0, 0.0),
+                z_order: -1,
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(3.0, 0.0),
+                ..Key::example()
+            },
+        ];
+
+        let drawing = Drawing::new(&keys, &options);
+
+        let origins: Vec<_> = drawing.keys.iter().map(|key| key.origin.x).collect();
+        assert_eq!(origins, [2.0, 1.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn options_draw_caption() {
+        let options = Options {
+            caption: Some(Caption {
+                text: "keyset".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Below,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+        let keys = [Key::example()];
+
+        let drawing = Drawing::new(&keys, &options);
+
+        assert_is_close!(drawing.bounds.width(), 1.0);
+        assert!(drawing.bounds.height() > 1.0);
+        assert_eq!(drawing.keys.len(), 2);
+    }
+
+    #[test]
+    fn options_draw_no_font() {
+        let options = Options {
+            font: None,
+            caption: Some(Caption {
+                text: "keyset".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Below,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+        let keys = [Key::example()];
+
+        let drawing = Drawing::new(&keys, &options);
+
+        // Neither the caption nor the example key's text legends are drawn
+        assert_is_close!(drawing.bounds.height(), 1.0);
+        assert_eq!(drawing.keys.len(), 1);
+        assert!(drawing.keys[0]
+            .paths
+            .iter()
+            .all(|path| path.layer != PathLayer::Legend));
+        assert!(drawing.font.is_none());
+    }
+
+    #[test]
+    fn options_draw_unit_pitch() {
+        let profile = Profile {
+            unit_pitch: Length::new(18.0),
+            ..Profile::default()
+        };
+        let options = Options {
+            profile: &profile,
+            ..Options::default()
+        };
+        let keys = [
+            Key::example(),
+            Key {
+                position: Point::new(1.0, 0.0),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(0.0, 1.0),
+                ..Key::example()
+            },
+        ];
+
+        let drawing = Drawing::new(&keys, &options);
+
+        // Keys are closer together, but the keycaps are the same size. The keys span two rows and
+        // columns so the bounds aren't clamped to the minimum 1u drawing size
+        let pitch = 18.0 / 19.05;
+        assert_is_close!(drawing.bounds.width(), 2.0 * pitch);
+        assert_is_close!(drawing.bounds.height(), 2.0 * pitch);
+        assert_is_close!(drawing.keys[1].origin, Point::new(pitch, 0.0));
+        assert_is_close!(drawing.keys[2].origin, Point::new(0.0, pitch));
+        assert_is_close!(
+            drawing.keys[1].paths[0].data.bounds.size(),
+            profile.bottom.size
+        );
+    }
+
+    #[test]
+    fn options_draw_case() {
+        let options = Options {
+            case: Some(CaseParams::default()),
+            ..Options::default()
+        };
+        let keys = [Key::example()];
+
+        let drawing = Drawing::new(&keys, &options);
+
+        assert_is_close!(
+            drawing.bounds,
+            Rect::new(Point::new(-0.25, -0.25), Point::new(1.25, 1.25))
+        );
+        assert_eq!(drawing.keys.len(), 2);
+        assert_eq!(drawing.keys[0].name.as_deref(), Some("case"));
+        assert_eq!(drawing.keys[1].index, Some(0));
+    }
 }
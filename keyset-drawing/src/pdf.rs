@@ -1,16 +1,32 @@
+use std::collections::BTreeMap;
+
+use font::Font;
 use geom::{
     Dot, PathSegment, Point, Scale, ToTransform, Transform, Vector, DOT_PER_INCH, DOT_PER_UNIT,
 };
+use log::warn;
 use miniz_oxide::deflate::{compress_to_vec_zlib, CompressionLevel};
-use pdf_writer::{Content, Filter, Finish, Pdf, Rect, Ref, TextStr};
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, TextRenderingMode, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use saturate::SaturatingFrom;
+use subsetter::Profile;
 
-use crate::{Drawing, KeyDrawing, KeyPath};
+use crate::imp::TextRun;
+use crate::{Drawing, KeyDrawing, KeyPath, PdfOptions};
 
 #[derive(Debug, Clone, Copy)]
 struct PdfUnit;
 
 const PDF_SCALE: Scale<Dot, PdfUnit> = Scale::new(72.0 / DOT_PER_INCH.0); // PDF uses 72 dpi
 const COMPRESSION_LEVEL: u8 = CompressionLevel::DefaultLevel as u8;
+const FONT_NAME: Name<'_> = Name(b"F1");
+// PDF requires the name of a subset font to be prefixed with a tag of six uppercase letters
+const SUBSET_TAG: &str = "KEYSET";
+const IDENTITY_SYSTEM_INFO: SystemInfo<'_> = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
 
 struct RefGen(i32);
 
@@ -25,7 +41,7 @@ impl RefGen {
     }
 }
 
-pub fn draw(drawing: &Drawing) -> Vec<u8> {
+pub fn draw(drawing: &Drawing, options: PdfOptions) -> Vec<u8> {
     let scale = PDF_SCALE * Scale::<PdfUnit, PdfUnit>::new(drawing.scale);
     let size = drawing.bounds.size() * DOT_PER_UNIT * scale;
 
@@ -40,15 +56,27 @@ pub fn draw(drawing: &Drawing) -> Vec<u8> {
     let content_id = ref_gen.next();
     let doc_info_id = ref_gen.next();
 
+    // Only TrueType outlines can be embedded as a CIDFontType2 font, so we fall back to drawing
+    // outlines for CFF fonts or if the font can't be subset
+    let glyphs = glyph_text(drawing);
+    let font = drawing
+        .font
+        .as_ref()
+        .filter(|font| !options.text_as_outlines && font.has_truetype_outlines())
+        .and_then(|font| Some((font, subset(font, &glyphs)?)));
+    let font_id = font.as_ref().map(|_| ref_gen.next());
+
     writer.catalog(catalog_id).pages(tree_id);
     writer.pages(tree_id).kids([page_id]).count(1);
 
-    writer
-        .page(page_id)
-        .media_box(Rect::new(0.0, 0.0, size.width, size.height))
+    let mut page = writer.page(page_id);
+    page.media_box(Rect::new(0.0, 0.0, size.width, size.height))
         .parent(tree_id)
-        .contents(content_id)
-        .finish();
+        .contents(content_id);
+    if let Some(font_id) = font_id {
+        page.resources().fonts().pair(FONT_NAME, font_id);
+    }
+    page.finish();
 
     let mut content = Content::new();
 
@@ -58,7 +86,7 @@ pub fn draw(drawing: &Drawing) -> Vec<u8> {
         .then_scale(1.0, -1.0)
         .then_translate(Vector::new(0.0, size.height));
     for key in &drawing.keys {
-        draw_key(&mut content, key, transform);
+        draw_key(&mut content, key, transform, font.as_ref().map(|f| f.0));
     }
 
     let data = compress_to_vec_zlib(&content.finish(), COMPRESSION_LEVEL);
@@ -67,6 +95,10 @@ pub fn draw(drawing: &Drawing) -> Vec<u8> {
         .filter(Filter::FlateDecode)
         .finish();
 
+    if let Some(((font, data), font_id)) = font.zip(font_id) {
+        write_font(&mut writer, &mut ref_gen, font, &data, font_id, &glyphs);
+    }
+
     writer
         .document_info(doc_info_id)
         .creator(TextStr("keyset-rs"))
@@ -77,15 +109,180 @@ pub fn draw(drawing: &Drawing) -> Vec<u8> {
     writer.finish()
 }
 
-fn draw_key(content: &mut Content, key: &KeyDrawing, transform: Transform<Dot, PdfUnit>) {
+// The text for each glyph used in the drawing, used for widths and to map glyphs back to text
+fn glyph_text(drawing: &Drawing) -> BTreeMap<u16, String> {
+    let mut glyphs = BTreeMap::<u16, String>::new();
+    let runs = drawing
+        .keys
+        .iter()
+        .flat_map(|key| key.paths.iter())
+        .filter_map(|path| path.text.as_deref())
+        .flatten();
+    for glyph in runs.flat_map(|run| run.glyphs.iter()) {
+        let text = glyphs.entry(glyph.glyph_id).or_default();
+        if text.is_empty() {
+            text.clone_from(&glyph.text);
+        }
+    }
+    glyphs
+}
+
+// Subset the font to the glyphs used in the drawing. Glyph IDs are unchanged by subsetting
+fn subset(font: &Font, glyphs: &BTreeMap<u16, String>) -> Option<Vec<u8>> {
+    let glyph_ids: Vec<_> = glyphs.keys().copied().collect();
+    subsetter::subset(font.data(), 0, Profile::pdf(&glyph_ids))
+        .map_err(|error| warn!("failed to subset font, drawing text as outlines: {error}"))
+        .ok()
+}
+
+fn write_font(
+    writer: &mut Pdf,
+    ref_gen: &mut RefGen,
+    font: &Font,
+    data: &[u8],
+    font_id: Ref,
+    glyphs: &BTreeMap<u16, String>,
+) {
+    let cid_font_id = ref_gen.next();
+    let descriptor_id = ref_gen.next();
+    let font_file_id = ref_gen.next();
+    let cmap_id = ref_gen.next();
+
+    // PDF uses 1000 units per em for all font metrics
+    let to_pdf_units = |length: f32| length * 1000.0 / font.em_size().get();
+
+    let base_font: String = font
+        .name()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let base_font = if base_font.is_empty() {
+        format!("{SUBSET_TAG}+Font")
+    } else {
+        format!("{SUBSET_TAG}+{base_font}")
+    };
+
+    writer
+        .type0_font(font_id)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_font_id)
+        .to_unicode(cmap_id);
+
+    let mut cid_font = writer.cid_font(cid_font_id);
+    cid_font
+        .subtype(CidFontType::Type2)
+        .base_font(Name(base_font.as_bytes()))
+        .system_info(IDENTITY_SYSTEM_INFO)
+        .font_descriptor(descriptor_id)
+        .default_width(0.0)
+        .cid_to_gid_map_predefined(Name(b"Identity"));
+    let mut widths = cid_font.widths();
+    for &glyph_id in glyphs.keys() {
+        widths.consecutive(glyph_id, [to_pdf_units(font.glyph_advance(glyph_id).get())]);
+    }
+    widths.finish();
+    cid_font.finish();
+
+    let bbox = font.bounding_box();
+    writer
+        .font_descriptor(descriptor_id)
+        .name(Name(base_font.as_bytes()))
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(
+            to_pdf_units(bbox.min.x),
+            to_pdf_units(bbox.min.y),
+            to_pdf_units(bbox.max.x),
+            to_pdf_units(bbox.max.y),
+        ))
+        .italic_angle(-font.slope().to_degrees())
+        .ascent(to_pdf_units(font.ascender().get()))
+        .descent(to_pdf_units(-font.descender().get()))
+        .cap_height(to_pdf_units(font.cap_height().get()))
+        .stem_v(80.0) // Not available from the font, so use a typical value
+        .font_file2(font_file_id);
+
+    let compressed = compress_to_vec_zlib(data, COMPRESSION_LEVEL);
+    writer
+        .stream(font_file_id, &compressed)
+        .filter(Filter::FlateDecode)
+        .pair(Name(b"Length1"), i32::saturating_from(data.len()));
+
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), IDENTITY_SYSTEM_INFO);
+    for (&glyph_id, text) in glyphs.iter().filter(|&(_, text)| !text.is_empty()) {
+        cmap.pair_with_multiple(glyph_id, text.chars());
+    }
+    writer.cmap(cmap_id, &cmap.finish());
+}
+
+fn draw_key(
+    content: &mut Content,
+    key: &KeyDrawing,
+    transform: Transform<Dot, PdfUnit>,
+    font: Option<&Font>,
+) {
     let transform = (key.origin.to_vector() * DOT_PER_UNIT)
         .to_transform()
         .then(&transform);
     for path in &key.paths {
-        draw_path(content, path, transform);
+        if let Some((font, runs)) = font.zip(path.text.as_deref()) {
+            draw_text(content, path, runs, font, transform);
+        } else {
+            draw_path(content, path, transform);
+        }
     }
 }
 
+fn draw_text(
+    content: &mut Content,
+    path: &KeyPath,
+    runs: &[TextRun],
+    font: &Font,
+    transform: Transform<Dot, PdfUnit>,
+) {
+    let em_size = font.em_size().get();
+
+    set_colors(content, path, transform);
+
+    content.begin_text();
+    content.set_font(FONT_NAME, 1.0);
+    content.set_text_rendering_mode(if path.outline.is_some() {
+        TextRenderingMode::FillStroke
+    } else {
+        TextRenderingMode::Fill
+    });
+
+    for run in runs.iter().filter(|run| !run.glyphs.is_empty()) {
+        // Text space is 1 em per unit at a font size of 1, so scale the run's transform from
+        // font units accordingly
+        let xform = run.transform.then(&transform);
+        content.set_text_matrix([
+            xform.m11 * em_size,
+            xform.m12 * em_size,
+            xform.m21 * em_size,
+            xform.m22 * em_size,
+            xform.m31,
+            xform.m32,
+        ]);
+
+        // Adjust the position of each glyph relative to where the glyph's advance would place it.
+        // Vertical offsets are not supported by the TJ operator, so are ignored
+        let mut positioned = content.show_positioned();
+        let mut items = positioned.items();
+        let mut pen = 0.0;
+        for glyph in &run.glyphs {
+            let adjustment = (glyph.position.x - pen) * 1000.0 / em_size;
+            if adjustment != 0.0 {
+                items.adjust(-adjustment);
+            }
+            items.show(Str(&glyph.glyph_id.to_be_bytes()));
+            pen = glyph.position.x + font.glyph_advance(glyph.glyph_id).get();
+        }
+    }
+
+    content.end_text();
+}
+
 fn draw_path(content: &mut Content, path: &KeyPath, transform: Transform<Dot, PdfUnit>) {
     // origin needed for close; previous point needed for distance => point and quad => cubic
     // Bézier conversion
@@ -126,6 +323,23 @@ fn draw_path(content: &mut Content, path: &KeyPath, transform: Transform<Dot, Pd
         }
     }
 
+    set_colors(content, path, transform);
+
+    match (path.fill, path.outline) {
+        (Some(_), Some(_)) => {
+            content.fill_even_odd_and_stroke();
+        }
+        (Some(_), None) => {
+            content.fill_even_odd();
+        }
+        (None, Some(_)) => {
+            content.stroke();
+        }
+        (None, None) => {} // unreachable!() ? // it makes sense to just do nothing here regardless
+    }
+}
+
+fn set_colors(content: &mut Content, path: &KeyPath, transform: Transform<Dot, PdfUnit>) {
     if let Some(color) = path.fill {
         let (r, g, b) = color.into();
         content.set_fill_rgb(r, g, b);
@@ -141,26 +355,14 @@ fn draw_path(content: &mut Content, path: &KeyPath, transform: Transform<Dot, Pd
         );
         content.set_line_width((outline.width * scale).get());
     }
-
-    match (path.fill, path.outline) {
-        (Some(_), Some(_)) => {
-            content.fill_even_odd_and_stroke();
-        }
-        (Some(_), None) => {
-            content.fill_even_odd();
-        }
-        (None, Some(_)) => {
-            content.stroke();
-        }
-        (None, None) => {} // unreachable!() ? // it makes sense to just do nothing here regardless
-    };
 }
 
 #[cfg(test)]
 mod tests {
-    use key::Key;
+    use font::Font;
+    use key::{Key, Legends};
 
-    use crate::{Drawing, Options};
+    use crate::{Drawing, Options, PdfOptions};
 
     #[test]
     fn test_to_pdf() {
@@ -176,4 +378,56 @@ mod tests {
 
         assert_eq!(pdf, ai);
     }
+
+    #[test]
+    fn test_to_pdf_with_options() {
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &Options::default());
+
+        let contains = |data: &[u8], needle: &[u8]| data.windows(needle.len()).any(|w| w == needle);
+
+        let pdf = drawing.to_pdf_with_options(&PdfOptions::default());
+        assert_eq!(pdf, drawing.to_pdf());
+        assert!(!contains(&pdf, b"/Type0"));
+
+        let pdf = drawing.to_pdf_with_options(&PdfOptions {
+            text_as_outlines: false,
+            ..PdfOptions::default()
+        });
+        assert!(contains(&pdf, b"/Type0"));
+        assert!(contains(&pdf, b"/CIDFontType2"));
+        assert!(contains(&pdf, b"/FontFile2"));
+        assert!(contains(&pdf, b"/ToUnicode"));
+        assert!(contains(&pdf, b"/F1"));
+    }
+
+    #[test]
+    fn test_to_pdf_font_subset() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let options = Options {
+            font: Some(&font),
+            ..Options::default()
+        };
+        let keys = [Key {
+            legends: Legends::from_compact_string("A||||||||"),
+            ..Key::new()
+        }];
+        let drawing = Drawing::new(&keys, &options);
+
+        let pdf = drawing.to_pdf_with_options(&PdfOptions {
+            text_as_outlines: false,
+            ..PdfOptions::default()
+        });
+        let contains = |needle: &[u8]| pdf.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"/BaseFont /KEYSET+demoregular"));
+
+        // The embedded font is subset to the glyphs used, so is smaller than the original
+        let start = pdf.windows(9).position(|w| w == b"/Length1 ").unwrap() + 9;
+        let length: String = pdf[start..]
+            .iter()
+            .map(|&b| char::from(b))
+            .take_while(char::is_ascii_digit)
+            .collect();
+        assert!(length.parse::<usize>().unwrap() < font.data().len());
+    }
 }
@@ -1,14 +1,36 @@
+use color::Color;
 use geom::{
     Dot, Inch, PathSegment, Point, Scale, ToTransform, Transform, DOT_PER_INCH, DOT_PER_UNIT,
 };
-use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Shader, Stroke, Transform as SkiaTransform};
+use saturate::SaturatingFrom;
+use tiny_skia::{
+    FillRule, FilterQuality, Paint, PathBuilder, Pattern, Pixmap, PixmapPaint, Shader, SpreadMode,
+    Stroke, Transform as SkiaTransform,
+};
+
+use crate::{Drawing, Error, KeyDrawing, KeyPath, PngOptions, TextureParams};
 
-use crate::{Drawing, Error, KeyDrawing, KeyPath};
+// The number of noise cells along each side of the repeating texture tile
+const TEXTURE_TILE_CELLS: u32 = 64;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Pixel;
 
-pub fn draw(drawing: &Drawing, ppi: Scale<Inch, Pixel>) -> Result<Vec<u8>, Error> {
+pub fn draw(
+    drawing: &Drawing,
+    ppi: Scale<Inch, Pixel>,
+    options: PngOptions<'_>,
+) -> Result<Vec<u8>, Error> {
+    Ok(render(drawing, ppi, options)?
+        .encode_png()
+        .unwrap_or_else(|_| unreachable!("writing to Vec<_> should not fail")))
+}
+
+pub fn render(
+    drawing: &Drawing,
+    ppi: Scale<Inch, Pixel>,
+    options: PngOptions<'_>,
+) -> Result<Pixmap, Error> {
     let scale = (DOT_PER_INCH.inverse() * ppi) * Scale::<Pixel, Pixel>::new(drawing.scale);
     let size = drawing.bounds.size() * DOT_PER_UNIT * scale;
 
@@ -19,25 +41,84 @@ pub fn draw(drawing: &Drawing, ppi: Scale<Inch, Pixel>) -> Result<Vec<u8>, Error
 
     pixmap.fill(tiny_skia::Color::TRANSPARENT);
 
+    if let Some(image) = options.background_image {
+        draw_background(&mut pixmap, image);
+    }
+
     let transform = scale.to_transform();
     for key in &drawing.keys {
-        draw_key(&mut pixmap, key, transform);
+        draw_key(&mut pixmap, key, transform, options);
     }
 
-    Ok(pixmap
-        .encode_png()
-        .unwrap_or_else(|_| unreachable!("writing to Vec<_> should not fail")))
+    Ok(pixmap)
 }
 
-fn draw_key(pixmap: &mut Pixmap, key: &KeyDrawing, transform: Transform<Dot, Pixel>) {
+pub fn estimate_size(drawing: &Drawing, ppi: Scale<Inch, Pixel>) -> usize {
+    // Arbitrary but generous allowance for the PNG signature, header, and other chunks
+    const EST_OVERHEAD_BYTES: usize = 100;
+
+    let scale = (DOT_PER_INCH.inverse() * ppi) * Scale::<Pixel, Pixel>::new(drawing.scale);
+    let size = drawing.bounds.size() * DOT_PER_UNIT * scale;
+    let (width, height) = (
+        usize::saturating_from(size.width),
+        usize::saturating_from(size.height),
+    );
+
+    // Raw RGBA data plus a filter type byte for each row
+    height
+        .saturating_mul(width.saturating_mul(4).saturating_add(1))
+        .saturating_add(EST_OVERHEAD_BYTES)
+}
+
+// Draw the image scaled to fit within the pixmap and centred
+fn draw_background(pixmap: &mut Pixmap, image: &Pixmap) {
+    let (width, height) = (
+        f32::saturating_from(pixmap.width()),
+        f32::saturating_from(pixmap.height()),
+    );
+    let (image_width, image_height) = (
+        f32::saturating_from(image.width()),
+        f32::saturating_from(image.height()),
+    );
+    let scale = (width / image_width).min(height / image_height);
+    let transform = SkiaTransform::from_scale(scale, scale).post_translate(
+        (width - image_width * scale) / 2.0,
+        (height - image_height * scale) / 2.0,
+    );
+    let paint = PixmapPaint {
+        quality: FilterQuality::Bicubic,
+        ..PixmapPaint::default()
+    };
+    pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, transform, None);
+}
+
+fn draw_key(
+    pixmap: &mut Pixmap,
+    key: &KeyDrawing,
+    transform: Transform<Dot, Pixel>,
+    options: PngOptions<'_>,
+) {
     let transform = (key.origin.to_vector() * DOT_PER_UNIT)
         .to_transform()
         .then(&transform);
     for path in &key.paths {
-        draw_path(pixmap, path, transform);
+        // Only text legends and the caption have an anchor. Their chips and bounds share the
+        // legend's anchor so they move along with it
+        match path.snap_anchor {
+            Some(anchor) if options.snap_legends => {
+                draw_path(pixmap, path, snap_to_pixels(anchor, transform));
+            }
+            _ => draw_path(pixmap, path, transform),
+        }
     }
 }
 
+// Adjust the transform so the anchor lies on a whole pixel
+fn snap_to_pixels(anchor: Point<Dot>, transform: Transform<Dot, Pixel>) -> Transform<Dot, Pixel> {
+    let corner = transform.transform_point(anchor);
+    transform.then_translate(corner.round() - corner)
+}
+
 fn draw_path(pixmap: &mut Pixmap, path: &KeyPath, transform: Transform<Dot, Pixel>) {
     let path_builder = {
         let mut builder = PathBuilder::new();
@@ -94,8 +175,25 @@ fn draw_path(pixmap: &mut Pixmap, path: &KeyPath, transform: Transform<Dot, Pixe
     };
 
     if let Some(color) = path.fill {
+        let tile = path
+            .texture
+            .and_then(|texture| texture_tile(color, texture).map(|tile| (tile, texture)));
+        let shader = tile.as_ref().map_or_else(
+            || Shader::SolidColor(color.into()),
+            |&(ref tile, texture)| {
+                // Scale each pixel of the tile to the size of one noise cell
+                let scale = texture.scale.get();
+                Pattern::new(
+                    tile.as_ref(),
+                    SpreadMode::Repeat,
+                    FilterQuality::Nearest,
+                    1.0,
+                    SkiaTransform::from_scale(scale, scale),
+                )
+            },
+        );
         let paint = Paint {
-            shader: Shader::SolidColor(color.into()),
+            shader,
             anti_alias: true,
             ..Default::default()
         };
@@ -116,14 +214,44 @@ fn draw_path(pixmap: &mut Pixmap, path: &KeyPath, transform: Transform<Dot, Pixe
     }
 }
 
+// Create a tile of random noise based on the given colour, with one pixel per noise cell
+fn texture_tile(color: Color, texture: TextureParams) -> Option<Pixmap> {
+    let mut tile = Pixmap::new(TEXTURE_TILE_CELLS, TEXTURE_TILE_CELLS)?;
+    for (i, pixel) in tile.pixels_mut().iter_mut().enumerate() {
+        let noise = noise(u32::saturating_from(i)) * texture.strength;
+        let color = if noise > 0.0 {
+            color.lighter(noise)
+        } else {
+            color.darker(-noise)
+        };
+        *pixel = tiny_skia::Color::from(color).premultiply().to_color_u8();
+    }
+    Some(tile)
+}
+
+// Deterministic pseudo-random noise in the range -1.0..=1.0, using the finaliser from MurmurHash3
+fn noise(index: u32) -> f32 {
+    let mut hash = index.wrapping_mul(0x9e37_79b9);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+
+    f32::from(u16::saturating_from(hash >> 16)) / f32::from(u16::MAX) * 2.0 - 1.0
+}
+
 #[cfg(test)]
 mod tests {
-    use isclose::assert_is_close_abs_tol;
+    use geom::{Length, Vector};
+    use isclose::{assert_is_close, assert_is_close_abs_tol};
     use itertools::izip;
     use key::Key;
     use tiny_skia::{Color, Pixmap, PremultipliedColorU8};
 
-    use crate::{Drawing, Options};
+    use crate::{Drawing, Options, PngOptions, Ppi, TextureParams};
+
+    use super::*;
 
     fn premul_u8_to_f32(color: PremultipliedColorU8) -> Color {
         let [r, g, b, a] =
@@ -163,4 +291,153 @@ mod tests {
             assert_is_close_abs_tol!(res_a, exp_a, 0.025);
         }
     }
+
+    #[test]
+    fn test_to_png_texture() {
+        let keys = [Key::example()];
+        let render = |options: &Options<'_>| {
+            let png = Drawing::new(&keys, options).to_png(Ppi::SCREEN).unwrap();
+            Pixmap::decode_png(&png).unwrap()
+        };
+
+        let flat = render(&Options::default());
+        let textured = render(&Options {
+            key_texture: Some(TextureParams {
+                strength: 0.2,
+                scale: Length::new(25.0),
+                ..TextureParams::default()
+            }),
+            ..Default::default()
+        });
+
+        // Pixels in the middle of the key top are uniform without a texture, but vary with one
+        let row = |pixmap: &Pixmap| -> Vec<_> {
+            (28..44)
+                .map(|x| pixmap.pixel(x, 50).unwrap().red())
+                .collect()
+        };
+        assert!(row(&flat).windows(2).all(|pair| pair[0] == pair[1]));
+        assert!(row(&textured).windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn test_snap_to_pixels() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+        let legend = drawing.keys[0]
+            .paths
+            .iter()
+            .find(|path| path.text.is_some())
+            .unwrap();
+
+        let transform = Transform::scale(0.1, 0.1).then_translate(Vector::new(0.3, 0.6));
+        let corner = transform.transform_point(legend.data.bounds.min);
+        assert!(corner.x.fract().abs() > 1e-3 || corner.y.fract().abs() > 1e-3);
+
+        let snapped = snap_to_pixels(legend.snap_anchor.unwrap(), transform);
+        let corner = snapped.transform_point(legend.data.bounds.min);
+        assert_is_close!(corner, corner.round());
+    }
+
+    #[test]
+    fn test_to_png_snap_legends() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let png = drawing.to_png(Ppi::SCREEN).unwrap();
+        let snapped = drawing
+            .to_png_with_options(
+                Ppi::SCREEN,
+                &PngOptions {
+                    snap_legends: true,
+                    ..PngOptions::default()
+                },
+            )
+            .unwrap();
+        let (png, snapped) = (
+            Pixmap::decode_png(&png).unwrap(),
+            Pixmap::decode_png(&snapped).unwrap(),
+        );
+
+        assert_eq!(png.width(), snapped.width());
+        assert_eq!(png.height(), snapped.height());
+        assert_ne!(png.data(), snapped.data());
+    }
+
+    #[test]
+    fn test_to_png_background_image() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        // A wide red image is scaled to fit the width of the square drawing and centred vertically
+        let mut image = Pixmap::new(4, 2).unwrap();
+        image.fill(Color::from_rgba8(255, 0, 0, 255));
+        let png = drawing
+            .to_png_with_options(
+                Ppi::SCREEN,
+                &PngOptions {
+                    background_image: Some(&image),
+                    ..PngOptions::default()
+                },
+            )
+            .unwrap();
+        let pixmap = Pixmap::decode_png(&png).unwrap();
+        let (width, height) = (pixmap.width(), pixmap.height());
+
+        // The image is visible in the corners of the key's outline, but not above or below it
+        let red = PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap();
+        assert_eq!(pixmap.pixel(0, height / 2).unwrap(), red);
+        assert_eq!(pixmap.pixel(width / 2, 0).unwrap().alpha(), 0);
+
+        let png = drawing.to_png(Ppi::SCREEN).unwrap();
+        let pixmap = Pixmap::decode_png(&png).unwrap();
+        assert_eq!(pixmap.pixel(0, height / 2).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn test_estimated_png_bytes() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let estimate = drawing.estimated_png_bytes(96.0);
+        let actual = drawing.to_png(96.0).unwrap().len();
+
+        // 72 x 72 pixel image
+        assert_eq!(estimate, 72 * (72 * 4 + 1) + 100);
+        assert!(estimate > actual);
+    }
+
+    #[test]
+    fn test_to_png_print() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let png = drawing.to_png_print().unwrap();
+        let result = Pixmap::decode_png(&png).unwrap();
+
+        // 0.75 in key at 300 PPI, allowing for rounding
+        assert!(result.width().abs_diff(225) <= 1);
+        assert!(result.height().abs_diff(225) <= 1);
+    }
+
+    #[test]
+    fn test_to_png_ppi() {
+        let options = Options::default();
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        assert_eq!(
+            drawing.to_png(Ppi::SCREEN).unwrap(),
+            drawing.to_png(96.0).unwrap()
+        );
+        assert_eq!(
+            drawing.to_png(Ppi::PRINT).unwrap(),
+            drawing.to_png_print().unwrap()
+        );
+    }
 }
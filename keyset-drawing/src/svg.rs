@@ -1,8 +1,20 @@
-use geom::{PathSegment, Scale, Unit, DOT_PER_UNIT, MM_PER_UNIT};
-use svg::node::element::{Group, Path as SvgPath};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use color::Color;
+use geom::{Dot, PathSegment, Rect, Scale, Unit, DOT_PER_UNIT, MM_PER_UNIT};
+use svg::node::element::{Definitions, Group, Line, Path as SvgPath, Pattern, Rectangle, Symbol};
+use svg::node::Comment;
 use svg::Document;
 
-use super::{Drawing, KeyDrawing, KeyPath};
+use super::{Drawing, KeyDrawing, KeyPath, SvgOptions, TextureParams};
+
+// Approximate sizes of the SVG elements we emit, used when estimating the size of a drawing.
+// Coordinates are rounded to 3 d.p. and typically take ~8 bytes each including the separator
+const EST_DOCUMENT_BYTES: usize = 150;
+const EST_KEY_BYTES: usize = 40;
+const EST_PATH_BYTES: usize = 80;
+const EST_COORD_BYTES: usize = 8;
 
 macro_rules! float {
     ($arg:expr $(,)?) => {
@@ -25,14 +37,37 @@ macro_rules! float {
     };
 }
 
-pub fn draw(drawing: &Drawing) -> String {
+pub fn draw(drawing: &Drawing, options: SvgOptions) -> String {
     let size = drawing.bounds.size() * Scale::<Unit, Unit>::new(drawing.scale) * MM_PER_UNIT;
     let view_box = drawing.bounds * DOT_PER_UNIT; // Use 1000 user units per key
 
     let document = Document::new()
         .set("width", format!("{}mm", float!(size.width)))
-        .set("height", format!("{}mm", float!(size.height)))
-        .set(
+        .set("height", format!("{}mm", float!(size.height)));
+
+    let document = if options.y_up {
+        // Keys are drawn with their coordinates relative to the bottom left corner of the drawing
+        // and y pointing up, and this root transform flips them back to SVG's y-down coordinates
+        let root = Group::new().set(
+            "transform",
+            format!("matrix(1 0 0 -1 0 {})", float!(view_box.height())),
+        );
+        let root = drawing
+            .keys
+            .iter()
+            .map(|key| draw_key_y_up(key, view_box))
+            .fold(root, Group::add);
+
+        let document = document.set(
+            "viewBox",
+            float!(0.0_f32, 0.0_f32, view_box.width(), view_box.height()),
+        );
+        texture_defs(drawing, options)
+            .into_iter()
+            .fold(document, Document::add)
+            .add(root)
+    } else {
+        let document = document.set(
             "viewBox",
             float!(
                 view_box.min.x,
@@ -41,16 +76,100 @@ pub fn draw(drawing: &Drawing) -> String {
                 view_box.size().height
             ),
         );
+        let document = texture_defs(drawing, options)
+            .into_iter()
+            .fold(document, Document::add);
+        drawing
+            .keys
+            .iter()
+            .map(draw_key)
+            .fold(document, Document::add)
+    };
 
-    let document = drawing
-        .keys
-        .iter()
-        .map(draw_key)
-        .fold(document, Document::add);
+    document.to_string()
+}
+
+pub fn draw_sprites(drawing: &Drawing) -> String {
+    // Map of each unique key's SVG paths to the keys using them, in order of first appearance so
+    // symbol ids are stable for a given layout
+    let mut sprites: Vec<(Vec<SvgPath>, Vec<&KeyDrawing>)> = Vec::new();
+    let mut lookup: HashMap<String, usize> = HashMap::new();
+
+    // Skip drawings without an index such as the case and caption, which aren't keys
+    for key in drawing.keys.iter().filter(|key| key.index.is_some()) {
+        let paths: Vec<_> = key.paths.iter().map(draw_path).collect();
+        let signature: String = paths.iter().map(ToString::to_string).collect();
+
+        let idx = *lookup.entry(signature).or_insert_with(|| {
+            sprites.push((paths, Vec::new()));
+            sprites.len() - 1
+        });
+        sprites[idx].1.push(key);
+    }
+
+    let document = texture_defs(drawing, SvgOptions::default())
+        .into_iter()
+        .fold(Document::new(), Document::add);
+    let document =
+        sprites
+            .into_iter()
+            .enumerate()
+            .fold(document, |document, (id, (paths, keys))| {
+                let key_list = keys
+                    .iter()
+                    .filter_map(|key| key.index)
+                    .map(|index| index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let comment = Comment::new(format!("key-{id}: keys {key_list}"));
+
+                let bounds = keys
+                    .first()
+                    .and_then(|key| {
+                        key.paths
+                            .iter()
+                            .map(|path| path.data.bounds)
+                            .reduce(|a, b| a.union(&b))
+                    })
+                    .unwrap_or_else(Rect::zero);
+                let symbol = paths.into_iter().fold(
+                    Symbol::new().set("id", format!("key-{id}")).set(
+                        "viewBox",
+                        float!(
+                            bounds.min.x,
+                            bounds.min.y,
+                            bounds.size().width,
+                            bounds.size().height
+                        ),
+                    ),
+                    Symbol::add,
+                );
+
+                document.add(comment).add(symbol)
+            });
 
     document.to_string()
 }
 
+pub fn estimate_size(drawing: &Drawing) -> usize {
+    let paths = drawing.keys.iter().flat_map(|key| key.paths.iter());
+    let segments = paths.clone().flat_map(|path| path.data.iter());
+
+    let segment_bytes: usize = segments
+        .map(|el| match *el {
+            PathSegment::Move(..) | PathSegment::Line(..) => 1 + 2 * EST_COORD_BYTES,
+            PathSegment::CubicBezier(..) => 1 + 6 * EST_COORD_BYTES,
+            PathSegment::QuadraticBezier(..) => 1 + 4 * EST_COORD_BYTES,
+            PathSegment::Close => 1,
+        })
+        .sum();
+
+    EST_DOCUMENT_BYTES
+        + drawing.keys.len() * EST_KEY_BYTES
+        + paths.count() * EST_PATH_BYTES
+        + segment_bytes
+}
+
 fn draw_key(key: &KeyDrawing) -> Group {
     let origin = key.origin * DOT_PER_UNIT;
     let group = Group::new().set(
@@ -60,26 +179,101 @@ fn draw_key(key: &KeyDrawing) -> Group {
     key.paths.iter().map(draw_path).fold(group, Group::add)
 }
 
-fn draw_path(path: &KeyPath) -> SvgPath {
-    let data: String = path
-        .data
+// Draws a key with coordinates relative to the bottom left corner of the view box and y pointing
+// up, to be placed inside a root group which flips the y-axis back
+fn draw_key_y_up(key: &KeyDrawing, view_box: Rect<Dot>) -> Group {
+    let origin = key.origin * DOT_PER_UNIT;
+    let group = Group::new().set(
+        "transform",
+        format!(
+            "translate({},{})",
+            float!(origin.x - view_box.min.x),
+            float!(view_box.max.y - origin.y)
+        ),
+    );
+    key.paths
         .iter()
-        .map(|el| match *el {
-            PathSegment::Move(p) => format!("M{}", float!(p.x, p.y)),
-            PathSegment::Line(d) => format!("l{}", float!(d.x, d.y)),
-            PathSegment::CubicBezier(c1, c2, d) => {
-                format!("c{}", float!(c1.x, c1.y, c2.x, c2.y, d.x, d.y))
-            }
-            // GRCOV_EXCL_START - no quads in example
-            PathSegment::QuadraticBezier(c1, d) => format!("q{}", float!(c1.x, c1.y, d.x, d.y)),
-            // GRCOV_EXCL_STOP
-            PathSegment::Close => "z".into(),
+        .map(|path| {
+            draw_path(&KeyPath {
+                data: path.data.clone().scale(1.0, -1.0),
+                outline: path.outline,
+                fill: path.fill,
+                texture: path.texture,
+                text: None,
+                snap_anchor: None,
+                layer: path.layer,
+            })
         })
+        .fold(group, Group::add)
+}
+
+// Create a <defs> element containing a hatch pattern for each distinct textured fill colour in the
+// drawing, or None if nothing is textured
+fn texture_defs(drawing: &Drawing, options: SvgOptions) -> Option<Definitions> {
+    let mut ids = HashSet::new();
+    let patterns: Vec<_> = drawing
+        .keys
+        .iter()
+        .flat_map(|key| key.paths.iter())
+        .filter_map(|path| path.fill.zip(path.texture))
+        .filter(|&(color, _)| ids.insert(texture_id(color)))
+        .map(|(color, texture)| texture_pattern(color, texture, options))
         .collect();
 
-    let fill = path
-        .fill
-        .map_or_else(|| "none".to_owned(), |color| format!("{color:x}"));
+    (!patterns.is_empty()).then(|| {
+        patterns
+            .into_iter()
+            .fold(Definitions::new(), Definitions::add)
+    })
+}
+
+// All textures in a drawing share the same parameters, so patterns are identified by colour alone
+fn texture_id(color: Color) -> String {
+    format!("key-texture-{color:x}").replace('#', "")
+}
+
+fn texture_pattern(color: Color, texture: TextureParams, options: SvgOptions) -> Pattern {
+    let size = texture.scale.get();
+    // Patterns are flipped along with the keys, so rotate the other way to keep the same hatching
+    let angle = if options.y_up { -45 } else { 45 };
+
+    Pattern::new()
+        .set("id", texture_id(color))
+        .set("patternUnits", "userSpaceOnUse")
+        .set("patternTransform", format!("rotate({angle})"))
+        .set("width", float!(size))
+        .set("height", float!(size))
+        .add(
+            Rectangle::new()
+                .set("width", float!(size))
+                .set("height", float!(size))
+                .set("fill", format!("{color:x}")),
+        )
+        .add(
+            Line::new()
+                .set("x1", float!(size / 2.0))
+                .set("y1", 0)
+                .set("x2", float!(size / 2.0))
+                .set("y2", float!(size))
+                .set("stroke", format!("{:x}", color.highlight(texture.strength)))
+                .set("stroke-width", float!(size / 2.0)),
+        )
+}
+
+fn draw_path(path: &KeyPath) -> SvgPath {
+    let data: String = path
+        .data
+        .svg_commands()
+        .fold(String::new(), |mut data, cmd| {
+            let _ = write!(data, "{cmd:.3}"); // Writing to a String can't fail
+            data
+        });
+
+    let fill = match (path.fill, path.texture) {
+        (Some(color), Some(_)) => format!("url(#{})", texture_id(color)),
+        (Some(color), None) => format!("{color:x}"),
+        (None, _) => "none".to_owned(),
+    };
     let svg_path = SvgPath::new().set("d", data).set("fill", fill);
 
     if let Some(outline) = path.outline {
@@ -95,9 +289,13 @@ fn draw_path(path: &KeyPath) -> SvgPath {
 mod tests {
     use indoc::indoc;
 
+    use color::Color;
+    use geom::{Length, Point};
     use key::Key;
 
-    use crate::{Drawing, Options};
+    use crate::{
+        Caption, CaptionPosition, CaseParams, Drawing, Options, SvgOptions, TextureParams,
+    };
 
     #[test]
     fn test_to_svg() {
@@ -119,13 +317,161 @@ mod tests {
                 <path d="M25 90c0-35.899 29.101-65 65-65l820 0c35.899 0 65 29.101 65 65l0 820c0 35.899-29.101 65-65 65l-820 0c-35.899 0-65-29.101-65-65z" fill="#cccccc" stroke="#aeaeae" stroke-width="10"/>
                 <path d="M170 120c0-35.899 29.101-65 65-65l530 0c35.899 0 65 29.101 65 65l0 605c0 35.899-29.101 65-65 65l-530 0c-35.899 0-65-29.101-65-65z" fill="#cccccc" stroke="#aeaeae" stroke-width="10"/>
                 <path d="M220 105l560 0l0 635l-560 0z" fill="none" stroke="#ff0000" stroke-width="5"/>
-                <path d="M220 299.444l0-194.444l126.362 0l0 194.444l-126.362-0zM235.523 270.305l37.037-68.083l-37.037-68.083l0 136.166zM244.237 120.523l38.943 69.989l38.943-69.989l-77.887-0zM330.839 134.139l-37.037 68.083l37.037 68.083l0-136.166zM322.124 283.922l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
-                <path d="M653.638 299.444l0-194.444l126.362 0l0 194.444l-126.362-0zM669.161 270.305l37.037-68.083l-37.037-68.083l0 136.166zM677.876 120.523l38.943 69.989l38.943-69.989l-77.887-0zM764.477 134.139l-37.037 68.083l37.037 68.083l0-136.166zM755.763 283.922l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
-                <path d="M220 740l0-194.444l126.362 0l0 194.444l-126.362-0zM235.523 710.861l37.037-68.083l-37.037-68.083l0 136.166zM244.237 561.078l38.943 69.989l38.943-69.989l-77.887-0zM330.839 574.695l-37.037 68.083l37.037 68.083l0-136.166zM322.124 724.477l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
-                <path d="M653.638 740l0-194.444l126.362 0l0 194.444l-126.362-0zM669.161 710.861l37.037-68.083l-37.037-68.083l0 136.166zM677.876 561.078l38.943 69.989l38.943-69.989l-77.887-0zM764.477 574.695l-37.037 68.083l37.037 68.083l0-136.166zM755.763 724.477l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
+                <path d="M220 299.444l0-194.444l126.362 0l0 194.444l-126.362 0zM235.523 270.305l37.037-68.083l-37.037-68.083l0 136.166zM244.237 120.523l38.943 69.989l38.943-69.989l-77.887 0zM330.839 134.139l-37.037 68.083l37.037 68.083l0-136.166zM322.124 283.922l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
+                <path d="M653.638 299.444l0-194.444l126.362 0l0 194.444l-126.362 0zM669.161 270.305l37.037-68.083l-37.037-68.083l0 136.166zM677.876 120.523l38.943 69.989l38.943-69.989l-77.887 0zM764.477 134.139l-37.037 68.083l37.037 68.083l0-136.166zM755.763 283.922l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
+                <path d="M220 740l0-194.444l126.362 0l0 194.444l-126.362 0zM235.523 710.861l37.037-68.083l-37.037-68.083l0 136.166zM244.237 561.078l38.943 69.989l38.943-69.989l-77.887 0zM330.839 574.695l-37.037 68.083l37.037 68.083l0-136.166zM322.124 724.477l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
+                <path d="M653.638 740l0-194.444l126.362 0l0 194.444l-126.362 0zM669.161 710.861l37.037-68.083l-37.037-68.083l0 136.166zM677.876 561.078l38.943 69.989l38.943-69.989l-77.887 0zM764.477 574.695l-37.037 68.083l37.037 68.083l0-136.166zM755.763 724.477l-38.943-69.989l-38.943 69.989l77.887 0z" fill="#000000" stroke="none"/>
                 </g>
                 </svg>"##
             )
         );
     }
+
+    #[test]
+    fn test_to_svg_y_up() {
+        let keys = [
+            Key::example(),
+            Key {
+                position: Point::new(1.0, 1.0),
+                ..Key::example()
+            },
+        ];
+        let drawing = Drawing::new(&keys, &Options::default());
+
+        let svg = drawing.to_svg_with_options(&SvgOptions {
+            y_up: true,
+            ..SvgOptions::default()
+        });
+
+        assert!(svg.contains(r#"viewBox="0 0 2000 2000""#));
+        assert!(svg.contains(r#"<g transform="matrix(1 0 0 -1 0 2000)">"#));
+        assert!(svg.contains(r#"<g transform="translate(0,2000)">"#));
+        assert!(svg.contains(r#"<g transform="translate(1000,1000)">"#));
+        // Path coordinates are flipped relative to the key's origin
+        assert!(svg.contains(r#"d="M25-90c0 35.899 29.101 65 65 65l820 0"#));
+
+        let svg = drawing.to_svg_with_options(&SvgOptions::default());
+        assert_eq!(svg, drawing.to_svg());
+        assert!(!svg.contains("matrix"));
+    }
+
+    #[test]
+    fn test_layer_svg() {
+        let options = Options {
+            show_margin: true,
+            ..Default::default()
+        };
+        let keys = [Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let legends = drawing.legend_layer_svg();
+        let keys = drawing.key_layer_svg();
+
+        let view_box = r#"viewBox="0 0 1000 1000""#;
+        assert!(legends.contains(view_box));
+        assert!(keys.contains(view_box));
+
+        // The example key has 4 legends and a top and bottom surface. The margin is in neither
+        assert_eq!(legends.matches("<path").count(), 4);
+        assert_eq!(legends.matches(r##"fill="#000000""##).count(), 4);
+        assert_eq!(keys.matches("<path").count(), 2);
+        assert!(!keys.contains(r##"fill="#000000""##));
+        assert!(!legends.contains("#ff0000") && !keys.contains("#ff0000"));
+    }
+
+    #[test]
+    fn test_legend_layer_svg_overlay() {
+        let keys = [
+            Key::example(),
+            Key {
+                position: Point::new(1.0, 0.0),
+                ..Key::example()
+            },
+        ];
+        let drawing = Drawing::new(&keys, &Options::default());
+
+        let svg = drawing.legend_layer_svg();
+
+        assert!(svg.contains(r#"viewBox="0 0 2000 1000""#));
+        assert!(svg.contains(r#"<g transform="translate(1000,0)">"#));
+        // Only the legends are drawn, without any key bodies or background
+        assert_eq!(svg.matches("<path").count(), 8);
+        assert!(!svg.contains("#cccccc"));
+        assert!(!svg.contains("<rect"));
+    }
+
+    #[test]
+    fn test_to_svg_texture() {
+        let options = Options {
+            key_texture: Some(TextureParams::default()),
+            ..Default::default()
+        };
+        let keys = [Key::example(), Key::example()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let svg = drawing.to_svg();
+
+        // One pattern is shared by both keys' tops
+        assert_eq!(svg.matches("<pattern").count(), 1);
+        assert!(svg.contains(r#"<pattern height="10" id="key-texture-cccccc""#));
+        assert_eq!(svg.matches(r#"fill="url(#key-texture-cccccc)""#).count(), 2);
+
+        let svg = drawing.to_svg_sprites();
+        assert_eq!(svg.matches("<pattern").count(), 1);
+
+        let svg = Drawing::new(&keys, &Options::default()).to_svg();
+        assert!(!svg.contains("<defs"));
+    }
+
+    #[test]
+    fn test_to_svg_sprites() {
+        let options = Options::default();
+        let keys = [Key::example(), Key::example(), Key::new()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let svg = drawing.to_svg_sprites();
+
+        assert_eq!(svg.matches("<symbol").count(), 2);
+        assert!(svg.contains(r#"id="key-0""#));
+        assert!(svg.contains(r#"id="key-1""#));
+        assert!(svg.contains("<!-- key-0: keys 0, 1 -->"));
+        assert!(svg.contains("<!-- key-1: keys 2 -->"));
+    }
+
+    #[test]
+    fn test_to_svg_sprites_case_caption() {
+        let options = Options {
+            case: Some(CaseParams::default()),
+            caption: Some(Caption {
+                text: "keyset".to_owned(),
+                font_size: Length::new(50.0),
+                color: Color::new(0.0, 0.0, 0.0),
+                position: CaptionPosition::Below,
+                ..Caption::default()
+            }),
+            ..Options::default()
+        };
+        let keys = [Key::example(), Key::new()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let svg = drawing.to_svg_sprites();
+
+        // The case and caption aren't keys, so they don't get a sprite
+        assert_eq!(svg.matches("<symbol").count(), 2);
+        assert!(svg.contains("<!-- key-0: keys 0 -->"));
+        assert!(svg.contains("<!-- key-1: keys 1 -->"));
+    }
+
+    #[test]
+    fn test_estimated_svg_bytes() {
+        let options = Options::default();
+        let keys = [Key::example(), Key::example(), Key::new()];
+        let drawing = Drawing::new(&keys, &options);
+
+        let estimate = drawing.estimated_svg_bytes();
+        let actual = drawing.to_svg().len();
+
+        assert!(estimate > actual / 2);
+        assert!(estimate < actual * 2);
+    }
 }
@@ -0,0 +1,50 @@
+#![allow(
+    missing_docs,
+    unused_crate_dependencies,
+    clippy::pedantic,
+    clippy::restriction
+)] // This is just a benchmark
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use keyset_font::Font;
+
+// The legends of a full size ANSI keyboard, most of which are a single ASCII character
+#[rustfmt::skip]
+const LEGENDS: &[&str] = &[
+    "Esc", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "PrtSc",
+    "Scroll Lock", "Pause", "~", "`", "!", "1", "@", "2", "#", "3", "$", "4", "%", "5", "^", "6",
+    "&", "7", "*", "8", "(", "9", ")", "0", "_", "-", "+", "=", "Backspace", "Insert", "Home",
+    "PgUp", "Num Lock", "/", "*", "-", "Tab", "Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P",
+    "{", "[", "}", "]", "|", "\\", "Delete", "End", "PgDn", "7", "8", "9", "+", "Caps Lock", "A",
+    "S", "D", "F", "G", "H", "J", "K", "L", ":", ";", "\"", "'", "Enter", "4", "5", "6", "Shift",
+    "Z", "X", "C", "V", "B", "N", "M", "<", ",", ">", ".", "?", "/", "Shift", "↑", "1", "2", "3",
+    "Enter", "Ctrl", "Win", "Alt", "Alt", "Win", "Menu", "Ctrl", "←", "↓", "→", "0", ".",
+];
+const ITERATIONS: u32 = 200;
+
+fn time(name: &str, font: &Font) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for legend in LEGENDS {
+            // The same work done for each legend when drawing
+            let glyphs = font.shape_string(black_box(legend));
+            black_box(font.render_glyphs(&glyphs));
+        }
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!("{name:<20} {elapsed:>12.2?} per keyboard");
+}
+
+fn main() {
+    // Set KEYSET_BENCH_FONT to benchmark a real font, since the demo font only has a few glyphs
+    let path = std::env::var("KEYSET_BENCH_FONT").unwrap_or_else(|_| env!("DEMO_TTF").to_owned());
+    let data = std::fs::read(&path).unwrap_or_else(|e| panic!("failed to read {path}: {e}"));
+    let shaped = Font::from_ttf(data).unwrap_or_else(|e| panic!("failed to load {path}: {e}"));
+    let fast = shaped.clone().with_fast_path(true);
+
+    println!("{} legends using {path}", LEGENDS.len());
+    time("full shaping", &shaped);
+    time("fast path", &fast);
+}
@@ -29,6 +29,52 @@ impl fmt::Display for PermissionError {
 
 impl std::error::Error for PermissionError {}
 
+/// A font property that may be missing from a font
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FontProperty {
+    /// The font family name
+    Family,
+    /// The font's full name
+    FullName,
+    /// The font's capital height
+    CapHeight,
+    /// The font's x-height
+    XHeight,
+}
+
+impl fmt::Display for FontProperty {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Family => write!(f, "font family"),
+            Self::FullName => write!(f, "full font name"),
+            Self::CapHeight => write!(f, "capital height"),
+            Self::XHeight => write!(f, "x height"),
+        }
+    }
+}
+
+/// A font data format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FontFormat {
+    /// Web Open Font Format
+    Woff,
+    /// Web Open Font Format 2.0
+    Woff2,
+}
+
+impl fmt::Display for FontFormat {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Woff => write!(f, "WOFF"),
+            Self::Woff2 => write!(f, "WOFF2"),
+        }
+    }
+}
+
 /// A font error
 #[derive(Debug, Clone)]
 #[non_exhaustive]
@@ -38,7 +84,15 @@ pub enum Error {
     /// The font's permissions don't allow us to use it
     PermissionError(PermissionError),
     /// Missing required property
-    MissingProperty(String),
+    MissingProperty {
+        /// The property which is missing
+        kind: FontProperty,
+    },
+    /// The font data is in a format which is not supported
+    UnsupportedFormat(FontFormat),
+    /// The WOFF or WOFF2 font data is invalid
+    #[cfg(feature = "woff")]
+    InvalidWoff,
 }
 
 impl fmt::Display for Error {
@@ -47,7 +101,10 @@ impl fmt::Display for Error {
         match *self {
             Self::ParsingError(ref error) => write!(f, "error parsing font: {error}"),
             Self::PermissionError(ref error) => write!(f, "permissions error: {error}"),
-            Self::MissingProperty(ref prop) => write!(f, "missing property {prop}"),
+            Self::MissingProperty { kind } => write!(f, "missing property {kind}"),
+            Self::UnsupportedFormat(format) => write!(f, "unsupported font format {format}"),
+            #[cfg(feature = "woff")]
+            Self::InvalidWoff => write!(f, "invalid WOFF data"),
         }
     }
 }
@@ -58,7 +115,9 @@ impl std::error::Error for Error {
         match *self {
             Self::ParsingError(ref error) => Some(error),
             Self::PermissionError(ref error) => Some(error),
-            Self::MissingProperty(..) => None,
+            Self::MissingProperty { .. } | Self::UnsupportedFormat(..) => None,
+            #[cfg(feature = "woff")]
+            Self::InvalidWoff => None,
         }
     }
 }
@@ -109,6 +168,30 @@ mod tests {
 
         let error = Font::from_ttf(std::fs::read(env!("NULL_TTF")).unwrap()).unwrap_err();
         assert_eq!(format!("{error}"), "missing property font family");
+
+        let error = Error::MissingProperty {
+            kind: FontProperty::FullName,
+        };
+        assert_eq!(format!("{error}"), "missing property full font name");
+
+        let error = Error::MissingProperty {
+            kind: FontProperty::CapHeight,
+        };
+        assert_eq!(format!("{error}"), "missing property capital height");
+
+        let error = Error::MissingProperty {
+            kind: FontProperty::XHeight,
+        };
+        assert_eq!(format!("{error}"), "missing property x height");
+
+        let error = Error::UnsupportedFormat(FontFormat::Woff2);
+        assert_eq!(format!("{error}"), "unsupported font format WOFF2");
+
+        #[cfg(feature = "woff")]
+        {
+            let error = Font::from_woff(b"invalid".to_vec()).unwrap_err();
+            assert_eq!(format!("{error}"), "invalid WOFF data");
+        }
     }
 
     #[test]
@@ -71,6 +71,10 @@ impl Face {
         self.borrow_inner()
     }
 
+    pub fn data(&self) -> &[u8] {
+        self.borrow_data()
+    }
+
     pub fn names(&self) -> ttf_parser::name::Names<'_> {
         self.borrow_inner().names()
     }
@@ -152,6 +156,28 @@ impl Face {
         self.borrow_inner().glyph_index(code_point).map(|gid| gid.0)
     }
 
+    pub fn glyph_advance(&self, glyph_id: u16) -> Option<u16> {
+        self.borrow_inner().glyph_hor_advance(GlyphId(glyph_id))
+    }
+
+    pub fn global_bounding_box(&self) -> ttf_parser::Rect {
+        self.borrow_inner().global_bounding_box()
+    }
+
+    pub fn has_truetype_outlines(&self) -> bool {
+        self.borrow_inner().tables().glyf.is_some()
+    }
+
+    pub fn is_color_font(&self) -> bool {
+        let tables = self.borrow_inner().tables();
+        tables.colr.is_some() || tables.svg.is_some()
+    }
+
+    pub fn is_color_glyph(&self, glyph_id: u16) -> bool {
+        let face = self.borrow_inner();
+        face.is_color_glyph(GlyphId(glyph_id)) || face.glyph_svg_image(GlyphId(glyph_id)).is_some()
+    }
+
     pub fn outline_length(&self, glyph_id: u16) -> usize {
         struct LengthBuilder(usize);
         impl ttf_parser::OutlineBuilder for LengthBuilder {
@@ -350,6 +376,30 @@ mod tests {
         assert_eq!(face.x_height(), Some(450));
         assert_eq!(face.capital_height(), Some(650));
         assert_eq!(face.number_of_glyphs(), 3);
+        assert!(face.has_truetype_outlines());
+        let bbox = face.global_bounding_box();
+        assert_eq!(
+            (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max),
+            (6, 0, 600, 700)
+        );
+    }
+
+    #[test]
+    fn face_data() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let face = Face::from_ttf(data.clone()).unwrap();
+
+        assert_eq!(face.data(), data);
+    }
+
+    #[test]
+    fn face_glyph_advance() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let face = Face::from_ttf(data).unwrap();
+
+        assert_eq!(face.glyph_advance(0), Some(600));
+        assert_eq!(face.glyph_advance(1), Some(540));
+        assert_eq!(face.glyph_advance(3), None);
     }
 
     #[test]
@@ -5,28 +5,77 @@
 mod default;
 mod error;
 mod face;
+mod warning;
+#[cfg(feature = "woff")]
+mod woff;
+#[cfg(feature = "woff")]
+mod woff2;
 
-use geom::{Angle, Length, Path, PathBuilder, Vector};
+use std::sync::Arc;
+
+use geom::{Angle, Length, Path, PathBuilder, Point, Rect, Vector};
 use itertools::izip;
+use log::warn;
 use rustybuzz::ttf_parser::name_id;
-use rustybuzz::{BufferClusterLevel, ShapePlan, UnicodeBuffer};
+use rustybuzz::{BufferClusterLevel, GlyphBuffer, ShapePlan, UnicodeBuffer};
 use saturate::SaturatingInto;
 
-pub use self::error::{Error, Result};
+pub use self::error::{Error, FontFormat, FontProperty, Result};
+pub use self::warning::Warning;
 use face::Face;
 
 /// Unit within a font
 #[derive(Debug, Clone, Copy)]
 pub struct FontUnit;
 
+/// Explicit values for font metrics, used by [`Font::from_ttf_with_metrics`]
+///
+/// This is useful for fonts such as icon or symbol fonts which do not specify these metrics and
+/// do not have the glyphs needed to measure them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricOverrides {
+    /// The capital height in font units
+    pub cap_height: Option<Length<FontUnit>>,
+    /// The x-height in font units
+    pub x_height: Option<Length<FontUnit>>,
+}
+
+/// Vertical metrics for a block of text, as returned by [`Font::line_metrics`]
+#[derive(Debug, Clone)]
+pub struct LineMetrics {
+    /// The number of lines in the text
+    pub num_lines: usize,
+    /// The total height of the block of text. This is the line height multiplied by the number of
+    /// lines, minus the line gap after the last line
+    pub height: Length<FontUnit>,
+    /// The offset of each line's baseline from the first line's baseline. Positive values are in
+    /// a downwards direction
+    pub baselines: Vec<Length<FontUnit>>,
+}
+
+/// A glyph positioned by [`Font::shape_string`]
+#[derive(Debug, Clone)]
+pub struct ShapedGlyph {
+    /// The glyph's ID within the font
+    pub glyph_id: u16,
+    /// The position of the glyph's origin in font units, including any offset applied by shaping
+    pub position: Point<FontUnit>,
+    /// The text represented by the glyph. Where several glyphs represent the same text (e.g. a
+    /// base character and a combining mark drawn as separate glyphs) only the first has the text
+    pub text: String,
+}
+
 /// A parsed font
+///
+/// The parsed font data is shared between clones, so cloning a font is cheap
 #[derive(Debug, Clone)]
 pub struct Font {
-    face: Face,
+    face: Arc<Face>,
     family: String,
     name: String,
     cap_height: Length<FontUnit>,
     x_height: Length<FontUnit>, // TODO is this used?
+    fast_path: bool,
 }
 
 impl Default for Font {
@@ -55,37 +104,129 @@ impl Font {
     /// cannot be determined
     #[inline]
     pub fn from_ttf(data: Vec<u8>) -> Result<Self> {
+        Self::from_ttf_with_metrics(data, MetricOverrides::default())
+    }
+
+    /// Parse a font from WOFF format font data
+    ///
+    /// # Errors
+    ///
+    /// If the WOFF data is invalid, there is an error parsing the font data it contains, or some
+    /// of the required font properties cannot be determined
+    #[cfg(feature = "woff")]
+    #[inline]
+    #[allow(clippy::needless_pass_by_value)] // Take ownership for consistency with from_ttf
+    pub fn from_woff(data: Vec<u8>) -> Result<Self> {
+        Self::from_ttf(woff::decode(&data)?)
+    }
+
+    /// Parse a font from WOFF2 format font data
+    ///
+    /// WOFF2 font collections are not supported
+    ///
+    /// # Errors
+    ///
+    /// If the WOFF2 data is invalid or contains a font collection, there is an error parsing the
+    /// font data it contains, or some of the required font properties cannot be determined
+    #[cfg(feature = "woff")]
+    #[inline]
+    #[allow(clippy::needless_pass_by_value)] // Take ownership for consistency with from_ttf
+    pub fn from_woff2(data: Vec<u8>) -> Result<Self> {
+        Self::from_ttf(woff2::decode(&data)?)
+    }
+
+    /// Parse a font from TrueType, OpenType, WOFF, or WOFF2 format font data, detecting the format
+    /// from the data's magic bytes
+    ///
+    /// WOFF and WOFF2 data require the `woff` feature
+    ///
+    /// # Errors
+    ///
+    /// If the format is not supported, there is an error parsing the font data, or some of the
+    /// required font properties cannot be determined
+    #[inline]
+    pub fn from_any(data: Vec<u8>) -> Result<Self> {
+        match data.get(..4) {
+            #[cfg(feature = "woff")]
+            Some(b"wOFF") => Self::from_woff(data),
+            #[cfg(feature = "woff")]
+            Some(b"wOF2") => Self::from_woff2(data),
+            #[cfg(not(feature = "woff"))]
+            Some(b"wOFF") => Err(Error::UnsupportedFormat(FontFormat::Woff)),
+            #[cfg(not(feature = "woff"))]
+            Some(b"wOF2") => Err(Error::UnsupportedFormat(FontFormat::Woff2)),
+            _ => Self::from_ttf(data),
+        }
+    }
+
+    /// Parse a font from TrueType or OpenType format font data, overriding some font metrics
+    ///
+    /// Metrics given in `overrides` take precedence over values set by the font, which in turn
+    /// take precedence over values measured from the font's glyphs
+    ///
+    /// # Errors
+    ///
+    /// If there is an error parsing the font data, or some of the required font properties
+    /// cannot be determined
+    #[inline]
+    pub fn from_ttf_with_metrics(data: Vec<u8>, overrides: MetricOverrides) -> Result<Self> {
         let face = Face::from_ttf(data)?;
 
-        let family = face
-            .name(name_id::FAMILY)
-            .ok_or_else(|| Error::MissingProperty("font family".to_owned()))?;
+        let family = face.name(name_id::FAMILY).ok_or(Error::MissingProperty {
+            kind: FontProperty::Family,
+        })?;
         let name = face
             .name(name_id::FULL_NAME)
-            .ok_or_else(|| Error::MissingProperty("full font name".to_owned()))?;
+            .ok_or(Error::MissingProperty {
+                kind: FontProperty::FullName,
+            })?;
 
-        let cap_height = Length::new(
-            face.capital_height()
-                .or_else(|| Some(face.glyph_bounds(face.glyph_index('H')?)?.height()))
-                .ok_or_else(|| Error::MissingProperty("capital height".to_owned()))?
-                .into(),
-        );
-        let x_height = Length::new(
-            face.x_height()
-                .or_else(|| Some(face.glyph_bounds(face.glyph_index('x')?)?.height()))
-                .ok_or_else(|| Error::MissingProperty("x height".to_owned()))?
-                .into(),
-        );
+        let cap_height = overrides.cap_height.map_or_else(
+            || {
+                face.capital_height()
+                    .or_else(|| Some(face.glyph_bounds(face.glyph_index('H')?)?.height()))
+                    .map(|height| Length::new(height.into()))
+                    .ok_or(Error::MissingProperty {
+                        kind: FontProperty::CapHeight,
+                    })
+            },
+            Ok,
+        )?;
+        let x_height = overrides.x_height.map_or_else(
+            || {
+                face.x_height()
+                    .or_else(|| Some(face.glyph_bounds(face.glyph_index('x')?)?.height()))
+                    .map(|height| Length::new(height.into()))
+                    .ok_or(Error::MissingProperty {
+                        kind: FontProperty::XHeight,
+                    })
+            },
+            Ok,
+        )?;
 
         Ok(Self {
-            face,
+            face: Arc::new(face),
             family,
             name,
             cap_height,
             x_height,
+            fast_path: false,
         })
     }
 
+    /// Enable or disable the fast path for single-character strings
+    ///
+    /// When enabled, a string consisting of a single printable ASCII character which is present
+    /// in the font is drawn using the font's glyph for that character directly, skipping text
+    /// shaping. This is much faster for the common case of single-character legends, but ignores
+    /// any substitutions the font would apply to the character. Other strings are always shaped
+    #[inline]
+    #[must_use]
+    pub const fn with_fast_path(mut self, enabled: bool) -> Self {
+        self.fast_path = enabled;
+        self
+    }
+
     /// The font family name
     #[inline]
     #[must_use]
@@ -157,6 +298,31 @@ impl Font {
         self.ascender() + self.descender() + self.line_gap()
     }
 
+    /// Calculates the vertical metrics for a block of text, with lines separated as by
+    /// [`str::lines`]
+    #[must_use]
+    pub fn line_metrics(&self, text: &str) -> LineMetrics {
+        let line_height = self.line_height();
+        let baselines: Vec<_> = text
+            .lines()
+            .scan(Length::new(0.0), |baseline, _| {
+                let result = *baseline;
+                *baseline += line_height;
+                Some(result)
+            })
+            .collect();
+        let num_lines = baselines.len();
+        let height = baselines.last().map_or(Length::new(0.0), |&last| {
+            last + line_height - self.line_gap()
+        });
+
+        LineMetrics {
+            num_lines,
+            height,
+            baselines,
+        }
+    }
+
     /// The font's slope angle
     ///
     /// Clockwise (forward) angles are positive
@@ -167,7 +333,7 @@ impl Font {
     #[inline]
     #[must_use]
     pub fn slope(&self) -> Angle {
-        Angle::degrees(-self.face.italic_angle())
+        -Angle::degrees(self.face.italic_angle())
     }
 
     /// The number of glyph outlines in the font
@@ -184,24 +350,51 @@ impl Font {
         self.face.glyph_index(code_point).is_some()
     }
 
+    /// Checks if the font contains colour glyphs, i.e. it has a `COLR` or `SVG ` table
+    ///
+    /// Colour glyphs are not supported by `keyset`, and are drawn using their monochrome outline
+    #[inline]
+    #[must_use]
+    pub fn is_color_font(&self) -> bool {
+        self.face.is_color_font()
+    }
+
     /// Renders a string of text to a path
+    ///
+    /// Any [`Warning`]s encountered while rendering the text are logged. Use
+    /// [`Font::render_string_with_warnings`] to handle these programmatically instead
     #[must_use]
     pub fn render_string(&self, text: &str) -> Path<FontUnit> {
-        let mut buffer = UnicodeBuffer::new();
-        buffer.push_str(text);
-        buffer.guess_segment_properties(); // TODO set properties explicitly?
-        buffer.set_cluster_level(BufferClusterLevel::MonotoneCharacters);
+        let (path, warnings) = self.render_string_with_warnings(text);
+        for warning in warnings {
+            warn!("{warning}");
+        }
+        path
+    }
 
-        // TODO: cache plan?
-        let plan = ShapePlan::new(
-            self.face.inner(),
-            buffer.direction(),
-            Some(buffer.script()),
-            buffer.language().as_ref(),
-            &[],
-        );
+    /// Renders a string of text to a path, also returning any [`Warning`]s encountered while
+    /// rendering the text
+    #[must_use]
+    pub fn render_string_with_warnings(&self, text: &str) -> (Path<FontUnit>, Vec<Warning>) {
+        if let Some((character, glyph_id)) = self.fast_path_glyph(text) {
+            let mut builder = PathBuilder::with_capacity(self.face.outline_length(glyph_id));
+            self.face
+                .outline_glyph(glyph_id, &mut builder, Vector::zero());
+
+            let warnings = self
+                .face
+                .is_color_glyph(glyph_id)
+                .then_some(Warning::ColorGlyphIgnored {
+                    character,
+                    glyph_id,
+                })
+                .into_iter()
+                .collect();
 
-        let glyph_buffer = rustybuzz::shape_with_plan(self.face.inner(), &plan, buffer);
+            return (builder.build(), warnings);
+        }
+
+        let glyph_buffer = self.shape(text);
 
         let infos = glyph_buffer.glyph_infos();
         let positions = glyph_buffer.glyph_positions();
@@ -213,8 +406,10 @@ impl Font {
             .sum();
 
         let mut builder = PathBuilder::with_capacity(capacity);
+        let mut warnings = Vec::new();
         let mut position = Vector::zero();
         for (info, pos) in izip!(infos, positions) {
+            let glyph_id = info.glyph_id.saturating_into(); // guaranteed in u16 range by rustybuzz
             let advance = Vector::new(
                 pos.x_advance.saturating_into(),
                 pos.y_advance.saturating_into(),
@@ -224,16 +419,216 @@ impl Font {
                 pos.y_offset.saturating_into(),
             );
 
-            self.face.outline_glyph(
-                info.glyph_id.saturating_into(), // guaranteed to be in u16 range by rustybuzz
-                &mut builder,
-                position + offset,
-            );
+            if self.face.is_color_glyph(glyph_id) {
+                let character = text
+                    .get(info.cluster.saturating_into()..)
+                    .and_then(|s| s.chars().next())
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                warnings.push(Warning::ColorGlyphIgnored {
+                    character,
+                    glyph_id,
+                });
+            }
+
+            self.face
+                .outline_glyph(glyph_id, &mut builder, position + offset);
 
             position += advance;
         }
 
-        builder.build()
+        (builder.build(), warnings)
+    }
+
+    /// Shapes a string of text, returning the positioned glyphs without rendering them
+    ///
+    /// This is useful for output formats which can embed the font and draw text directly. Only
+    /// horizontal positions and offsets are supported
+    #[must_use]
+    pub fn shape_string(&self, text: &str) -> Vec<ShapedGlyph> {
+        if let Some((_, glyph_id)) = self.fast_path_glyph(text) {
+            return vec![ShapedGlyph {
+                glyph_id,
+                position: Point::origin(),
+                text: text.to_owned(),
+            }];
+        }
+
+        let glyph_buffer = self.shape(text);
+        let infos = glyph_buffer.glyph_infos();
+        let positions = glyph_buffer.glyph_positions();
+
+        let clusters: Vec<usize> = infos
+            .iter()
+            .map(|info| info.cluster.saturating_into())
+            .collect();
+
+        let mut position = Vector::zero();
+        izip!(infos, positions, clusters.iter().enumerate())
+            .map(|(info, pos, (i, &cluster))| {
+                // The first glyph in each cluster gets the cluster's text
+                let is_first = i == 0 || clusters[i - 1] != cluster;
+                let end = clusters[i..]
+                    .iter()
+                    .find(|&&c| c > cluster)
+                    .map_or(text.len(), |&c| c);
+                let glyph_text = is_first
+                    .then(|| text.get(cluster..end))
+                    .flatten()
+                    .unwrap_or_default();
+
+                let offset = Vector::new(
+                    pos.x_offset.saturating_into(),
+                    pos.y_offset.saturating_into(),
+                );
+                let glyph = ShapedGlyph {
+                    glyph_id: info.glyph_id.saturating_into(), // guaranteed in u16 range
+                    position: (position + offset).to_point(),
+                    text: glyph_text.to_owned(),
+                };
+                position += Vector::new(
+                    pos.x_advance.saturating_into(),
+                    pos.y_advance.saturating_into(),
+                );
+                glyph
+            })
+            .collect()
+    }
+
+    /// Renders glyphs positioned by [`Font::shape_string`] to a path
+    ///
+    /// This is equivalent to [`Font::render_string`] for the text the glyphs were shaped from, and
+    /// allows the shaped glyphs to be reused without shaping the text twice. Any [`Warning`]s
+    /// encountered while rendering the glyphs are logged. Use
+    /// [`Font::render_glyphs_with_warnings`] to handle these programmatically instead
+    #[must_use]
+    pub fn render_glyphs(&self, glyphs: &[ShapedGlyph]) -> Path<FontUnit> {
+        let (path, warnings) = self.render_glyphs_with_warnings(glyphs);
+        for warning in warnings {
+            warn!("{warning}");
+        }
+        path
+    }
+
+    /// Renders glyphs positioned by [`Font::shape_string`] to a path, also returning any
+    /// [`Warning`]s encountered while rendering the glyphs
+    #[must_use]
+    pub fn render_glyphs_with_warnings(
+        &self,
+        glyphs: &[ShapedGlyph],
+    ) -> (Path<FontUnit>, Vec<Warning>) {
+        let capacity = glyphs
+            .iter()
+            .map(|glyph| self.face.outline_length(glyph.glyph_id))
+            .sum();
+
+        let mut builder = PathBuilder::with_capacity(capacity);
+        let mut warnings = Vec::new();
+        let mut character = char::REPLACEMENT_CHARACTER;
+        for glyph in glyphs {
+            // Only the first glyph in each cluster has text, the rest belong to the same character
+            character = glyph.text.chars().next().unwrap_or(character);
+
+            if self.face.is_color_glyph(glyph.glyph_id) {
+                warnings.push(Warning::ColorGlyphIgnored {
+                    character,
+                    glyph_id: glyph.glyph_id,
+                });
+            }
+
+            self.face
+                .outline_glyph(glyph.glyph_id, &mut builder, glyph.position.to_vector());
+        }
+
+        (builder.build(), warnings)
+    }
+
+    /// Returns `true` if `text` is shaped identically using this font and `other`
+    ///
+    /// This compares the glyph IDs, advances, and offsets produced by shaping `text` with each
+    /// font. It does not compare the glyphs' outlines, so two fonts with the same glyph IDs and
+    /// metrics but different glyph designs are still considered identical
+    #[must_use]
+    pub fn shapes_identically(&self, other: &Self, text: &str) -> bool {
+        let (buffer, other_buffer) = (self.shape(text), other.shape(text));
+
+        let glyphs = izip!(buffer.glyph_infos(), buffer.glyph_positions());
+        let other_glyphs = izip!(other_buffer.glyph_infos(), other_buffer.glyph_positions());
+
+        buffer.len() == other_buffer.len()
+            && glyphs
+                .zip(other_glyphs)
+                .all(|((info, pos), (other_info, other_pos))| {
+                    info.glyph_id == other_info.glyph_id
+                        && pos.x_advance == other_pos.x_advance
+                        && pos.y_advance == other_pos.y_advance
+                        && pos.x_offset == other_pos.x_offset
+                        && pos.y_offset == other_pos.y_offset
+                })
+    }
+
+    /// The horizontal advance of a glyph in font units, or zero if the glyph does not exist
+    #[inline]
+    #[must_use]
+    pub fn glyph_advance(&self, glyph_id: u16) -> Length<FontUnit> {
+        Length::new(self.face.glyph_advance(glyph_id).unwrap_or(0).into())
+    }
+
+    /// The bounding box of all glyphs in the font in font units
+    #[inline]
+    #[must_use]
+    pub fn bounding_box(&self) -> Rect<FontUnit> {
+        let bbox = self.face.global_bounding_box();
+        Rect::new(
+            Point::new(bbox.x_min.into(), bbox.y_min.into()),
+            Point::new(bbox.x_max.into(), bbox.y_max.into()),
+        )
+    }
+
+    /// The raw font data the font was loaded from
+    #[inline]
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        self.face.data()
+    }
+
+    /// Checks if the font contains TrueType (`glyf`) outlines, as opposed to CFF outlines
+    #[inline]
+    #[must_use]
+    pub fn has_truetype_outlines(&self) -> bool {
+        self.face.has_truetype_outlines()
+    }
+
+    // Returns the character and its glyph if the fast path is enabled and can be used for text
+    fn fast_path_glyph(&self, text: &str) -> Option<(char, u16)> {
+        if !self.fast_path {
+            return None;
+        }
+
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(character), None) if character.is_ascii_graphic() => {
+                Some((character, self.face.glyph_index(character)?))
+            }
+            _ => None,
+        }
+    }
+
+    fn shape(&self, text: &str) -> GlyphBuffer {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.guess_segment_properties(); // TODO set properties explicitly?
+        buffer.set_cluster_level(BufferClusterLevel::MonotoneCharacters);
+
+        // TODO: cache plan?
+        let plan = ShapePlan::new(
+            self.face.inner(),
+            buffer.direction(),
+            Some(buffer.script()),
+            buffer.language().as_ref(),
+            &[],
+        );
+
+        rustybuzz::shape_with_plan(self.face.inner(), &plan, buffer)
     }
 }
 
@@ -244,6 +639,18 @@ mod tests {
 
     use super::*;
 
+    // A subset of Source Code Pro, see resources/fonts/README.md
+    #[cfg(feature = "woff")]
+    const SUBSET_WOFF: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.woff"
+    );
+    #[cfg(feature = "woff")]
+    const SUBSET_WOFF2: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.woff2"
+    );
+
     #[test]
     fn font_default() {
         let default = Font::default();
@@ -275,6 +682,79 @@ mod tests {
         assert_eq!(font.x_height, Length::new(450.0));
     }
 
+    #[cfg(feature = "woff")]
+    #[test]
+    fn font_from_woff() {
+        let woff = std::fs::read(SUBSET_WOFF).unwrap();
+        let font = Font::from_woff(woff).unwrap();
+
+        assert_eq!(font.family(), "Code Subset");
+        assert_eq!(font.num_glyphs(), 38);
+
+        let ttf = std::fs::read(env!("DEMO_TTF")).unwrap();
+        assert!(Font::from_woff(ttf).is_err());
+    }
+
+    #[cfg(feature = "woff")]
+    #[test]
+    fn font_from_woff2() {
+        let woff2 = std::fs::read(SUBSET_WOFF2).unwrap();
+        let font = Font::from_woff2(woff2).unwrap();
+
+        assert_eq!(font.family(), "Code Subset");
+        assert_eq!(font.num_glyphs(), 38);
+
+        let ttf = std::fs::read(env!("DEMO_TTF")).unwrap();
+        assert!(Font::from_woff2(ttf).is_err());
+    }
+
+    #[test]
+    fn font_from_any() {
+        #[cfg(feature = "woff")]
+        {
+            let font = Font::from_any(std::fs::read(SUBSET_WOFF).unwrap()).unwrap();
+            assert_eq!(font.num_glyphs(), 38);
+
+            let font = Font::from_any(std::fs::read(SUBSET_WOFF2).unwrap()).unwrap();
+            assert_eq!(font.num_glyphs(), 38);
+        }
+        #[cfg(not(feature = "woff"))]
+        {
+            let error = Font::from_any(b"wOF2....".to_vec()).unwrap_err();
+            assert_eq!(format!("{error}"), "unsupported font format WOFF2");
+        }
+
+        let ttf = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let font = Font::from_any(ttf).unwrap();
+        assert_eq!(font.num_glyphs(), 3);
+        assert!(Font::from_any(b"invalid".to_vec()).is_err());
+    }
+
+    #[test]
+    fn font_from_ttf_with_metrics() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+
+        let font = Font::from_ttf_with_metrics(data.clone(), MetricOverrides::default()).unwrap();
+        assert_eq!(font.cap_height, Length::new(650.0));
+        assert_eq!(font.x_height, Length::new(450.0));
+
+        let overrides = MetricOverrides {
+            cap_height: Some(Length::new(700.0)),
+            ..MetricOverrides::default()
+        };
+        let font = Font::from_ttf_with_metrics(data.clone(), overrides).unwrap();
+        assert_eq!(font.cap_height, Length::new(700.0));
+        assert_eq!(font.x_height, Length::new(450.0));
+
+        let overrides = MetricOverrides {
+            cap_height: Some(Length::new(700.0)),
+            x_height: Some(Length::new(500.0)),
+        };
+        let font = Font::from_ttf_with_metrics(data, overrides).unwrap();
+        assert_eq!(font.cap_height, Length::new(700.0));
+        assert_eq!(font.x_height, Length::new(500.0));
+    }
+
     #[test]
     fn font_properties() {
         type Length = geom::Length<FontUnit>;
@@ -295,7 +775,71 @@ mod tests {
         assert_eq!(font.num_glyphs(), 3);
 
         let data = std::fs::read(env!("NULL_TTF")).unwrap();
-        let _err = Font::from_ttf(data).unwrap_err();
+        let err = Font::from_ttf(data).unwrap_err();
+        assert_matches!(
+            err,
+            Error::MissingProperty {
+                kind: FontProperty::Family,
+            }
+        );
+    }
+
+    #[test]
+    fn font_line_metrics() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+
+        let metrics = font.line_metrics("A\nV\nAV");
+        assert_eq!(metrics.num_lines, 3);
+        assert_is_close!(metrics.height, font.line_height() * 3.0 - font.line_gap());
+        assert_eq!(metrics.baselines.len(), 3);
+        assert_is_close!(metrics.baselines[0], Length::new(0.0));
+        assert_is_close!(metrics.baselines[2], font.line_height() * 2.0);
+
+        let metrics = font.line_metrics("");
+        assert_eq!(metrics.num_lines, 0);
+        assert_is_close!(metrics.height, Length::new(0.0));
+        assert!(metrics.baselines.is_empty());
+    }
+
+    #[test]
+    fn font_shape_string() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+
+        let glyphs = font.shape_string("AV");
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].glyph_id, 1);
+        assert_eq!(glyphs[0].text, "A");
+        assert_is_close!(glyphs[0].position, Point::new(0.0, 0.0));
+        assert_eq!(glyphs[1].glyph_id, 2);
+        assert_eq!(glyphs[1].text, "V");
+        assert!(glyphs[1].position.x > 0.0);
+
+        assert!(font.shape_string("").is_empty());
+    }
+
+    #[test]
+    fn font_shapes_identically() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+        let notdef = Font::default(); // Only contains .notdef
+
+        assert!(font.shapes_identically(&font.clone(), "AV"));
+        assert!(font.shapes_identically(&notdef, ""));
+        assert!(!font.shapes_identically(&notdef, "AV"));
+        assert!(!notdef.shapes_identically(&font, "A"));
+    }
+
+    #[test]
+    fn font_glyph_metrics() {
+        let font = Font::from_ttf(std::fs::read(env!("DEMO_TTF")).unwrap()).unwrap();
+
+        assert_is_close!(font.glyph_advance(1), Length::new(540.0));
+        assert_is_close!(font.glyph_advance(100), Length::new(0.0));
+        assert_is_close!(
+            font.bounding_box(),
+            Rect::new(Point::new(6.0, 0.0), Point::new(600.0, 700.0))
+        );
+        assert!(font.has_truetype_outlines());
+        assert_eq!(font.data(), std::fs::read(env!("DEMO_TTF")).unwrap());
     }
 
     #[test]
@@ -319,4 +863,190 @@ mod tests {
         let path = font.render_string("P");
         assert_eq!(path.len(), 12); // == .notdef length
     }
+
+    #[test]
+    fn font_render_string_with_warnings() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let font = Font::from_ttf(data).unwrap();
+
+        let (path, warnings) = font.render_string_with_warnings("AV");
+        assert_eq!(path.len(), 24);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn font_with_fast_path() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let shaped = Font::from_ttf(data).unwrap();
+        let fast = shaped.clone().with_fast_path(true);
+
+        for text in ["A", "V", "P", "AV", " ", ""] {
+            let (expected, _) = shaped.render_string_with_warnings(text);
+            let (path, warnings) = fast.render_string_with_warnings(text);
+            assert_eq!(path.len(), expected.len());
+            assert_is_close!(path.bounds, expected.bounds);
+            assert!(warnings.is_empty());
+
+            let expected = shaped.shape_string(text);
+            let glyphs = fast.shape_string(text);
+            assert_eq!(glyphs.len(), expected.len());
+            for (glyph, exp) in glyphs.iter().zip(&expected) {
+                assert_eq!(glyph.glyph_id, exp.glyph_id);
+                assert_is_close!(glyph.position, exp.position);
+                assert_eq!(glyph.text, exp.text);
+            }
+        }
+    }
+
+    #[test]
+    fn font_render_glyphs() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let shaped = Font::from_ttf(data).unwrap();
+        let fast = shaped.clone().with_fast_path(true);
+
+        for font in [&shaped, &fast] {
+            for text in ["A", "AV", "P", ""] {
+                let expected = font.render_string(text);
+                let (path, warnings) = font.render_glyphs_with_warnings(&font.shape_string(text));
+                assert_eq!(path.len(), expected.len());
+                assert_is_close!(path.bounds, expected.bounds);
+                assert!(warnings.is_empty());
+            }
+        }
+
+        let font = Font::from_ttf(with_color_glyph(
+            &std::fs::read(env!("DEMO_TTF")).unwrap(),
+            1,
+        ))
+        .unwrap();
+        let (_, warnings) = font.render_glyphs_with_warnings(&font.shape_string("VA"));
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            Warning::ColorGlyphIgnored {
+                character: 'A',
+                glyph_id: 1
+            }
+        );
+    }
+
+    #[test]
+    fn font_is_color_font() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let font = Font::from_ttf(data).unwrap();
+
+        assert!(!font.is_color_font());
+        assert!(!Font::default().is_color_font());
+
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let font = Font::from_ttf(with_color_glyph(&data, 1)).unwrap();
+        assert!(font.is_color_font());
+    }
+
+    #[test]
+    fn font_render_string_color_glyph() {
+        let data = std::fs::read(env!("DEMO_TTF")).unwrap();
+        let shaped = Font::from_ttf(with_color_glyph(&data, 1)).unwrap(); // 'A'
+        let fast = shaped.clone().with_fast_path(true);
+        let plain = Font::from_ttf(data).unwrap();
+
+        for font in [&shaped, &fast] {
+            // The colour glyph is still drawn using its outline
+            let (path, warnings) = font.render_string_with_warnings("A");
+            assert_eq!(path.len(), plain.render_string("A").len());
+            assert_eq!(warnings.len(), 1);
+            assert_matches!(
+                warnings[0],
+                Warning::ColorGlyphIgnored {
+                    character: 'A',
+                    glyph_id: 1
+                }
+            );
+
+            let (_, warnings) = font.render_string_with_warnings("V");
+            assert!(warnings.is_empty());
+        }
+
+        let (path, warnings) = shaped.render_string_with_warnings("VA");
+        assert_eq!(path.len(), plain.render_string("VA").len());
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            Warning::ColorGlyphIgnored {
+                character: 'A',
+                glyph_id: 1
+            }
+        );
+    }
+
+    // Add minimal COLR and CPAL tables to TrueType font data, making the given glyph a colour glyph
+    // with a single layer drawn using its own outline
+    fn with_color_glyph(ttf: &[u8], glyph_id: u16) -> Vec<u8> {
+        const HEADER_LEN: usize = 12;
+        const RECORD_LEN: usize = 16;
+
+        let read_u32 =
+            |offset: usize| u32::from_be_bytes(ttf[offset..offset + 4].try_into().unwrap());
+        let num_tables = usize::from(u16::from_be_bytes([ttf[4], ttf[5]]));
+
+        let mut tables: Vec<([u8; 4], Vec<u8>)> = (0..num_tables)
+            .map(|i| {
+                let start = HEADER_LEN + i * RECORD_LEN;
+                let offset = read_u32(start + 8) as usize;
+                let length = read_u32(start + 12) as usize;
+                (
+                    ttf[start..start + 4].try_into().unwrap(),
+                    ttf[offset..offset + length].to_vec(),
+                )
+            })
+            .collect();
+
+        // COLR version 0 with one base glyph record and one layer record
+        let colr = [
+            &0_u16.to_be_bytes()[..], // version
+            &1_u16.to_be_bytes(),     // numBaseGlyphRecords
+            &14_u32.to_be_bytes(),    // baseGlyphRecordsOffset
+            &20_u32.to_be_bytes(),    // layerRecordsOffset
+            &1_u16.to_be_bytes(),     // numLayerRecords
+            &glyph_id.to_be_bytes(),  // baseGlyphRecord.glyphID
+            &0_u16.to_be_bytes(),     // baseGlyphRecord.firstLayerIndex
+            &1_u16.to_be_bytes(),     // baseGlyphRecord.numLayers
+            &glyph_id.to_be_bytes(),  // layerRecord.glyphID
+            &0_u16.to_be_bytes(),     // layerRecord.paletteIndex
+        ]
+        .concat();
+        // CPAL version 0 with a single palette containing a single red entry
+        let cpal = [
+            &0_u16.to_be_bytes()[..],  // version
+            &1_u16.to_be_bytes(),      // numPaletteEntries
+            &1_u16.to_be_bytes(),      // numPalettes
+            &1_u16.to_be_bytes(),      // numColorRecords
+            &14_u32.to_be_bytes(),     // colorRecordsArrayOffset
+            &0_u16.to_be_bytes(),      // colorRecordIndices[0]
+            &[0x00, 0x00, 0xff, 0xff], // colorRecord (BGRA)
+        ]
+        .concat();
+        tables.push((*b"COLR", colr));
+        tables.push((*b"CPAL", cpal));
+        tables.sort_by_key(|&(tag, _)| tag);
+
+        let mut header = ttf[..HEADER_LEN].to_vec();
+        header[4..6].copy_from_slice(&u16::try_from(tables.len()).unwrap().to_be_bytes());
+
+        let mut offset = HEADER_LEN + tables.len() * RECORD_LEN;
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        for (tag, table) in tables {
+            directory.extend(tag);
+            directory.extend([0; 4]); // checksum, which isn't validated
+            directory.extend(u32::try_from(offset).unwrap().to_be_bytes());
+            directory.extend(u32::try_from(table.len()).unwrap().to_be_bytes());
+
+            offset += table.len().next_multiple_of(4);
+            data.extend(&table);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        [header, directory, data].concat()
+    }
 }
@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// A non-fatal issue encountered while rendering text
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The glyph has colour data (from a `COLR` or `SVG ` table) which is not supported by
+    /// `keyset`. The glyph's monochrome outline is drawn instead, if it has one
+    ColorGlyphIgnored {
+        /// The character the glyph was rendered for
+        character: char,
+        /// The ID of the glyph in the font
+        glyph_id: u16,
+    },
+}
+
+impl fmt::Display for Warning {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::ColorGlyphIgnored {
+                character,
+                glyph_id,
+            } => write!(
+                f,
+                "ignoring colour data for glyph {glyph_id} ({character:?}). Note colour glyphs \
+                are currently drawn using their monochrome outline"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_fmt() {
+        let color_glyph_ignored = Warning::ColorGlyphIgnored {
+            character: 'A',
+            glyph_id: 1,
+        };
+        assert_eq!(
+            format!("{color_glyph_ignored}"),
+            "ignoring colour data for glyph 1 ('A'). Note colour glyphs are currently drawn \
+            using their monochrome outline"
+        );
+    }
+}
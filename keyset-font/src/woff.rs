@@ -0,0 +1,185 @@
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+use crate::{Error, Result};
+
+// See: <https://www.w3.org/TR/WOFF/>
+const WOFF_MAGIC: [u8; 4] = *b"wOFF";
+
+const WOFF_HEADER_LEN: usize = 44;
+const WOFF_TABLE_ENTRY_LEN: usize = 20;
+pub const SFNT_HEADER_LEN: usize = 12;
+pub const SFNT_TABLE_RECORD_LEN: usize = 16;
+
+struct TableEntry {
+    tag: [u8; 4],
+    offset: usize,
+    comp_length: usize,
+    orig_length: usize,
+    checksum: u32,
+}
+
+/// Decode WOFF font data into the TrueType or OpenType font data it contains
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    if data.get(..4) != Some(&WOFF_MAGIC[..]) {
+        return Err(Error::InvalidWoff);
+    }
+    let flavor = read_u32(data, 4)?;
+    let num_tables = read_u16(data, 12)?;
+
+    let entries = (0..usize::from(num_tables))
+        .map(|i| {
+            let start = WOFF_HEADER_LEN + i * WOFF_TABLE_ENTRY_LEN;
+            Ok(TableEntry {
+                tag: read_u32(data, start)?.to_be_bytes(),
+                offset: read_u32(data, start + 4)?
+                    .try_into()
+                    .map_err(|_| Error::InvalidWoff)?,
+                comp_length: read_u32(data, start + 8)?
+                    .try_into()
+                    .map_err(|_| Error::InvalidWoff)?,
+                orig_length: read_u32(data, start + 12)?
+                    .try_into()
+                    .map_err(|_| Error::InvalidWoff)?,
+                checksum: read_u32(data, start + 16)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Table data starts after the sfnt header and table records, with each table 4-byte aligned
+    let mut offset = SFNT_HEADER_LEN + entries.len() * SFNT_TABLE_RECORD_LEN;
+    let mut header = sfnt_header(flavor, num_tables)?;
+    let mut tables = Vec::new();
+
+    for entry in entries {
+        let comp_data = entry
+            .offset
+            .checked_add(entry.comp_length)
+            .and_then(|end| data.get(entry.offset..end))
+            .ok_or(Error::InvalidWoff)?;
+
+        // Tables are only compressed if it makes them smaller. The limit stops malicious data from
+        // decompressing to more than the table's stated length
+        let table = if entry.comp_length < entry.orig_length {
+            decompress_to_vec_zlib_with_limit(comp_data, entry.orig_length)
+                .map_err(|_| Error::InvalidWoff)?
+        } else {
+            comp_data.to_vec()
+        };
+        if table.len() != entry.orig_length {
+            return Err(Error::InvalidWoff);
+        }
+
+        header.extend(entry.tag);
+        header.extend(entry.checksum.to_be_bytes());
+        header.extend(
+            u32::try_from(offset)
+                .map_err(|_| Error::InvalidWoff)?
+                .to_be_bytes(),
+        );
+        header.extend(
+            u32::try_from(table.len())
+                .map_err(|_| Error::InvalidWoff)?
+                .to_be_bytes(),
+        );
+
+        let padded_len = table.len().next_multiple_of(4);
+        tables.extend(table);
+        tables.resize(tables.len() + padded_len - entry.orig_length, 0);
+        offset += padded_len;
+    }
+
+    header.extend(tables);
+    Ok(header)
+}
+
+/// Create the header of an sfnt (TrueType or OpenType) font with the given number of tables
+pub fn sfnt_header(flavor: u32, num_tables: u16) -> Result<Vec<u8>> {
+    // searchRange, entrySelector, and rangeShift are derived from the largest power of 2 <= the
+    // number of tables. These overflow a u16 for fonts with 4096 or more tables
+    let entry_selector = num_tables.checked_ilog2().unwrap_or(0);
+    let search_range = (1_u32 << entry_selector) * 16;
+    let range_shift = u32::from(num_tables) * 16 - search_range;
+
+    let mut header = Vec::with_capacity(SFNT_HEADER_LEN);
+    header.extend(flavor.to_be_bytes());
+    header.extend(num_tables.to_be_bytes());
+    for field in [search_range, entry_selector, range_shift] {
+        let field = u16::try_from(field).map_err(|_| Error::InvalidWoff)?;
+        header.extend(field.to_be_bytes());
+    }
+    Ok(header)
+}
+
+pub fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u16::from_be_bytes)
+        .ok_or(Error::InvalidWoff)
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_be_bytes)
+        .ok_or(Error::InvalidWoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A subset of Source Code Pro, see resources/fonts/README.md
+    const SUBSET_TTF: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.ttf"
+    );
+    const SUBSET_WOFF: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.woff"
+    );
+
+    #[test]
+    fn woff_decode() {
+        let ttf = std::fs::read(SUBSET_TTF).unwrap();
+        let woff = std::fs::read(SUBSET_WOFF).unwrap();
+
+        assert_eq!(decode(&woff).unwrap(), ttf);
+    }
+
+    #[test]
+    fn woff_decode_invalid() {
+        assert!(decode(b"invalid").is_err());
+        assert!(decode(b"wOFF").is_err());
+
+        let woff = std::fs::read(SUBSET_WOFF).unwrap();
+        assert!(decode(&woff[..woff.len() / 2]).is_err());
+
+        // A table which decompresses to more than its stated length
+        let mut too_large = woff.clone();
+        let entry = (0..usize::from(read_u16(&woff, 12).unwrap()))
+            .map(|i| WOFF_HEADER_LEN + i * WOFF_TABLE_ENTRY_LEN)
+            .find(|&e| read_u32(&woff, e + 8).unwrap() + 1 < read_u32(&woff, e + 12).unwrap())
+            .unwrap();
+        let orig_length = read_u32(&woff, entry + 12).unwrap() - 1;
+        too_large[entry + 12..entry + 16].copy_from_slice(&orig_length.to_be_bytes());
+        assert!(decode(&too_large).is_err());
+
+        // searchRange and rangeShift don't fit in the sfnt header with this many tables
+        let mut too_many_tables = woff;
+        too_many_tables[12..14].copy_from_slice(&4096_u16.to_be_bytes());
+        too_many_tables.resize(WOFF_HEADER_LEN + 4096 * WOFF_TABLE_ENTRY_LEN, 0);
+        assert!(decode(&too_many_tables).is_err());
+    }
+
+    #[test]
+    fn woff_sfnt_header() {
+        let header = sfnt_header(0x0001_0000, 11).unwrap();
+        assert_eq!(header, [0, 1, 0, 0, 0, 11, 0, 128, 0, 3, 0, 48]);
+
+        let header = sfnt_header(0x0001_0000, 4095).unwrap();
+        assert_eq!(header[6..], [0x80, 0x00, 0, 11, 0x7f, 0xf0]);
+
+        assert!(sfnt_header(0x0001_0000, 4096).is_err());
+        assert!(sfnt_header(0x0001_0000, u16::MAX).is_err());
+    }
+}
@@ -0,0 +1,1058 @@
+use std::collections::BTreeMap;
+use std::io::Read as _;
+
+use brotli_decompressor::Decompressor;
+
+use crate::woff::{read_u16, sfnt_header, SFNT_HEADER_LEN, SFNT_TABLE_RECORD_LEN};
+use crate::{Error, FontFormat, Result};
+
+// See: <https://www.w3.org/TR/WOFF2/>
+//
+// The decoder is implemented here rather than wrapping a crate. The WOFF2 decoders on crates.io
+// either bind Google's C++ reference implementation, which would need a C++ toolchain to build, or
+// are part of complete font parsing libraries such as allsorts, which would duplicate what
+// rustybuzz already does. Brotli decompression is still delegated to brotli-decompressor. The
+// decoder only reads through the bounds-checked Reader below, and every length read from the file
+// is checked before use, so malformed or truncated data is reported as Error::InvalidWoff
+const WOFF2_MAGIC: [u8; 4] = *b"wOF2";
+const COLLECTION_FLAVOR: [u8; 4] = *b"ttcf";
+
+const WOFF2_HEADER_LEN: usize = 48;
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
+// Tags which can be encoded in the table directory using their index
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+const ARBITRARY_TAG: u8 = 63;
+
+// Simple glyph flags
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// Composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+// Transformed hmtx flags
+const PROPORTIONAL_LSBS_OMITTED: u8 = 0x01;
+const MONOSPACED_LSBS_OMITTED: u8 = 0x02;
+const LSBS_OMITTED: u8 = PROPORTIONAL_LSBS_OMITTED | MONOSPACED_LSBS_OMITTED;
+
+struct TableEntry {
+    tag: [u8; 4],
+    length: usize,
+    transformed: bool,
+}
+
+// The reconstructed glyf and loca tables
+struct GlyfTables {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+    x_mins: Vec<i16>,
+}
+
+// Reads big-endian values from the start of some data, advancing past them
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let (bytes, rest) = self.data.split_at_checked(len).ok_or(Error::InvalidWoff)?;
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn take(&mut self, len: usize) -> Result<Self> {
+        self.bytes(len).map(Self::new)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.bytes(N)?.try_into().map_err(|_| Error::InvalidWoff)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        self.array().map(u8::from_be_bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        self.array().map(i16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn length(&mut self) -> Result<usize> {
+        self.u32()?.try_into().map_err(|_| Error::InvalidWoff)
+    }
+
+    // A variable length UIntBase128 value of up to 5 bytes
+    fn base128(&mut self) -> Result<usize> {
+        let mut value = 0_u32;
+        for i in 0..5 {
+            let byte = self.u8()?;
+            // Leading zeros and values which overflow a u32 are invalid
+            if (i == 0 && byte == 0x80) || value.leading_zeros() < 7 {
+                return Err(Error::InvalidWoff);
+            }
+            value = (value << 7) | u32::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return value.try_into().map_err(|_| Error::InvalidWoff);
+            }
+        }
+        Err(Error::InvalidWoff)
+    }
+
+    // A variable length 255UInt16 value of 1 to 3 bytes
+    fn uint255(&mut self) -> Result<usize> {
+        let value = match self.u8()? {
+            253 => self.u16()?,
+            254 => u16::from(self.u8()?) + 253 * 2,
+            255 => u16::from(self.u8()?) + 253,
+            code => u16::from(code),
+        };
+        Ok(usize::from(value))
+    }
+}
+
+/// Decode WOFF2 font data into the TrueType or OpenType font data it contains
+///
+/// Font collections are not supported
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut header = Reader::new(data);
+    if header.array()? != WOFF2_MAGIC {
+        return Err(Error::InvalidWoff);
+    }
+    let flavor = header.array()?;
+    if flavor == COLLECTION_FLAVOR {
+        return Err(Error::UnsupportedFormat(FontFormat::Woff2));
+    }
+    let _length = header.u32()?;
+    let num_tables = header.u16()?;
+    let _reserved = header.u16()?;
+    let total_sfnt_size = header.u32()?;
+    let compressed_len = header.length()?;
+
+    // The table directory follows the header, skipping the version, metadata, and private data
+    let mut directory = Reader::new(data.get(WOFF2_HEADER_LEN..).ok_or(Error::InvalidWoff)?);
+    let entries = (0..num_tables)
+        .map(|_| {
+            let flags = directory.u8()?;
+            let tag = match flags & 0x3f {
+                ARBITRARY_TAG => directory.array()?,
+                index => *KNOWN_TAGS[usize::from(index)],
+            };
+            let orig_length = directory.base128()?;
+
+            // glyf and loca use version 3 for the null transform, all other tables use version 0
+            let version = flags >> 6;
+            let transformed = if matches!(&tag, b"glyf" | b"loca") {
+                version != 3
+            } else {
+                version != 0
+            };
+            let length = if transformed {
+                directory.base128()?
+            } else {
+                orig_length
+            };
+            Ok(TableEntry {
+                tag,
+                length,
+                transformed,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // All tables are compressed together in a single brotli stream
+    let stream = decompress(directory.bytes(compressed_len)?, &entries, total_sfnt_size)?;
+    let mut stream = Reader::new(&stream);
+
+    // BTreeMap keeps the tables sorted by tag, as required for the sfnt table records
+    let mut tables = BTreeMap::new();
+    let mut insert = |tag: [u8; 4], table: Vec<u8>| {
+        tables
+            .insert(tag, table)
+            .map_or(Ok(()), |_| Err(Error::InvalidWoff))
+    };
+    let mut x_mins = None;
+    let mut hmtx = None;
+    for entry in entries {
+        let table = stream.bytes(entry.length)?;
+        match (entry.transformed, &entry.tag) {
+            (false, _) => insert(entry.tag, table.to_vec())?,
+            (true, b"glyf") => {
+                let glyf = decode_glyf(table)?;
+                insert(*b"glyf", glyf.glyf)?;
+                insert(*b"loca", glyf.loca)?;
+                x_mins = Some(glyf.x_mins);
+            }
+            // loca is reconstructed along with glyf
+            (true, b"loca") if table.is_empty() => {}
+            (true, b"hmtx") => hmtx = Some(table),
+            (true, _) => return Err(Error::InvalidWoff),
+        }
+    }
+
+    // hmtx is reconstructed from glyf, so can only be done once every table is decoded
+    if let Some(hmtx) = hmtx {
+        let x_mins = x_mins.ok_or(Error::InvalidWoff)?;
+        let hhea = tables.get(b"hhea").ok_or(Error::InvalidWoff)?;
+        let num_h_metrics = read_u16(hhea, 34)?;
+        let hmtx = decode_hmtx(hmtx, usize::from(num_h_metrics), &x_mins)?;
+        tables.insert(*b"hmtx", hmtx);
+    }
+
+    // Table data starts after the sfnt header and table records, with each table 4-byte aligned
+    let mut offset = SFNT_HEADER_LEN + tables.len() * SFNT_TABLE_RECORD_LEN;
+    let num_tables = u16::try_from(tables.len()).map_err(|_| Error::InvalidWoff)?;
+    let mut header = sfnt_header(u32::from_be_bytes(flavor), num_tables)?;
+    let mut data = Vec::new();
+
+    for (tag, table) in tables {
+        header.extend(tag);
+        header.extend(checksum(&table).to_be_bytes());
+        header.extend(
+            u32::try_from(offset)
+                .map_err(|_| Error::InvalidWoff)?
+                .to_be_bytes(),
+        );
+        header.extend(
+            u32::try_from(table.len())
+                .map_err(|_| Error::InvalidWoff)?
+                .to_be_bytes(),
+        );
+
+        offset += table.len().next_multiple_of(4);
+        data.extend(table);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+
+    header.extend(data);
+    Ok(header)
+}
+
+// Decompress the brotli stream containing the font's tables, which must be exactly the length of
+// the tables given in the table directory
+fn decompress(data: &[u8], entries: &[TableEntry], total_sfnt_size: u32) -> Result<Vec<u8>> {
+    let len = entries
+        .iter()
+        .try_fold(0_usize, |len, entry| len.checked_add(entry.length))
+        .ok_or(Error::InvalidWoff)?;
+
+    // The tables can't be larger than the decoded font, so limit the output to stop malicious
+    // data from decompressing to an excessive size
+    let mut stream = Vec::new();
+    Decompressor::new(data, BROTLI_BUFFER_SIZE)
+        .take(u64::from(total_sfnt_size))
+        .read_to_end(&mut stream)
+        .map_err(|_| Error::InvalidWoff)?;
+
+    if stream.len() == len {
+        Ok(stream)
+    } else {
+        Err(Error::InvalidWoff)
+    }
+}
+
+// Reconstruct the glyf and loca tables from the transformed glyf table, also returning the minimum
+// x coordinate of each glyph for reconstructing hmtx
+fn decode_glyf(data: &[u8]) -> Result<GlyfTables> {
+    let mut reader = Reader::new(data);
+    let _reserved = reader.u16()?;
+    let option_flags = reader.u16()?;
+    let num_glyphs = usize::from(reader.u16()?);
+    let index_format = reader.u16()?;
+    if index_format > 1 {
+        return Err(Error::InvalidWoff);
+    }
+
+    // The lengths of each of the streams which follow the header
+    let mut lengths = [0; 7];
+    for length in &mut lengths {
+        *length = reader.length()?;
+    }
+    let mut n_contours_stream = reader.take(lengths[0])?;
+    let mut n_points_stream = reader.take(lengths[1])?;
+    let mut flags_stream = reader.take(lengths[2])?;
+    let mut glyph_stream = reader.take(lengths[3])?;
+    let mut composite_stream = reader.take(lengths[4])?;
+    let mut bbox_stream = reader.take(lengths[5])?;
+    let mut instruction_stream = reader.take(lengths[6])?;
+
+    let bbox_bitmap = bbox_stream.bytes(4 * num_glyphs.div_ceil(32))?;
+    let overlap_bitmap = if option_flags & 0x0001 != 0 {
+        Some(reader.bytes(num_glyphs.div_ceil(8))?)
+    } else {
+        None
+    };
+    let bit_set = |bitmap: &[u8], index: usize| bitmap[index / 8] & (0x80 >> (index % 8)) != 0;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(num_glyphs + 1);
+    let mut x_mins = Vec::with_capacity(num_glyphs);
+
+    for index in 0..num_glyphs {
+        offsets.push(glyf.len());
+        let has_bbox = bit_set(bbox_bitmap, index);
+
+        match n_contours_stream.i16()? {
+            // An empty glyph has no data and can't have a bounding box
+            0 if !has_bbox => x_mins.push(0),
+            n_contours @ 1.. => {
+                let mut end_points = Vec::new();
+                let mut num_points = 0;
+                for _ in 0..n_contours {
+                    num_points += n_points_stream.uint255()?;
+                    let end_point = u16::try_from(num_points.wrapping_sub(1))
+                        .map_err(|_| Error::InvalidWoff)?;
+                    end_points.push(end_point);
+                }
+
+                let flags = flags_stream.bytes(num_points)?;
+                let points = decode_points(flags, &mut glyph_stream)?;
+                let instructions_len = glyph_stream.uint255()?;
+                let instructions = instruction_stream.bytes(instructions_len)?;
+
+                let bbox = if has_bbox {
+                    read_bbox(&mut bbox_stream)?
+                } else {
+                    points_bbox(&points)
+                };
+                let overlap = overlap_bitmap.is_some_and(|bitmap| bit_set(bitmap, index));
+
+                glyf.extend(n_contours.to_be_bytes());
+                glyf.extend(bbox.iter().flat_map(|v| v.to_be_bytes()));
+                glyf.extend(end_points.iter().flat_map(|p| p.to_be_bytes()));
+                glyf.extend(
+                    u16::try_from(instructions_len)
+                        .map_err(|_| Error::InvalidWoff)?
+                        .to_be_bytes(),
+                );
+                glyf.extend(instructions);
+                encode_points(&mut glyf, &points, overlap);
+                x_mins.push(bbox[0]);
+            }
+            // Composite glyphs must have an explicit bounding box
+            -1 if has_bbox => {
+                let bbox = read_bbox(&mut bbox_stream)?;
+                let (components, have_instructions) = read_components(&mut composite_stream)?;
+
+                glyf.extend((-1_i16).to_be_bytes());
+                glyf.extend(bbox.iter().flat_map(|v| v.to_be_bytes()));
+                glyf.extend(components);
+                if have_instructions {
+                    let instructions_len = glyph_stream.uint255()?;
+                    glyf.extend(
+                        u16::try_from(instructions_len)
+                            .map_err(|_| Error::InvalidWoff)?
+                            .to_be_bytes(),
+                    );
+                    glyf.extend(instruction_stream.bytes(instructions_len)?);
+                }
+                x_mins.push(bbox[0]);
+            }
+            _ => return Err(Error::InvalidWoff),
+        }
+
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    offsets.push(glyf.len());
+
+    // Short loca offsets are stored divided by 2
+    let mut loca = Vec::with_capacity(offsets.len() * if index_format == 0 { 2 } else { 4 });
+    for offset in offsets {
+        if index_format == 0 {
+            let offset = u16::try_from(offset / 2).map_err(|_| Error::InvalidWoff)?;
+            loca.extend(offset.to_be_bytes());
+        } else {
+            let offset = u32::try_from(offset).map_err(|_| Error::InvalidWoff)?;
+            loca.extend(offset.to_be_bytes());
+        }
+    }
+
+    Ok(GlyfTables { glyf, loca, x_mins })
+}
+
+fn read_bbox(reader: &mut Reader<'_>) -> Result<[i16; 4]> {
+    Ok([reader.i16()?, reader.i16()?, reader.i16()?, reader.i16()?])
+}
+
+fn points_bbox(points: &[(i16, i16, bool)]) -> [i16; 4] {
+    points.iter().fold(
+        [i16::MAX, i16::MAX, i16::MIN, i16::MIN],
+        |[x_min, y_min, x_max, y_max], &(x, y, _)| {
+            [x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y)]
+        },
+    )
+}
+
+// Decode a simple glyph's points from their flags and triplet-encoded coordinates
+fn decode_points(flags: &[u8], glyph_stream: &mut Reader<'_>) -> Result<Vec<(i16, i16, bool)>> {
+    // Apply the sign of a delta from the least significant bit of the flag
+    let with_sign = |flag: u8, value: u16| {
+        let value = i16::try_from(value).map_err(|_| Error::InvalidWoff)?;
+        Ok::<_, Error>(if flag & 1 == 0 { -value } else { value })
+    };
+
+    let (mut x, mut y) = (0_i16, 0_i16);
+    flags
+        .iter()
+        .map(|&flag| {
+            let on_curve = flag & 0x80 == 0;
+            let flag = flag & 0x7f;
+            let (dx, dy) = match flag {
+                0..10 => {
+                    let b0 = glyph_stream.u8()?;
+                    let dy = (u16::from(flag & 0x0e) << 7) + u16::from(b0);
+                    (0, with_sign(flag, dy)?)
+                }
+                10..20 => {
+                    let b0 = glyph_stream.u8()?;
+                    let dx = (u16::from((flag - 10) & 0x0e) << 7) + u16::from(b0);
+                    (with_sign(flag, dx)?, 0)
+                }
+                20..84 => {
+                    let (b0, b1) = (flag - 20, glyph_stream.u8()?);
+                    let dx = 1 + u16::from(b0 & 0x30) + u16::from(b1 >> 4);
+                    let dy = 1 + (u16::from(b0 & 0x0c) << 2) + u16::from(b1 & 0x0f);
+                    (with_sign(flag, dx)?, with_sign(flag >> 1, dy)?)
+                }
+                84..120 => {
+                    let (b0, [b1, b2]) = (flag - 84, glyph_stream.array()?);
+                    let dx = 1 + (u16::from(b0 / 12) << 8) + u16::from(b1);
+                    let dy = 1 + (u16::from((b0 % 12) >> 2) << 8) + u16::from(b2);
+                    (with_sign(flag, dx)?, with_sign(flag >> 1, dy)?)
+                }
+                120..124 => {
+                    let [b0, b1, b2] = glyph_stream.array()?;
+                    let dx = (u16::from(b0) << 4) + u16::from(b1 >> 4);
+                    let dy = (u16::from(b1 & 0x0f) << 8) + u16::from(b2);
+                    (with_sign(flag, dx)?, with_sign(flag >> 1, dy)?)
+                }
+                _ => {
+                    let [b0, b1, b2, b3] = glyph_stream.array()?;
+                    let dx = u16::from_be_bytes([b0, b1]);
+                    let dy = u16::from_be_bytes([b2, b3]);
+                    (with_sign(flag, dx)?, with_sign(flag >> 1, dy)?)
+                }
+            };
+            x = x.wrapping_add(dx);
+            y = y.wrapping_add(dy);
+            Ok((x, y, on_curve))
+        })
+        .collect()
+}
+
+// Encode a simple glyph's points as flags followed by x and y coordinates, using short vectors
+// where possible
+fn encode_points(glyf: &mut Vec<u8>, points: &[(i16, i16, bool)], overlap: bool) {
+    let deltas: Vec<_> = points
+        .iter()
+        .scan((0_i16, 0_i16), |prev, &(x, y, on_curve)| {
+            let delta = (x.wrapping_sub(prev.0), y.wrapping_sub(prev.1));
+            *prev = (x, y);
+            Some((delta, on_curve))
+        })
+        .collect();
+
+    let coordinate_flag = |delta: i16, short: u8, same_or_positive: u8| match delta {
+        0 => same_or_positive,
+        1..=255 => short | same_or_positive,
+        -255..=-1 => short,
+        _ => 0,
+    };
+    let flags: Vec<_> = deltas
+        .iter()
+        .enumerate()
+        .map(|(i, &((dx, dy), on_curve))| {
+            let mut flag = coordinate_flag(dx, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE)
+                | coordinate_flag(dy, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE);
+            if on_curve {
+                flag |= ON_CURVE_POINT;
+            }
+            // The overlap flag is only set on the first point
+            if overlap && i == 0 {
+                flag |= OVERLAP_SIMPLE;
+            }
+            flag
+        })
+        .collect();
+    glyf.extend(&flags);
+
+    let mut encode_coordinate = |delta: i16, flag: u8, short: u8, same_or_positive: u8| {
+        if flag & short != 0 {
+            glyf.extend(delta.unsigned_abs().to_be_bytes().last());
+        } else if flag & same_or_positive == 0 {
+            glyf.extend(delta.to_be_bytes());
+        }
+    };
+    for (&((dx, _), _), &flag) in deltas.iter().zip(&flags) {
+        encode_coordinate(dx, flag, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE);
+    }
+    for (&((_, dy), _), &flag) in deltas.iter().zip(&flags) {
+        encode_coordinate(dy, flag, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE);
+    }
+}
+
+// Read the components of a composite glyph, which are stored unchanged. Also returns whether the
+// glyph has instructions
+fn read_components<'a>(composite_stream: &mut Reader<'a>) -> Result<(&'a [u8], bool)> {
+    let start = composite_stream.data;
+    let mut have_instructions = false;
+    loop {
+        let flags = composite_stream.u16()?;
+        let _glyph_index = composite_stream.u16()?;
+        let args_len = if flags & ARG_1_AND_2_ARE_WORDS == 0 {
+            2
+        } else {
+            4
+        };
+        let transform_len = if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        composite_stream.bytes(args_len + transform_len)?;
+
+        have_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    let len = start.len() - composite_stream.data.len();
+    Ok((&start[..len], have_instructions))
+}
+
+// Reconstruct the hmtx table from the transformed hmtx table, using each glyph's minimum x
+// coordinate for any omitted left side bearings
+fn decode_hmtx(data: &[u8], num_h_metrics: usize, x_mins: &[i16]) -> Result<Vec<u8>> {
+    let mut reader = Reader::new(data);
+    let flags = reader.u8()?;
+    // Reserved bits must be clear, and at least one of the left side bearing arrays is omitted
+    if flags & !LSBS_OMITTED != 0
+        || flags & LSBS_OMITTED == 0
+        || !(1..=x_mins.len()).contains(&num_h_metrics)
+    {
+        return Err(Error::InvalidWoff);
+    }
+    let (proportional_x_mins, monospaced_x_mins) = x_mins.split_at(num_h_metrics);
+
+    let advances = (0..num_h_metrics)
+        .map(|_| reader.u16())
+        .collect::<Result<Vec<_>>>()?;
+    let mut lsbs = |x_mins: &[i16], omitted: bool| {
+        x_mins
+            .iter()
+            .map(|&x_min| if omitted { Ok(x_min) } else { reader.i16() })
+            .collect::<Result<Vec<_>>>()
+    };
+    let proportional_lsbs = lsbs(proportional_x_mins, flags & PROPORTIONAL_LSBS_OMITTED != 0)?;
+    let monospaced_lsbs = lsbs(monospaced_x_mins, flags & MONOSPACED_LSBS_OMITTED != 0)?;
+
+    let mut hmtx = Vec::with_capacity(4 * num_h_metrics + 2 * monospaced_lsbs.len());
+    for (advance, lsb) in advances.into_iter().zip(proportional_lsbs) {
+        hmtx.extend(advance.to_be_bytes());
+        hmtx.extend(lsb.to_be_bytes());
+    }
+    hmtx.extend(monospaced_lsbs.iter().flat_map(|lsb| lsb.to_be_bytes()));
+    Ok(hmtx)
+}
+
+// The sfnt checksum of a table, which is the sum of its contents as u32s
+fn checksum(table: &[u8]) -> u32 {
+    table
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .fold(0, u32::wrapping_add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::woff::read_u32;
+
+    // A subset of Source Code Pro, see resources/fonts/README.md. The WOFF2 file keeps the
+    // transformed glyph data produced by a reference WOFF2 encoder
+    const SUBSET_TTF: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.ttf"
+    );
+    const SUBSET_WOFF2: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/fonts/code_subset.woff2"
+    );
+
+    #[derive(Debug, PartialEq)]
+    enum Glyph<'a> {
+        Empty,
+        Simple {
+            bbox: [i16; 4],
+            end_points: Vec<u16>,
+            instructions: &'a [u8],
+            points: Vec<(i16, i16, bool)>,
+        },
+        Composite {
+            bbox: [i16; 4],
+            components: &'a [u8],
+            instructions: Option<&'a [u8]>,
+        },
+    }
+
+    fn parse_glyph(data: &[u8]) -> Glyph<'_> {
+        if data.is_empty() {
+            return Glyph::Empty;
+        }
+        let mut reader = Reader::new(data);
+        let n_contours = reader.i16().unwrap();
+        let bbox = read_bbox(&mut reader).unwrap();
+
+        if n_contours < 0 {
+            let (components, have_instructions) = read_components(&mut reader).unwrap();
+            let instructions = have_instructions.then(|| {
+                let len = reader.u16().unwrap();
+                reader.bytes(usize::from(len)).unwrap()
+            });
+            return Glyph::Composite {
+                bbox,
+                components,
+                instructions,
+            };
+        }
+
+        let end_points: Vec<_> = (0..n_contours).map(|_| reader.u16().unwrap()).collect();
+        let num_points = end_points.last().map_or(0, |&p| usize::from(p) + 1);
+        let instructions_len = reader.u16().unwrap();
+        let instructions = reader.bytes(usize::from(instructions_len)).unwrap();
+
+        let mut flags = Vec::with_capacity(num_points);
+        while flags.len() < num_points {
+            let flag = reader.u8().unwrap();
+            let repeat = if flag & 0x08 == 0 {
+                0
+            } else {
+                reader.u8().unwrap()
+            };
+            flags.extend(std::iter::repeat_n(flag, usize::from(repeat) + 1));
+        }
+        let mut coordinates = |short: u8, same_or_positive: u8| -> Vec<i16> {
+            flags
+                .iter()
+                .scan(0_i16, |value, &flag| {
+                    *value += if flag & short != 0 {
+                        let delta = i16::from(reader.u8().unwrap());
+                        if flag & same_or_positive == 0 {
+                            -delta
+                        } else {
+                            delta
+                        }
+                    } else if flag & same_or_positive == 0 {
+                        reader.i16().unwrap()
+                    } else {
+                        0
+                    };
+                    Some(*value)
+                })
+                .collect()
+        };
+        let xs = coordinates(X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE);
+        let ys = coordinates(Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE);
+        let points = itertools::izip!(xs, ys, &flags)
+            .map(|(x, y, flag)| (x, y, flag & ON_CURVE_POINT != 0))
+            .collect();
+
+        Glyph::Simple {
+            bbox,
+            end_points,
+            instructions,
+            points,
+        }
+    }
+
+    fn glyph_offsets(loca: &[u8], index_format: i16) -> Vec<usize> {
+        if index_format == 0 {
+            loca.chunks(2)
+                .map(|c| usize::from(u16::from_be_bytes([c[0], c[1]])) * 2)
+                .collect()
+        } else {
+            loca.chunks(4)
+                .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]) as usize)
+                .collect()
+        }
+    }
+
+    // The sfnt tables in the font data, keyed by tag
+    fn sfnt_tables(ttf: &[u8]) -> BTreeMap<[u8; 4], &[u8]> {
+        let num_tables = read_u16(ttf, 4).unwrap();
+        (0..usize::from(num_tables))
+            .map(|i| {
+                let start = SFNT_HEADER_LEN + i * SFNT_TABLE_RECORD_LEN;
+                let offset = read_u32(ttf, start + 8).unwrap() as usize;
+                let length = read_u32(ttf, start + 12).unwrap() as usize;
+                (
+                    ttf[start..start + 4].try_into().unwrap(),
+                    &ttf[offset..offset + length],
+                )
+            })
+            .collect()
+    }
+
+    // The glyphs in the font data, parsed using loca
+    fn glyphs<'a>(tables: &BTreeMap<[u8; 4], &'a [u8]>) -> Vec<Glyph<'a>> {
+        let index_format = i16::from_be_bytes(tables[b"head"][50..52].try_into().unwrap());
+        let glyf = tables[b"glyf"];
+        glyph_offsets(tables[b"loca"], index_format)
+            .windows(2)
+            .map(|range| parse_glyph(&glyf[range[0]..range[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn woff2_decode() {
+        let ttf = std::fs::read(SUBSET_TTF).unwrap();
+        let woff2 = std::fs::read(SUBSET_WOFF2).unwrap();
+
+        let decoded = decode(&woff2).unwrap();
+        let tables = sfnt_tables(&decoded);
+        let expected = sfnt_tables(&ttf);
+        assert!(tables.keys().eq(expected.keys()));
+
+        for (tag, table) in &tables {
+            match tag {
+                // The glyphs' point data can be encoded differently, so these are compared glyph
+                // by glyph below
+                b"glyf" | b"loca" => {}
+                // The encoder sets bit 11 of the flags to mark the font as transformed, which also
+                // changes the whole-font checksum adjustment
+                b"head" => {
+                    let mask = |head: &[u8]| {
+                        let mut head = head.to_vec();
+                        head[8..12].fill(0);
+                        head[16] &= !0x08;
+                        head
+                    };
+                    assert_eq!(mask(table), mask(expected[tag]));
+                }
+                _ => assert_eq!(*table, expected[tag], "{}", String::from_utf8_lossy(tag)),
+            }
+        }
+
+        assert_eq!(glyphs(&tables), glyphs(&expected));
+    }
+
+    #[test]
+    fn woff2_decode_glyf() {
+        let simple = [
+            &1_i16.to_be_bytes()[..],      // numberOfContours
+            &[0, 0, 0, 0, 0, 100, 0, 100], // bbox
+            &[0, 2, 0, 0],                 // endPtsOfContours, instructionLength
+            &[0x01, 0x01, 0x01],           // flags
+            &[0, 0, 0, 100, 0xff, 0xce],   // x coordinates
+            &[0, 0, 0, 0, 0, 100],         // y coordinates
+        ]
+        .concat();
+        let offset_bbox = [
+            &simple[..2],
+            &[0xff, 0xf6, 0, 0, 0, 100, 0, 100], // bbox with a different xMin
+            &simple[10..],
+        ]
+        .concat();
+        let components = [
+            &(ARG_1_AND_2_ARE_WORDS | WE_HAVE_A_SCALE | MORE_COMPONENTS).to_be_bytes()[..],
+            &[0, 0, 0, 0, 0, 0, 0x40, 0], // glyph 0, offset (0, 0), scale 1.0
+            &WE_HAVE_INSTRUCTIONS.to_be_bytes(),
+            &[0, 2, 200, 0], // glyph 2, offset (200, 0)
+        ]
+        .concat();
+        let composite = [
+            &(-1_i16).to_be_bytes()[..],
+            &[0, 0, 0, 0, 0x01, 0x2c, 0, 100],
+            &components,
+            &[0, 2, 0xb0, 0x01], // instructions
+        ]
+        .concat();
+
+        // The same glyphs with the WOFF2 glyf transform applied
+        let n_contours = [0, 1, 0, 1, 0, 0, 0xff, 0xff]; // simple, simple, empty, composite
+        let n_points = [3, 3];
+        let flags = [1, 11, 86, 1, 11, 86];
+        let glyph = [0, 100, 49, 99, 0, 0, 100, 49, 99, 0, 2]; // points and instruction lengths
+        let bbox = [
+            &[0x50, 0, 0, 0][..], // bboxBitmap, glyphs 1 and 3 have an explicit bbox
+            &offset_bbox[2..10],
+            &composite[2..10],
+        ]
+        .concat();
+        let instructions = [0xb0, 0x01];
+        let streams: [&[u8]; 7] = [
+            &n_contours,
+            &n_points,
+            &flags,
+            &glyph,
+            &components,
+            &bbox,
+            &instructions,
+        ];
+        let mut transformed = [0, 0, 0, 0, 0, 4, 0, 1].to_vec(); // 4 glyphs, long loca offsets
+        for stream in streams {
+            transformed.extend(u32::try_from(stream.len()).unwrap().to_be_bytes());
+        }
+        transformed.extend(streams.concat());
+
+        let decoded = decode_glyf(&transformed).unwrap();
+        assert_eq!(decoded.x_mins, [0, -10, 0, 0]);
+        let offsets = glyph_offsets(&decoded.loca, 1);
+        let glyphs: Vec<_> = offsets
+            .windows(2)
+            .map(|range| parse_glyph(&decoded.glyf[range[0]..range[1]]))
+            .collect();
+        assert_eq!(
+            glyphs,
+            [
+                parse_glyph(&simple),
+                parse_glyph(&offset_bbox),
+                Glyph::Empty,
+                parse_glyph(&composite)
+            ]
+        );
+
+        // Short loca offsets are halved
+        transformed[6..8].copy_from_slice(&0_u16.to_be_bytes());
+        let decoded = decode_glyf(&transformed).unwrap();
+        assert_eq!(glyph_offsets(&decoded.loca, 0), offsets);
+    }
+
+    #[test]
+    fn woff2_decode_triplets() {
+        // Each delta uses the smallest encoding, from 1 to 4 bytes. Every other point is on-curve
+        let triplets: [((i16, i16), u8, &[u8]); 12] = [
+            ((0, 5), 1, &[5]),
+            ((0, -1279), 136, &[255]),
+            ((7, 0), 11, &[7]),
+            ((-1279, 0), 146, &[255]),
+            ((64, -64), 81, &[255]),
+            ((-1, 1), 150, &[0]),
+            ((768, -768), 117, &[255, 255]),
+            ((65, 700), 223, &[64, 187]),
+            ((4095, -4095), 121, &[255, 255, 255]),
+            ((-769, 3000), 250, &[48, 27, 184]),
+            ((4096, 1), 127, &[16, 0, 0, 1]),
+            ((-20000, 32767), 254, &[78, 32, 127, 255]),
+        ];
+        let flags: Vec<_> = triplets.iter().map(|t| t.1).collect();
+        let data: Vec<_> = triplets.iter().flat_map(|t| t.2).copied().collect();
+
+        let mut glyph_stream = Reader::new(&data);
+        let points = decode_points(&flags, &mut glyph_stream).unwrap();
+        assert!(glyph_stream.data.is_empty());
+
+        let (mut x, mut y) = (0_i16, 0_i16);
+        for (i, (&((dx, dy), _, _), point)) in triplets.iter().zip(points).enumerate() {
+            x = x.wrapping_add(dx);
+            y = y.wrapping_add(dy);
+            assert_eq!(point, (x, y, i % 2 == 0));
+        }
+    }
+
+    #[test]
+    fn woff2_decode_hmtx() {
+        let x_mins = [5, 6, 7];
+        let hmtx = [0x02, 0, 10, 0, 20, 0xff, 0xff, 0, 1];
+        let decoded = decode_hmtx(&hmtx, 2, &x_mins).unwrap();
+        assert_eq!(decoded, [0, 10, 0xff, 0xff, 0, 20, 0, 1, 0, 7]);
+
+        let hmtx = [0x01, 0, 10, 0, 20, 0, 3];
+        let decoded = decode_hmtx(&hmtx, 2, &x_mins).unwrap();
+        assert_eq!(decoded, [0, 10, 0, 5, 0, 20, 0, 6, 0, 3]);
+
+        assert!(decode_hmtx(&[0x00, 0, 10, 0, 20], 2, &x_mins).is_err());
+        assert!(decode_hmtx(&[0x07, 0, 10, 0, 20], 2, &x_mins).is_err());
+        assert!(decode_hmtx(&[0x03, 0, 10], 4, &x_mins).is_err());
+        assert!(decode_hmtx(&[0x03, 0], 1, &x_mins).is_err());
+    }
+
+    #[test]
+    fn woff2_reader() {
+        assert_eq!(Reader::new(&[0x3f]).base128().unwrap(), 63);
+        assert_eq!(Reader::new(&[0x81, 0x00]).base128().unwrap(), 128);
+        assert_eq!(Reader::new(&[0x81, 0x80, 0x00]).base128().unwrap(), 16384);
+        assert_eq!(
+            Reader::new(&[0x8f, 0xff, 0xff, 0xff, 0x7f])
+                .base128()
+                .unwrap(),
+            0xffff_ffff
+        );
+        assert!(Reader::new(&[0x80, 0x01]).base128().is_err());
+        assert!(Reader::new(&[0x90, 0x80, 0x80, 0x80, 0x00])
+            .base128()
+            .is_err());
+        assert!(Reader::new(&[0x81, 0x80, 0x80, 0x80, 0x80, 0x00])
+            .base128()
+            .is_err());
+        assert!(Reader::new(&[0x81]).base128().is_err());
+
+        assert_eq!(Reader::new(&[252]).uint255().unwrap(), 252);
+        assert_eq!(Reader::new(&[255, 0]).uint255().unwrap(), 253);
+        assert_eq!(Reader::new(&[254, 0]).uint255().unwrap(), 506);
+        assert_eq!(Reader::new(&[253, 1, 0]).uint255().unwrap(), 256);
+        assert!(Reader::new(&[253, 1]).uint255().is_err());
+    }
+
+    #[test]
+    fn woff2_decode_invalid() {
+        assert!(decode(b"invalid").is_err());
+        assert!(decode(b"wOF2").is_err());
+
+        let woff2 = std::fs::read(SUBSET_WOFF2).unwrap();
+        assert!(decode(&woff2[..woff2.len() / 2]).is_err());
+
+        let mut collection = woff2.clone();
+        collection[4..8].copy_from_slice(b"ttcf");
+        let error = decode(&collection).unwrap_err();
+        assert_eq!(format!("{error}"), "unsupported font format WOFF2");
+
+        // The tables decompress to more than totalSfntSize
+        let mut too_large = woff2.clone();
+        too_large[16..20].copy_from_slice(&64_u32.to_be_bytes());
+        assert!(decode(&too_large).is_err());
+
+        // A transformed table with no known transform
+        let mut unknown_transform = woff2;
+        let flags = &mut unknown_transform[WOFF2_HEADER_LEN];
+        *flags |= 0x80;
+        assert!(decode(&unknown_transform).is_err());
+    }
+
+    #[test]
+    fn woff2_decode_truncated() {
+        let woff2 = std::fs::read(SUBSET_WOFF2).unwrap();
+
+        // Any truncation cuts into the compressed data, which must be read in full
+        for len in 0..woff2.len() {
+            assert!(decode(&woff2[..len]).is_err(), "truncated to {len} bytes");
+        }
+    }
+
+    #[test]
+    fn woff2_decode_corrupted() {
+        let woff2 = std::fs::read(SUBSET_WOFF2).unwrap();
+
+        // Corrupting any byte must not panic, although the result may still decode successfully
+        // if the byte isn't used, e.g. in the reserved fields or padding
+        for i in 0..woff2.len() {
+            let mut corrupted = woff2.clone();
+            corrupted[i] ^= 0xff;
+            drop(decode(&corrupted));
+        }
+    }
+
+    #[test]
+    fn woff2_decode_glyf_malformed() {
+        // A single simple glyph with the WOFF2 glyf transform applied
+        let streams: [&[u8]; 7] = [
+            &[0, 1],              // nContours
+            &[3],                 // nPoints
+            &[1, 11, 86],         // flags
+            &[0, 100, 49, 99, 0], // points and instruction length
+            &[],                  // composite
+            &[0, 0, 0, 0],        // bboxBitmap
+            &[],                  // instructions
+        ];
+        let transform = |streams: &[&[u8]; 7]| {
+            let mut transformed = [0, 0, 0, 0, 0, 1, 0, 0].to_vec(); // 1 glyph, short loca offsets
+            for stream in streams {
+                transformed.extend(u32::try_from(stream.len()).unwrap().to_be_bytes());
+            }
+            transformed.extend(streams.concat());
+            transformed
+        };
+        let transformed = transform(&streams);
+        assert!(decode_glyf(&transformed).is_ok());
+
+        for len in 0..transformed.len() {
+            assert!(
+                decode_glyf(&transformed[..len]).is_err(),
+                "truncated to {len} bytes"
+            );
+        }
+
+        // Stream lengths longer than the table
+        let mut overlong = transformed.clone();
+        overlong[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decode_glyf(&overlong).is_err());
+
+        // Unknown loca index format
+        let mut index_format = transformed;
+        index_format[6..8].copy_from_slice(&2_u16.to_be_bytes());
+        assert!(decode_glyf(&index_format).is_err());
+
+        // More points than there are flags
+        let mut malformed = streams;
+        malformed[1] = &[4];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+
+        // A contour with no points
+        let mut malformed = streams;
+        malformed[1] = &[0];
+        malformed[2] = &[];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+
+        // An empty glyph with a bounding box
+        let mut malformed = streams;
+        malformed[0] = &[0, 0];
+        malformed[5] = &[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 100, 0, 100];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+
+        // A composite glyph without a bounding box
+        let mut malformed = streams;
+        malformed[0] = &[0xff, 0xff];
+        malformed[4] = &[0, 0, 0, 0, 0, 0];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+
+        // An unsupported number of contours
+        let mut malformed = streams;
+        malformed[0] = &[0xff, 0xfe];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+
+        // A composite glyph whose components run past the end of the stream
+        let components = MORE_COMPONENTS.to_be_bytes();
+        let mut malformed = streams;
+        malformed[0] = &[0xff, 0xff];
+        malformed[4] = &components;
+        malformed[5] = &[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 100, 0, 100];
+        assert!(decode_glyf(&transform(&malformed)).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+#![allow(
+    missing_docs,
+    unused_crate_dependencies,
+    clippy::pedantic,
+    clippy::restriction
+)] // This is just a benchmark
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use keyset_geom::{Angle, Circle, Length, Path, Point, ToPath, Transform};
+
+// Roughly the number of glyphs in a full keyboard's legends, each approximated by a few circles
+const GLYPHS: usize = 2000;
+const ITERATIONS: u32 = 100;
+
+fn glyph(i: usize) -> Path<()> {
+    let origin = Point::new((i % 100) as f32 * 20.0, (i / 100) as f32 * 20.0);
+    let outlines: Vec<_> = (0..4)
+        .map(|j| Circle::new(origin + euclid::vec2(j as f32, 0.0), Length::new(5.0)).to_path())
+        .collect();
+    Path::from_slice(&outlines)
+}
+
+fn time(name: &str, mut f: impl FnMut() -> Path<()>) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed: Duration = start.elapsed() / ITERATIONS;
+    println!("{name:<40} {elapsed:>12.2?}");
+}
+
+fn main() {
+    let glyphs: Vec<_> = (0..GLYPHS).map(glyph).collect();
+    let path = Path::from_slice(&glyphs);
+    println!("{} segments", path.len());
+
+    time("concatenate glyph paths", || {
+        Path::from_slice(black_box(&glyphs))
+    });
+
+    // Axis-aligned transforms map the cached bounds directly
+    let translate = Transform::translation(10.0, 20.0).then_scale(2.0, 2.0);
+    time("axis-aligned transform (cached bounds)", || {
+        black_box(path.clone()) * translate
+    });
+
+    // Rotations need to recalculate the bounds from every segment
+    let rotate = Transform::rotation(Angle::degrees(15.0));
+    time("rotation (recalculated bounds)", || {
+        black_box(path.clone()) * rotate
+    });
+
+    time("clone only (baseline)", || black_box(path.clone()));
+}
@@ -0,0 +1,264 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use isclose::IsClose;
+
+use crate::{ExtRect, Point, Rect, RoundRect, Size, Vector};
+
+/// A rectangle with elliptical rounded corners
+pub struct EllipticalRoundRect<U> {
+    /// Minimum point
+    pub min: Point<U>,
+    /// Maximum point
+    pub max: Point<U>,
+    /// Horizontal and vertical corner radii
+    pub radii: Vector<U>,
+}
+
+// Impl here rather than derive so we don't require U: Clone
+impl<U> Clone for EllipticalRoundRect<U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+// Impl here rather than derive so we don't require U: Copy
+impl<U> Copy for EllipticalRoundRect<U> {}
+
+// Impl here rather than derive so we don't require U: PartialEq
+impl<U> PartialEq for EllipticalRoundRect<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.min.eq(&other.min) && self.max.eq(&other.max) && self.radii.eq(&other.radii)
+    }
+}
+
+// Impl here rather than derive so we don't require U: Debug
+impl<U> fmt::Debug for EllipticalRoundRect<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EllipticalRoundRect")
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("radii", &self.radii)
+            .finish()
+    }
+}
+
+impl<U> From<RoundRect<U>> for EllipticalRoundRect<U> {
+    #[inline]
+    fn from(value: RoundRect<U>) -> Self {
+        let RoundRect { min, max, radius } = value;
+        Self::new(min, max, Vector::splat(radius.get()))
+    }
+}
+
+impl<U> EllipticalRoundRect<U> {
+    /// Create a new rounded rectangle from minimum and maximum coordinates.
+    #[inline]
+    #[must_use]
+    pub const fn new(min: Point<U>, max: Point<U>, radii: Vector<U>) -> Self {
+        Self { min, max, radii }
+    }
+
+    /// Create a new rounded rectangle from a [`crate::Rect`] and its horizontal and vertical
+    /// radii.
+    #[inline]
+    #[must_use]
+    pub const fn from_rect_and_radii(rect: Rect<U>, radii: Vector<U>) -> Self {
+        let Rect { min, max } = rect;
+        Self { min, max, radii }
+    }
+
+    /// Create a new rounded rectangle from its center point, size, and radii.
+    #[inline]
+    #[must_use]
+    pub fn from_center_and_size(origin: Point<U>, size: Size<U>, radii: Vector<U>) -> Self {
+        Self::from_rect_and_radii(Rect::from_center_and_size(origin, size), radii)
+    }
+
+    /// Returns the width of the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    /// Returns the height of the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    /// Returns the horizontal and vertical radii of the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub const fn radii(&self) -> Vector<U> {
+        self.radii
+    }
+
+    /// Returns a rectangle with the same position and size as the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub const fn rect(&self) -> Rect<U> {
+        let Self { min, max, .. } = *self;
+
+        Rect { min, max }
+    }
+
+    /// Returns the center point of the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub fn center(&self) -> Point<U> {
+        (self.min + self.max.to_vector()) / 2.0
+    }
+
+    /// Returns the size of the rounded rectangle
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> Size<U> {
+        self.rect().size()
+    }
+}
+
+impl<U> IsClose<f32> for EllipticalRoundRect<U> {
+    const ABS_TOL: f32 = f32::ABS_TOL;
+    const REL_TOL: f32 = f32::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.min.is_close_tol(other.min, rel_tol, abs_tol)
+            && self.max.is_close_tol(other.max, rel_tol, abs_tol)
+            && self.radii.is_close_tol(other.radii, rel_tol, abs_tol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use crate::Length;
+
+    use super::*;
+
+    #[test]
+    fn elliptical_round_rect_clone() {
+        struct NonCloneable;
+        let rect = EllipticalRoundRect::<NonCloneable> {
+            min: Point::origin(),
+            max: Point::new(1.0, 2.0),
+            radii: Vector::new(0.5, 0.25),
+        };
+
+        #[allow(clippy::clone_on_copy)] // We want to test clone, not copy
+        let rect2 = rect.clone();
+
+        assert_is_close!(rect, rect2);
+    }
+
+    #[test]
+    fn elliptical_round_rect_partial_eq() {
+        struct NonPartialEq;
+        let rect = EllipticalRoundRect::<NonPartialEq> {
+            min: Point::origin(),
+            max: Point::new(1.0, 2.0),
+            radii: Vector::new(0.5, 0.25),
+        };
+        let rect2 = rect;
+
+        assert_eq!(rect, rect2);
+    }
+
+    #[test]
+    fn elliptical_round_rect_debug() {
+        struct NonDebug;
+        let rect = EllipticalRoundRect::<NonDebug>::new(
+            Point::new(1.0, 2.0),
+            Point::new(3.0, 5.0),
+            Vector::new(0.5, 0.25),
+        );
+        let dbg = format!("{rect:?}");
+
+        assert_eq!(
+            dbg,
+            "EllipticalRoundRect { min: (1.0, 2.0), max: (3.0, 5.0), radii: (0.5, 0.25) }"
+        );
+    }
+
+    #[test]
+    fn elliptical_round_rect_from_round_rect() {
+        let rect = EllipticalRoundRect::<()>::from(RoundRect::new(
+            Point::new(1.0, 2.0),
+            Point::new(3.0, 5.0),
+            Length::new(0.5),
+        ));
+
+        assert_is_close!(rect.min, Point::new(1.0, 2.0));
+        assert_is_close!(rect.max, Point::new(3.0, 5.0));
+        assert_is_close!(rect.radii, Vector::splat(0.5));
+    }
+
+    #[test]
+    fn elliptical_round_rect_new() {
+        let rect = EllipticalRoundRect::<()>::new(
+            Point::new(1.0, 2.0),
+            Point::new(3.0, 5.0),
+            Vector::new(0.5, 0.25),
+        );
+
+        assert_is_close!(rect.min, Point::new(1.0, 2.0));
+        assert_is_close!(rect.max, Point::new(3.0, 5.0));
+        assert_is_close!(rect.radii, Vector::new(0.5, 0.25));
+    }
+
+    #[test]
+    fn elliptical_round_rect_from_rect_and_radii() {
+        let rect = EllipticalRoundRect::<()>::from_rect_and_radii(
+            Rect::new(Point::new(1.0, 2.0), Point::new(3.0, 5.0)),
+            Vector::new(0.5, 0.25),
+        );
+
+        assert_is_close!(rect.min, Point::new(1.0, 2.0));
+        assert_is_close!(rect.max, Point::new(3.0, 5.0));
+        assert_is_close!(rect.radii, Vector::new(0.5, 0.25));
+    }
+
+    #[test]
+    fn elliptical_round_rect_from_center_and_size() {
+        let rect = EllipticalRoundRect::<()>::from_center_and_size(
+            Point::new(2.0, 3.5),
+            Size::new(2.0, 3.0),
+            Vector::new(0.5, 0.25),
+        );
+
+        assert_is_close!(rect.min, Point::new(1.0, 2.0));
+        assert_is_close!(rect.max, Point::new(3.0, 5.0));
+        assert_is_close!(rect.radii, Vector::new(0.5, 0.25));
+    }
+
+    #[test]
+    fn elliptical_round_rect_dimensions() {
+        let rect = EllipticalRoundRect::<()>::new(
+            Point::new(1.0, 2.0),
+            Point::new(3.0, 5.0),
+            Vector::new(0.5, 0.25),
+        );
+
+        assert_is_close!(rect.width(), 2.0);
+        assert_is_close!(rect.height(), 3.0);
+        assert_eq!(rect.radii(), Vector::new(0.5, 0.25));
+        assert_eq!(
+            rect.rect(),
+            Rect::new(Point::new(1.0, 2.0), Point::new(3.0, 5.0))
+        );
+        assert_eq!(rect.center(), Point::new(2.0, 3.5));
+        assert_eq!(rect.size(), Size::new(2.0, 3.0));
+    }
+}
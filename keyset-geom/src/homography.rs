@@ -0,0 +1,175 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::{Point, Rect};
+
+/// A 2-dimensional projective transformation (homography) with unit `U`
+///
+/// Unlike a [`Transform`](crate::Transform), a homography does not preserve parallel lines, so it
+/// can be used to map a rectangle onto an arbitrary quadrilateral such as a trapezoid
+pub struct Homography<U> {
+    // Row-major 3x3 matrix, with m[2][2] normalised to 1
+    m: [[f32; 3]; 3],
+    _unit: PhantomData<U>,
+}
+
+// Impl here rather than derive so we don't require U: Clone
+impl<U> Clone for Homography<U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+// Impl here rather than derive so we don't require U: Copy
+impl<U> Copy for Homography<U> {}
+
+// Impl here rather than derive so we don't require U: PartialEq
+impl<U> PartialEq for Homography<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.m.eq(&other.m)
+    }
+}
+
+// Impl here rather than derive so we don't require U: Debug
+impl<U> fmt::Debug for Homography<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Homography").field("m", &self.m).finish()
+    }
+}
+
+impl<U> Default for Homography<U> {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<U> Homography<U> {
+    /// Create an identity homography
+    #[inline]
+    #[must_use]
+    pub const fn identity() -> Self {
+        Self {
+            m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            _unit: PhantomData,
+        }
+    }
+
+    /// Create a homography mapping the corners of `rect` onto the corners of `quad`. The corners
+    /// of `quad` are given in the order `[min.x, min.y]`, `[max.x, min.y]`, `[max.x, max.y]`,
+    /// `[min.x, max.y]` of the rectangle they correspond to
+    ///
+    /// Returns [`None`] if either `rect` or `quad` is degenerate
+    #[inline]
+    #[must_use]
+    pub fn from_rect_to_quad(rect: Rect<U>, quad: [Point<U>; 4]) -> Option<Self> {
+        if rect.is_empty() {
+            return None;
+        }
+
+        // Map the unit square onto the quad as described by Heckbert, "Fundamentals of Texture
+        // Mapping and Image Warping", 1989
+        let [p0, p1, p2, p3] = quad;
+        let d1 = p1 - p2;
+        let d2 = p3 - p2;
+        let d3 = p0 - p1 + (p2 - p3);
+
+        let det = d1.cross(d2);
+        if det == 0.0 {
+            return None;
+        }
+        let g = d3.cross(d2) / det;
+        let h = d1.cross(d3) / det;
+
+        let square = [
+            [p1.x - p0.x + g * p1.x, p3.x - p0.x + h * p3.x, p0.x],
+            [p1.y - p0.y + g * p1.y, p3.y - p0.y + h * p3.y, p0.y],
+            [g, h, 1.0],
+        ];
+
+        // Then premultiply by the mapping from the rect onto the unit square
+        let (sx, sy) = (1.0 / rect.width(), 1.0 / rect.height());
+        let (tx, ty) = (-rect.min.x * sx, -rect.min.y * sy);
+        let m = square.map(|[a, b, c]| [a * sx, b * sy, a * tx + b * ty + c]);
+
+        Some(Self {
+            m,
+            _unit: PhantomData,
+        })
+    }
+
+    /// Transform a point. Points which are mapped to infinity will have non-finite coordinates
+    #[inline]
+    #[must_use]
+    pub fn transform_point(&self, point: Point<U>) -> Point<U> {
+        let [x, y, w] = self.m.map(|[a, b, c]| a * point.x + b * point.y + c);
+        Point::new(x / w, y / w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn homography_identity() {
+        let homography = Homography::<()>::identity();
+        assert_is_close!(
+            homography.transform_point(Point::new(1.0, 2.0)),
+            Point::new(1.0, 2.0)
+        );
+        assert_eq!(homography, Homography::default());
+    }
+
+    #[test]
+    fn homography_from_rect_to_quad() {
+        let rect = Rect::<()>::new(Point::new(1.0, 1.0), Point::new(3.0, 2.0));
+
+        // Affine
+        let quad = [
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(4.0, 2.0),
+            Point::new(0.0, 2.0),
+        ];
+        let homography = Homography::from_rect_to_quad(rect, quad).unwrap();
+        assert_is_close!(
+            homography.transform_point(Point::new(2.0, 1.5)),
+            Point::new(2.0, 1.0)
+        );
+
+        // Trapezoid
+        let quad = [
+            Point::new(1.0, 0.0),
+            Point::new(3.0, 0.0),
+            Point::new(4.0, 2.0),
+            Point::new(0.0, 2.0),
+        ];
+        let homography = Homography::from_rect_to_quad(rect, quad).unwrap();
+        for (&corner, &expected) in [
+            Point::new(1.0, 1.0),
+            Point::new(3.0, 1.0),
+            Point::new(3.0, 2.0),
+            Point::new(1.0, 2.0),
+        ]
+        .iter()
+        .zip(quad.iter())
+        {
+            assert_is_close!(homography.transform_point(corner), expected);
+        }
+        // The centre of the rect maps to the intersection of the quad's diagonals, which is
+        // closer to the narrower top edge
+        let center = homography.transform_point(Point::new(2.0, 1.5));
+        assert_is_close!(center.x, 2.0);
+        assert_is_close!(center.y, 2.0 / 3.0);
+
+        // Degenerate
+        let quad = [Point::new(0.0, 0.0); 4];
+        assert!(Homography::from_rect_to_quad(rect, quad).is_none());
+        let rect = Rect::<()>::new(Point::new(1.0, 1.0), Point::new(1.0, 2.0));
+        assert!(Homography::from_rect_to_quad(rect, quad).is_none());
+    }
+}
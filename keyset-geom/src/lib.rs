@@ -4,13 +4,17 @@
 //! [keyset]: https://crates.io/crates/keyset
 
 mod circle;
+mod elliptical_round_rect;
+mod homography;
 mod path;
 mod round_rect;
 mod traits;
 mod unit;
 
 pub use circle::Circle;
-pub use path::{Path, PathBuilder, PathSegment, ToPath};
+pub use elliptical_round_rect::EllipticalRoundRect;
+pub use homography::Homography;
+pub use path::{Path, PathBuilder, PathSegment, SvgCommand, ToPath, MAX_PATH_SAMPLES};
 pub use round_rect::RoundRect;
 pub use traits::*;
 pub use unit::{
@@ -18,6 +22,10 @@ pub use unit::{
 };
 
 /// An angle in radians
+///
+/// Angles support addition, subtraction, and negation, and can be multiplied or divided by an
+/// [`f32`]. Arithmetic does not wrap angles around, use [`euclid::Angle::signed`] or
+/// [`euclid::Angle::positive`] to normalise an angle
 pub type Angle = euclid::Angle<f32>;
 
 /// A one-dimensional distance with unit `U`
@@ -33,6 +41,10 @@ pub type Rect<U> = euclid::Box2D<f32, U>;
 pub type Scale<U, V> = euclid::Scale<f32, U, V>;
 
 /// A set of 2-dimensional side offsets for top/right/bottom/left borders, padding, and margins
+///
+/// Use [`Rect::inner_box`] or [`Rect::outer_box`] to apply the offsets to a rectangle, and
+/// [`SideOffsets::horizontal`] and [`SideOffsets::vertical`] to get the total offset along each
+/// axis. Offsets can also be added together and scaled
 pub type SideOffsets<U> = euclid::SideOffsets2D<f32, U>;
 
 /// A 2-dimensional size with unit `U`
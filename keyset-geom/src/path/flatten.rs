@@ -0,0 +1,95 @@
+use crate::{Point, Vector};
+
+use super::PathSegment;
+
+// Number of straight lines used to approximate each curve
+const CURVE_STEPS: u8 = 16;
+
+/// Approximate a path's segments as a sequence of straight lines, returned as pairs of start and
+/// end points. Moves do not produce a line, so the result may not be continuous
+pub fn flatten<U>(segments: &[PathSegment<U>]) -> Vec<(Point<U>, Point<U>)> {
+    let mut lines = Vec::with_capacity(segments.len());
+    let mut start = Point::origin();
+    let mut point = Point::origin();
+
+    for seg in segments {
+        match *seg {
+            PathSegment::Move(p) => {
+                start = p;
+                point = p;
+            }
+            PathSegment::Line(d) => {
+                lines.push((point, point + d));
+                point += d;
+            }
+            PathSegment::CubicBezier(c1, c2, d) => {
+                flatten_curve(&mut lines, point, |t| {
+                    let mt = 1.0 - t;
+                    c1 * (3.0 * mt * mt * t) + c2 * (3.0 * mt * t * t) + d * (t * t * t)
+                });
+                point += d;
+            }
+            PathSegment::QuadraticBezier(c1, d) => {
+                flatten_curve(&mut lines, point, |t| {
+                    let mt = 1.0 - t;
+                    c1 * (2.0 * mt * t) + d * (t * t)
+                });
+                point += d;
+            }
+            PathSegment::Close => {
+                lines.push((point, start));
+                point = start;
+            }
+        }
+    }
+
+    lines
+}
+
+fn flatten_curve<U>(
+    lines: &mut Vec<(Point<U>, Point<U>)>,
+    origin: Point<U>,
+    curve: impl Fn(f32) -> Vector<U>,
+) {
+    let mut prev = origin;
+    for i in 1..=CURVE_STEPS {
+        let next = origin + curve(f32::from(i) / f32::from(CURVE_STEPS));
+        lines.push((prev, next));
+        prev = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let segments = [
+            PathSegment::<()>::Move(Point::new(1.0, 1.0)),
+            PathSegment::Line(Vector::new(1.0, 0.0)),
+            PathSegment::CubicBezier(
+                Vector::new(0.5, 0.0),
+                Vector::new(1.0, 0.5),
+                Vector::new(1.0, 1.0),
+            ),
+            PathSegment::QuadraticBezier(Vector::new(0.0, 1.0), Vector::new(-1.0, 1.0)),
+            PathSegment::Close,
+        ];
+        let lines = flatten(&segments);
+
+        assert_eq!(lines.len(), 2 + 2 * usize::from(CURVE_STEPS));
+        assert_is_close!(lines[0].0, Point::new(1.0, 1.0));
+        assert_is_close!(lines[0].1, Point::new(2.0, 1.0));
+        assert_is_close!(lines[16].1, Point::new(3.0, 2.0));
+        assert_is_close!(lines[32].1, Point::new(2.0, 3.0));
+        assert_is_close!(lines[33].1, Point::new(1.0, 1.0));
+
+        // Each line starts where the previous one ends
+        for pair in lines.windows(2) {
+            assert_is_close!(pair[0].1, pair[1].0);
+        }
+    }
+}
@@ -1,16 +1,55 @@
 mod arc_to_bezier;
+mod flatten;
 mod segment;
+mod svg_command;
 mod to_path;
 
 use std::borrow::Borrow;
 use std::ops::{Add, Div, DivAssign, Mul, MulAssign};
 
 use arc_to_bezier::arc_to_bezier;
+use flatten::flatten;
+use saturate::SaturatingFrom;
 
 pub use segment::PathSegment;
+pub use svg_command::SvgCommand;
 pub use to_path::ToPath;
 
-use crate::{Angle, Length, Point, Rect, Scale, Transform, Vector};
+use crate::{Angle, ExtVec, Homography, Length, Point, Rect, Scale, Transform, Vector};
+
+/// The maximum number of points returned by [`Path::sample`]
+pub const MAX_PATH_SAMPLES: usize = 1 << 16;
+
+// Find the points at the given distances along a sequence of lines. The distances must be in
+// ascending order. Distances beyond the end of the lines are ignored
+fn points_at_distances<U>(
+    lines: &[(Point<U>, Point<U>)],
+    distances: impl IntoIterator<Item = f32>,
+) -> Vec<Point<U>> {
+    let mut points = Vec::new();
+    let mut lines = lines.iter();
+    let mut line = lines.next();
+    let mut distance = 0.0;
+
+    for target in distances {
+        while let Some(&(start, end)) = line {
+            let length = (end - start).length();
+            if target <= distance + length {
+                let t = if length > 0.0 {
+                    (target - distance) / length
+                } else {
+                    0.0
+                };
+                points.push(start.lerp(end, t));
+                break;
+            }
+            distance += length;
+            line = lines.next();
+        }
+    }
+
+    points
+}
 
 /// A 2-dimensional path represented by a number of path segments
 #[derive(Debug)]
@@ -32,6 +71,13 @@ impl<U> Clone for Path<U> {
     }
 }
 
+impl<U> PartialEq for Path<U> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.bounds == other.bounds
+    }
+}
+
 impl<U> Default for Path<U> {
     #[inline]
     fn default() -> Self {
@@ -66,12 +112,31 @@ impl<U> Path<U> {
     }
 
     /// Create a path by joining a slice of paths
+    ///
+    /// A move to the origin is inserted before any path after the first which doesn't start with
+    /// a move, so each path is drawn as it would be on its own. Use [`Path::concat_raw`] to join
+    /// paths without inserting moves
     #[inline]
     #[must_use]
     pub fn from_slice(slice: &[Self]) -> Self {
         slice.iter().collect()
     }
 
+    /// Create a path by concatenating the segments of a slice of paths verbatim
+    ///
+    /// Unlike [`Path::from_slice`] no moves are inserted, so a path which doesn't start with a
+    /// move continues from the end of the previous path rather than from the origin. This is
+    /// useful for joining pieces of a path such as a glyph outline which rely on implicitly
+    /// continuing from the previous segment. The bounds are recalculated from the joined segments
+    #[inline]
+    #[must_use]
+    pub fn concat_raw(paths: &[Self]) -> Self {
+        let data: Box<_> = paths.iter().flat_map(|path| path.iter().copied()).collect();
+        let bounds = calculate_bounds(&data);
+
+        Self { data, bounds }
+    }
+
     /// The number of segments in the path
     #[inline]
     #[must_use]
@@ -106,7 +171,206 @@ impl<U> Path<U> {
         }
     }
 
+    /// Skew the path along the x axis. Positive angles shift points in the positive x direction
+    /// as their y coordinate increases
+    #[inline]
+    #[must_use]
+    pub fn skew_x(self, angle: Angle) -> Self {
+        self * Transform::new(1.0, 0.0, angle.radians.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Skew the path along the y axis. Positive angles shift points in the positive y direction
+    /// as their x coordinate increases
+    #[inline]
+    #[must_use]
+    pub fn skew_y(self, angle: Angle) -> Self {
+        self * Transform::new(1.0, angle.radians.tan(), 0.0, 1.0, 0.0, 0.0)
+    }
+
+    /// Apply a projective transformation to the path
+    ///
+    /// Curves are projected by transforming their control points, which is an approximation since
+    /// the projection of a Bézier curve is not in general a Bézier curve. This is accurate enough
+    /// for mild perspective effects
+    #[inline]
+    #[must_use]
+    pub fn project(self, homography: &Homography<U>) -> Self {
+        let mut start = Point::origin();
+        let mut point = Point::origin();
+
+        let data: Box<_> = self
+            .iter()
+            .map(|&seg| {
+                let from = homography.transform_point(point);
+                let project = move |d: Vector<U>| homography.transform_point(point + d) - from;
+                match seg {
+                    PathSegment::Move(p) => {
+                        start = p;
+                        point = p;
+                        PathSegment::Move(homography.transform_point(p))
+                    }
+                    PathSegment::Line(d) => {
+                        let seg = PathSegment::Line(project(d));
+                        point += d;
+                        seg
+                    }
+                    PathSegment::CubicBezier(c1, c2, d) => {
+                        let seg = PathSegment::CubicBezier(project(c1), project(c2), project(d));
+                        point += d;
+                        seg
+                    }
+                    PathSegment::QuadraticBezier(c1, d) => {
+                        let seg = PathSegment::QuadraticBezier(project(c1), project(d));
+                        point += d;
+                        seg
+                    }
+                    PathSegment::Close => {
+                        point = start;
+                        PathSegment::Close
+                    }
+                }
+            })
+            .collect();
+        let bounds = calculate_bounds(&data);
+
+        Self { data, bounds }
+    }
+
+    /// Create a new path by mapping each segment of this path using `f`
+    ///
+    /// `f` is called with the current point, i.e. the point in the original path where the
+    /// segment starts, and the segment itself. This allows arbitrary distortions which can't be
+    /// expressed as a transformation. The bounds of the new path are recalculated from its
+    /// segments
+    #[inline]
+    #[must_use]
+    pub fn map_segments(
+        &self,
+        mut f: impl FnMut(Point<U>, &PathSegment<U>) -> PathSegment<U>,
+    ) -> Self {
+        let mut start = Point::origin();
+        let mut point = Point::origin();
+
+        let data: Box<_> = self
+            .iter()
+            .map(|seg| {
+                let mapped = f(point, seg);
+                match *seg {
+                    PathSegment::Move(p) => {
+                        start = p;
+                        point = p;
+                    }
+                    PathSegment::Line(d)
+                    | PathSegment::CubicBezier(_, _, d)
+                    | PathSegment::QuadraticBezier(_, d) => point += d,
+                    PathSegment::Close => point = start,
+                }
+                mapped
+            })
+            .collect();
+        let bounds = calculate_bounds(&data);
+
+        Self { data, bounds }
+    }
+
+    /// Sample evenly-spaced points along the path, starting at the beginning of the path. Returns
+    /// an empty [`Vec`] if `spacing` is not positive
+    ///
+    /// At most [`MAX_PATH_SAMPLES`] points are returned, so a very small `spacing` only samples
+    /// the start of the path
+    ///
+    /// Distances are measured along a flattened approximation of the path with each curve split
+    /// into 16 straight lines, so distances along tight curves are slightly underestimated. Moves
+    /// within the path are not counted towards the distance
+    #[inline]
+    #[must_use]
+    pub fn sample(&self, spacing: Length<U>) -> Vec<Point<U>> {
+        let spacing = spacing.get();
+        if spacing.is_nan() || spacing <= 0.0 {
+            return Vec::new();
+        }
+
+        let lines = flatten(&self.data);
+        let total: f32 = lines
+            .iter()
+            .map(|&(start, end)| (end - start).length())
+            .sum();
+        let count = if lines.is_empty() {
+            0
+        } else {
+            usize::saturating_from(total / spacing)
+                .saturating_add(1)
+                .min(MAX_PATH_SAMPLES)
+        };
+        let distances = (0..count).map(|i| f32::saturating_from(i) * spacing);
+
+        points_at_distances(&lines, distances)
+    }
+
+    /// Get the point a given fraction `t` of the way along the path, with `t` in the range
+    /// `0.0..=1.0`. Returns the origin for a path without any lines or curves
+    ///
+    /// Like [`Path::sample`], this uses a flattened approximation of the path so the same
+    /// precision limits for curves apply
+    #[inline]
+    #[must_use]
+    pub fn point_at(&self, t: f32) -> Point<U> {
+        let lines = flatten(&self.data);
+        let total: f32 = lines
+            .iter()
+            .map(|&(start, end)| (end - start).length())
+            .sum();
+        let distance = t.clamp(0.0, 1.0) * total;
+
+        points_at_distances(&lines, [distance])
+            .first()
+            .copied()
+            .or_else(|| lines.last().map(|&(_, end)| end))
+            .unwrap_or_else(Point::origin)
+    }
+
+    /// Create an iterator over the path's segments as SVG path commands
+    ///
+    /// This can be used to write the path into an SVG document without building the whole path
+    /// data string
+    #[inline]
+    #[must_use]
+    pub fn svg_commands(
+        &self,
+    ) -> impl ExactSizeIterator<Item = SvgCommand> + DoubleEndedIterator + '_ {
+        self.iter().map(|&seg| SvgCommand::from(seg))
+    }
+
+    /// Get the current point at the end of the path, i.e. where the next segment would start.
+    /// This is the start of the last sub-path if the path ends with a close. Returns [`None`] for
+    /// an empty path
+    #[inline]
+    #[must_use]
+    pub fn last_point(&self) -> Option<Point<U>> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let (_, point) =
+            self.data
+                .iter()
+                .fold(
+                    (Point::origin(), Point::origin()),
+                    |(start, point), seg| match *seg {
+                        PathSegment::Move(p) => (p, p),
+                        PathSegment::Line(d)
+                        | PathSegment::CubicBezier(_, _, d)
+                        | PathSegment::QuadraticBezier(_, d) => (start, point + d),
+                        PathSegment::Close => (start, start),
+                    },
+                );
+        Some(point)
+    }
+
     /// Create an iterator over the path's segments
+    ///
+    /// This and the other iterators over a path's segments are all [`ExactSizeIterator`]s and
+    /// [`DoubleEndedIterator`]s, so can be efficiently reversed
     #[inline]
     pub fn iter(&self) -> std::slice::Iter<'_, PathSegment<U>> {
         self.data.iter()
@@ -200,8 +464,8 @@ impl<U, V> Mul<Transform<U, V>> for Path<U> {
 
     #[inline]
     fn mul(self, transform: Transform<U, V>) -> Self::Output {
-        let data: Box<_> = self.into_iter().map(|seg| seg * transform).collect();
-        let bounds = calculate_bounds(&data);
+        let data: Box<_> = self.iter().map(|&seg| seg * transform).collect();
+        let bounds = transform_bounds(self.bounds, &data, transform);
         Self::Output { data, bounds }
     }
 }
@@ -218,7 +482,7 @@ impl<U> MulAssign<Transform<U, U>> for Path<U> {
     #[inline]
     fn mul_assign(&mut self, transform: Transform<U, U>) {
         self.data.iter_mut().for_each(|seg| *seg *= transform);
-        self.bounds = calculate_bounds(&self.data);
+        self.bounds = transform_bounds(self.bounds, &self.data, transform);
     }
 }
 
@@ -326,6 +590,24 @@ impl<U> PathBuilder<U> {
         }
     }
 
+    /// Append another [`PathBuilder`]'s segments to the builder verbatim
+    ///
+    /// Unlike [`PathBuilder::extend`] no leading move is inserted, so if `other` doesn't start
+    /// with a move its segments continue from this builder's current point rather than from the
+    /// origin
+    #[inline]
+    pub fn extend_raw(&mut self, other: Self) {
+        for seg in other.data {
+            match seg {
+                PathSegment::Move(p) => self.abs_move(p),
+                PathSegment::Line(d) => self.rel_line(d),
+                PathSegment::CubicBezier(d1, d2, d) => self.rel_cubic_bezier(d1, d2, d),
+                PathSegment::QuadraticBezier(d1, d) => self.rel_quadratic_bezier(d1, d),
+                PathSegment::Close => self.close(),
+            }
+        }
+    }
+
     /// Add a move segment with relative distance
     #[inline]
     pub fn rel_move(&mut self, d: Vector<U>) {
@@ -465,6 +747,45 @@ impl<U> PathBuilder<U> {
     pub fn abs_arc(&mut self, r: Vector<U>, xar: Angle, laf: bool, sf: bool, p: Point<U>) {
         self.rel_arc(r, xar, laf, sf, p - self.point);
     }
+
+    /// Add a closed circle as a new subpath. The circle starts and ends at its leftmost point and
+    /// is drawn clockwise
+    #[inline]
+    pub fn circle(&mut self, center: Point<U>, radius: Length<U>) {
+        let radius = radius.get();
+
+        self.abs_move(center - Vector::new(radius, 0.0));
+        self.rel_arc(
+            Vector::splat(radius),
+            Angle::zero(),
+            false,
+            true,
+            Vector::new(2.0 * radius, 0.0),
+        );
+        self.rel_arc(
+            Vector::splat(radius),
+            Angle::zero(),
+            false,
+            true,
+            Vector::new(-2.0 * radius, 0.0),
+        );
+        self.close();
+    }
+
+    /// Add a closed rectangle with rounded corners as a new subpath. The rectangle is drawn
+    /// clockwise starting from the bottom of the top left corner
+    #[inline]
+    pub fn rounded_rect(&mut self, rect: Rect<U>, radii: Vector<U>) {
+        self.abs_move(rect.min + Vector::new(0.0, radii.y));
+        self.rel_arc(radii, Angle::zero(), false, true, radii.neg_y());
+        self.abs_horiz_line(Length::new(rect.max.x - radii.x));
+        self.rel_arc(radii, Angle::zero(), false, true, radii);
+        self.abs_vert_line(Length::new(rect.max.y - radii.y));
+        self.rel_arc(radii, Angle::zero(), false, true, radii.neg_x());
+        self.abs_horiz_line(Length::new(rect.min.x + radii.x));
+        self.rel_arc(radii, Angle::zero(), false, true, -radii);
+        self.close();
+    }
 }
 
 impl<U> Add for PathBuilder<U> {
@@ -483,6 +804,24 @@ fn update_bounds<U>(bounds: Rect<U>, p: Point<U>) -> Rect<U> {
     Rect::new(Point::min(bounds.min, p), Point::max(bounds.max, p))
 }
 
+// Bounds of a path after it has been transformed, where data is the already-transformed segments.
+// Axis-aligned transforms (i.e. only scaling & translation) map the original bounds exactly, so we
+// avoid rescanning every segment. This is common when transforming large paths such as glyphs.
+// Paths which don't start with a move have an implicit move to the origin included in their
+// bounds, which doesn't get transformed, so we need to rescan those
+fn transform_bounds<U, V>(
+    bounds: Rect<U>,
+    data: &[PathSegment<V>],
+    transform: Transform<U, V>,
+) -> Rect<V> {
+    let axis_aligned = transform.m12 == 0.0 && transform.m21 == 0.0;
+    if axis_aligned && matches!(data.first(), Some(&PathSegment::Move(..))) {
+        transform.outer_transformed_box(&bounds)
+    } else {
+        calculate_bounds(data)
+    }
+}
+
 fn calculate_bounds<U>(data: &[PathSegment<U>]) -> Rect<U> {
     // Add leading move to (0, 0) if we don't already start with a move
     let mov = (!matches!(data.first(), Some(&PathSegment::Move(..))))
@@ -509,11 +848,11 @@ fn calculate_bounds<U>(data: &[PathSegment<U>]) -> Rect<U> {
 #[cfg(test)]
 mod tests {
     use euclid::Scale;
-    use isclose::assert_is_close;
+    use isclose::{assert_is_close, assert_is_close_abs_tol};
 
     use super::*;
 
-    use crate::{Angle, Size};
+    use crate::{Angle, Circle, Size, ToPath};
 
     #[test]
     fn test_path_clone() {
@@ -538,6 +877,15 @@ mod tests {
         assert_is_close!(path.bounds, path2.bounds);
     }
 
+    #[test]
+    fn test_path_partial_eq() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(3.0)).to_path();
+
+        assert_eq!(path, path.clone());
+        assert_ne!(path, Path::empty());
+        assert_ne!(path, path.clone().translate(Vector::one()));
+    }
+
     #[test]
     fn test_path_empty() {
         let paths = [Path::<()>::default(), Path::empty()];
@@ -591,6 +939,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_path_concat_raw() {
+        let paths = [
+            Path::<()>::empty(),
+            Path {
+                data: Box::new([
+                    PathSegment::Move(Point::zero()),
+                    PathSegment::Line(Vector::one()),
+                ]),
+                bounds: Rect::from_size(Size::splat(1.0)),
+            },
+            Path {
+                data: Box::new([PathSegment::Line(Vector::new(1.0, 0.0))]),
+                bounds: Rect::from_size(Size::new(1.0, 0.0)),
+            },
+        ];
+
+        let expected = Path {
+            data: Box::new([
+                PathSegment::Move(Point::zero()),
+                PathSegment::Line(Vector::one()),
+                PathSegment::Line(Vector::new(1.0, 0.0)),
+            ]),
+            bounds: Rect::from_size(Size::new(2.0, 1.0)),
+        };
+
+        let path = Path::concat_raw(&paths);
+
+        assert_eq!(path.len(), expected.len());
+        assert_is_close!(path.bounds, expected.bounds);
+        for (p, e) in path.data.iter().zip(expected.data.iter()) {
+            assert_is_close!(p, e);
+        }
+
+        // Unlike from_slice, which inserts a move before the last path
+        assert_eq!(Path::from_slice(&paths).len(), expected.len() + 1);
+    }
+
     #[test]
     fn test_path_len() {
         let path = Path::<()> {
@@ -747,6 +1133,168 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_path_skew() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(1.0)).to_path();
+
+        let skewed = path.clone().skew_x(Angle::degrees(45.0));
+        assert_is_close!(
+            skewed.bounds,
+            Rect::new(Point::zero(), Point::new(2.0, 1.0))
+        );
+
+        let skewed = path.skew_y(Angle::degrees(-45.0));
+        assert_is_close!(
+            skewed.bounds,
+            Rect::new(Point::new(0.0, -1.0), Point::new(1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_path_project() {
+        let rect = Rect::<()>::new(Point::zero(), Point::splat(2.0));
+        let path = rect.to_path();
+
+        let homography = Homography::from_rect_to_quad(
+            rect,
+            [
+                Point::new(0.5, 1.0),
+                Point::new(1.5, 1.0),
+                Point::new(2.0, 2.0),
+                Point::new(0.0, 2.0),
+            ],
+        )
+        .unwrap();
+        let projected = path.clone().project(&homography);
+        assert_eq!(projected.len(), path.len());
+        assert_is_close!(
+            projected.bounds,
+            Rect::new(Point::new(0.0, 1.0), Point::splat(2.0))
+        );
+
+        let projected = path.clone().project(&Homography::identity());
+        assert_is_close!(projected.bounds, path.bounds);
+        for (&seg, &expected) in projected.iter().zip(path.iter()) {
+            assert_is_close!(seg, expected);
+        }
+    }
+
+    #[test]
+    fn test_path_map_segments() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(1.0)).to_path();
+
+        let mut points = Vec::new();
+        let mapped = path.map_segments(|point, &seg| {
+            points.push(point);
+            seg
+        });
+        assert_eq!(mapped.len(), path.len());
+        assert_is_close!(mapped.bounds, path.bounds);
+        assert_is_close!(points[0], Point::zero()); // Before the initial move
+        assert_is_close!(points[1], Point::zero());
+        assert_is_close!(points[2], Point::new(1.0, 0.0));
+        assert_is_close!(points[3], Point::new(1.0, 1.0));
+
+        // Push lines outwards in the direction they're heading, and check the bounds are updated
+        let mapped = path.map_segments(|_, &seg| match seg {
+            PathSegment::Line(d) => PathSegment::Line(d * 2.0),
+            seg => seg,
+        });
+        assert_is_close!(mapped.bounds, Rect::new(Point::zero(), Point::splat(2.0)));
+    }
+
+    #[test]
+    fn test_path_sample() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(1.0)).to_path();
+
+        let points = path.sample(Length::new(0.5));
+        assert_eq!(points.len(), 9);
+        assert_is_close!(points[0], Point::zero());
+        assert_is_close!(points[1], Point::new(0.5, 0.0));
+        assert_is_close!(points[3], Point::new(1.0, 0.5));
+        assert_is_close!(points[8], Point::zero());
+
+        assert!(path.sample(Length::new(0.0)).is_empty());
+        assert!(path.sample(Length::new(-1.0)).is_empty());
+        assert!(path.sample(Length::new(f32::NAN)).is_empty());
+        assert!(Path::<()>::empty().sample(Length::new(1.0)).is_empty());
+
+        let points = path.sample(Length::new(f32::MIN_POSITIVE));
+        assert_eq!(points.len(), MAX_PATH_SAMPLES);
+        assert_is_close!(points[0], Point::zero());
+        assert_is_close!(points[MAX_PATH_SAMPLES - 1], Point::zero());
+
+        let circle = Circle::<()>::new(Point::zero(), Length::new(1.0)).to_path();
+        let points = circle.sample(Length::new(0.1));
+        // 2π / 0.1 = 62.8
+        assert_eq!(points.len(), 63);
+        // Points lie on the flattened curve, so may be slightly inside the circle
+        for point in points {
+            assert_is_close_abs_tol!(point.to_vector().length(), 1.0, 2e-3);
+        }
+    }
+
+    #[test]
+    fn test_path_point_at() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(1.0)).to_path();
+
+        assert_is_close!(path.point_at(0.0), Point::zero());
+        assert_is_close!(path.point_at(0.125), Point::new(0.5, 0.0));
+        assert_is_close!(path.point_at(0.5), Point::splat(1.0));
+        assert_is_close!(path.point_at(0.875), Point::new(0.0, 0.5));
+        assert_is_close!(path.point_at(1.0), Point::zero());
+        assert_is_close!(path.point_at(2.0), Point::zero());
+
+        assert_is_close!(Path::<()>::empty().point_at(0.5), Point::zero());
+    }
+
+    #[test]
+    fn test_path_last_point() {
+        let mut builder = Path::<()>::builder();
+        builder.abs_move(Point::new(1.0, 1.0));
+        builder.rel_line(Vector::new(1.0, 0.0));
+        builder.rel_cubic_bezier(
+            Vector::new(1.0, 0.0),
+            Vector::new(1.0, 1.0),
+            Vector::new(1.0, 1.0),
+        );
+        let open = builder.clone().build();
+        assert_is_close!(open.last_point().unwrap(), Point::new(3.0, 2.0));
+
+        builder.close();
+        let closed = builder.build();
+        assert_is_close!(closed.last_point().unwrap(), Point::new(1.0, 1.0));
+
+        assert!(Path::<()>::empty().last_point().is_none());
+    }
+
+    #[test]
+    fn test_path_iter_double_ended() {
+        let path = Rect::<()>::new(Point::zero(), Point::splat(1.0)).to_path();
+        let len = path.len();
+
+        assert_eq!(path.iter().len(), len);
+        assert_eq!(path.svg_commands().len(), len);
+        assert_eq!(path.clone().into_iter().len(), len);
+
+        assert_eq!(path.iter().next_back(), path.data.last());
+        assert_eq!(
+            path.svg_commands().next_back().map(|cmd| cmd.command()),
+            Some('z')
+        );
+        assert!(matches!(
+            path.clone().into_iter().next_back(),
+            Some(PathSegment::Close)
+        ));
+
+        let mut reversed: Vec<_> = path.clone().into_iter().rev().collect();
+        reversed.reverse();
+        assert_eq!(reversed.len(), len);
+        for (p1, p2) in reversed.iter().zip(path.iter()) {
+            assert_is_close!(p1, p2);
+        }
+    }
+
     #[test]
     fn test_path_iter() {
         let path = Path::<()> {
@@ -870,6 +1418,33 @@ mod tests {
         for (p1, p2) in path.data.iter().zip(path2.data.iter()) {
             assert_is_close!(*p1 * transform, p2);
         }
+
+        // Axis-aligned transforms map the bounds directly rather than recalculating them
+        let transform = Transform::scale(2.0, -1.0).then_translate(Vector::new(1.0, 2.0));
+        let path2 = path.clone() * transform;
+        assert_is_close!(path2.bounds, calculate_bounds(&path2.data));
+        assert_is_close!(
+            path2.bounds,
+            Rect::new(Point::new(1.0, -1.0), Point::new(7.0, 2.0))
+        );
+
+        let mut path2 = path;
+        path2 *= transform;
+        assert_is_close!(path2.bounds, calculate_bounds(&path2.data));
+
+        // Paths without a leading move include the untransformed origin, so their bounds can't be
+        // mapped directly
+        let path = Path::<()> {
+            data: Box::new([PathSegment::Line(Vector::new(1.0, 0.0))]),
+            bounds: Rect::new(Point::zero(), Point::new(1.0, 0.0)),
+        };
+        let transform = Transform::translation(5.0, 5.0);
+        let path2 = path.clone() * transform;
+        assert_is_close!(path2.bounds, Rect::new(Point::zero(), Point::new(1.0, 0.0)));
+
+        let mut path2 = path;
+        path2 *= transform;
+        assert_is_close!(path2.bounds, Rect::new(Point::zero(), Point::new(1.0, 0.0)));
     }
 
     #[test]
@@ -1013,6 +1588,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_path_builder_extend_raw() {
+        let mut line1 = PathBuilder::<()>::new();
+        line1.abs_line(Point::new(1.0, 1.0));
+
+        let mut line2 = PathBuilder::new();
+        line2.abs_line(Point::new(1.0, 0.0));
+
+        let mut line3 = PathBuilder::new();
+        line3.abs_move(Point::new(0.0, 1.0));
+        line3.abs_line(Point::new(1.0, 0.0));
+
+        // line2 continues from the end of line1 rather than starting at the origin
+        let mut continued = PathBuilder::new();
+        continued.abs_line(Point::new(1.0, 1.0));
+        continued.abs_line(Point::new(2.0, 1.0));
+
+        let mut cross = PathBuilder::new();
+        cross.abs_line(Point::new(1.0, 1.0));
+        cross.abs_move(Point::new(0.0, 1.0));
+        cross.abs_line(Point::new(1.0, 0.0));
+
+        let params = [
+            (PathBuilder::new(), line1.clone(), line1.clone()),
+            (line1.clone(), PathBuilder::new(), line1.clone()),
+            (line1.clone(), line2, continued),
+            (line1, line3, cross),
+        ];
+
+        for (mut first, second, expected) in params {
+            first.extend_raw(second);
+
+            assert_eq!(first.data.len(), expected.data.len());
+            assert_is_close!(first.start, expected.start);
+            assert_is_close!(first.point, expected.point);
+            assert_is_close!(first.bounds, expected.bounds);
+        }
+    }
+
     #[test]
     fn test_commands() {
         let mut mov = PathBuilder::<()>::new();
@@ -1092,6 +1706,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_path_builder_circle() {
+        let mut builder = PathBuilder::<()>::new();
+        builder.circle(Point::new(1.5, 2.0), Length::new(1.0));
+        builder.circle(Point::new(4.0, 2.0), Length::new(0.5));
+        let path = builder.build();
+
+        assert_eq!(
+            path.iter()
+                .filter(|seg| matches!(**seg, PathSegment::Move(..)))
+                .count(),
+            2
+        );
+        assert_is_close!(
+            path.bounds,
+            Rect::new(Point::new(0.5, 1.0), Point::new(4.5, 3.0))
+        );
+        assert_is_close!(path.last_point().unwrap(), Point::new(3.5, 2.0));
+
+        let expected = Circle::new(Point::new(1.5, 2.0), Length::new(1.0)).to_path();
+        for (el, ex) in path.iter().zip(expected.iter()) {
+            assert_is_close!(el, ex);
+        }
+    }
+
+    #[test]
+    fn test_path_builder_rounded_rect() {
+        let rect = Rect::<()>::new(Point::new(1.0, 2.0), Point::new(4.0, 6.0));
+
+        let mut builder = PathBuilder::new();
+        builder.rounded_rect(rect, Vector::new(0.5, 1.0));
+        let path = builder.build();
+
+        assert_eq!(path.len(), 9);
+        assert_is_close!(path.bounds, rect);
+
+        let mut builder = PathBuilder::new();
+        builder.rounded_rect(rect, Vector::zero());
+        assert_is_close!(builder.build().bounds, rect);
+    }
+
     #[test]
     fn test_path_builder_add() {
         let empty = PathBuilder::<()>::new();
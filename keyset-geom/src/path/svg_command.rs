@@ -0,0 +1,132 @@
+use std::fmt;
+
+use super::PathSegment;
+
+/// A single SVG path command, as produced by [`Path::svg_commands`]
+///
+/// Moves use absolute coordinates (`M`) while all other commands use relative coordinates (`l`,
+/// `c`, and `q`), matching the representation of [`PathSegment`]s
+///
+/// [`Path::svg_commands`]: super::Path::svg_commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgCommand {
+    command: char,
+    coords: [f32; 6],
+    len: usize,
+}
+
+impl SvgCommand {
+    /// The SVG command letter
+    #[inline]
+    #[must_use]
+    pub const fn command(&self) -> char {
+        self.command
+    }
+
+    /// The command's coordinates as a flat list of `x, y` pairs
+    #[inline]
+    #[must_use]
+    pub fn coords(&self) -> &[f32] {
+        &self.coords[..self.len]
+    }
+
+    fn new<const N: usize>(command: char, coords: [f32; N]) -> Self {
+        let mut padded = [0.0; 6];
+        padded[..N].copy_from_slice(&coords);
+        Self {
+            command,
+            coords: padded,
+            len: N,
+        }
+    }
+}
+
+impl<U> From<PathSegment<U>> for SvgCommand {
+    #[inline]
+    fn from(segment: PathSegment<U>) -> Self {
+        match segment {
+            PathSegment::Move(p) => Self::new('M', [p.x, p.y]),
+            PathSegment::Line(d) => Self::new('l', [d.x, d.y]),
+            PathSegment::CubicBezier(c1, c2, d) => {
+                Self::new('c', [c1.x, c1.y, c2.x, c2.y, d.x, d.y])
+            }
+            PathSegment::QuadraticBezier(c1, d) => Self::new('q', [c1.x, c1.y, d.x, d.y]),
+            PathSegment::Close => Self::new('z', []),
+        }
+    }
+}
+
+impl fmt::Display for SvgCommand {
+    /// Formats the command as SVG path data. Coordinates are separated by a space, or by their
+    /// sign if negative. Negative zero is written as `0`. If a precision is given, coordinates are
+    /// rounded to that many decimal places with trailing zeros removed
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.command)?;
+        for (i, &coord) in self.coords().iter().enumerate() {
+            let coord = f.precision().map_or(coord, |precision| {
+                let factor = 10_f32.powi(precision.try_into().unwrap_or(i32::MAX));
+                (coord * factor).round() / factor
+            });
+            // Adding zero turns -0 into 0, so we never output a negative zero
+            let coord = coord + 0.0;
+            if i > 0 && coord.is_sign_positive() {
+                write!(f, " ")?;
+            }
+            write!(f, "{coord}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Point, Vector};
+
+    use super::*;
+
+    #[test]
+    fn svg_command_from() {
+        let cmd = SvgCommand::from(PathSegment::<()>::Move(Point::new(1.0, 2.0)));
+        assert_eq!(cmd.command(), 'M');
+        assert_eq!(cmd.coords(), [1.0, 2.0]);
+
+        let cmd = SvgCommand::from(PathSegment::<()>::CubicBezier(
+            Vector::new(1.0, 2.0),
+            Vector::new(3.0, 4.0),
+            Vector::new(5.0, 6.0),
+        ));
+        assert_eq!(cmd.command(), 'c');
+        assert_eq!(cmd.coords(), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let cmd = SvgCommand::from(PathSegment::<()>::QuadraticBezier(
+            Vector::new(1.0, 2.0),
+            Vector::new(3.0, 4.0),
+        ));
+        assert_eq!(cmd.command(), 'q');
+        assert_eq!(cmd.coords(), [1.0, 2.0, 3.0, 4.0]);
+
+        let cmd = SvgCommand::from(PathSegment::<()>::Close);
+        assert_eq!(cmd.command(), 'z');
+        assert!(cmd.coords().is_empty());
+    }
+
+    #[test]
+    fn svg_command_display() {
+        let cmd = SvgCommand::from(PathSegment::<()>::Line(Vector::new(1.5, -2.0)));
+        assert_eq!(format!("{cmd}"), "l1.5-2");
+
+        let cmd = SvgCommand::from(PathSegment::<()>::Move(Point::new(-0.12345, 6.78901)));
+        assert_eq!(format!("{cmd:.3}"), "M-0.123 6.789");
+
+        let cmd = SvgCommand::from(PathSegment::<()>::Close);
+        assert_eq!(format!("{cmd}"), "z");
+
+        // Negative zero is written as zero, including when rounded to zero
+        let cmd = SvgCommand::from(PathSegment::<()>::Line(Vector::new(820.0, -0.0)));
+        assert_eq!(format!("{cmd}"), "l820 0");
+
+        let cmd = SvgCommand::from(PathSegment::<()>::Line(Vector::new(-0.0001, -0.0001)));
+        assert_eq!(format!("{cmd:.3}"), "l0 0");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Angle, Circle, ExtVec, Length, Path, Rect, RoundRect, Size, Vector};
+use crate::{Circle, EllipticalRoundRect, Length, Path, Rect, RoundRect, Vector};
 
 /// Trait to allow conversion of primitive shapes to a [`Path`]
 pub trait ToPath<U> {
@@ -9,26 +9,8 @@ pub trait ToPath<U> {
 impl<U> ToPath<U> for Circle<U> {
     #[inline]
     fn to_path(self) -> Path<U> {
-        let radius = self.radius.get();
-
-        let mut builder = Path::builder_with_capacity(4);
-        builder.abs_move(self.center - Size::new(radius, 0.0));
-        builder.rel_arc(
-            Vector::splat(radius),
-            Angle::zero(),
-            false,
-            true,
-            Vector::new(2.0 * radius, 0.0),
-        );
-        builder.rel_arc(
-            Vector::splat(radius),
-            Angle::zero(),
-            false,
-            true,
-            Vector::new(-2.0 * radius, 0.0),
-        );
-        builder.close();
-
+        let mut builder = Path::builder_with_capacity(6);
+        builder.circle(self.center, self.radius);
         builder.build()
     }
 }
@@ -50,20 +32,17 @@ impl<U> ToPath<U> for Rect<U> {
 impl<U> ToPath<U> for RoundRect<U> {
     #[inline]
     fn to_path(self) -> Path<U> {
-        let radius = self.radius.get();
-        let radii = Vector::splat(radius);
-
         let mut builder = Path::builder_with_capacity(9);
-        builder.abs_move(self.min + Size::new(0.0, radius));
-        builder.rel_arc(radii, Angle::zero(), false, true, radii.neg_y());
-        builder.abs_horiz_line(Length::new(self.max.x - radius));
-        builder.rel_arc(radii, Angle::zero(), false, true, radii);
-        builder.abs_vert_line(Length::new(self.max.y - radius));
-        builder.rel_arc(radii, Angle::zero(), false, true, radii.neg_x());
-        builder.abs_horiz_line(Length::new(self.min.x + radius));
-        builder.rel_arc(radii, Angle::zero(), false, true, -radii);
-        builder.close();
+        builder.rounded_rect(self.rect(), Vector::splat(self.radius.get()));
+        builder.build()
+    }
+}
 
+impl<U> ToPath<U> for EllipticalRoundRect<U> {
+    #[inline]
+    fn to_path(self) -> Path<U> {
+        let mut builder = Path::builder_with_capacity(9);
+        builder.rounded_rect(self.rect(), self.radii);
         builder.build()
     }
 }
@@ -72,7 +51,7 @@ impl<U> ToPath<U> for RoundRect<U> {
 mod tests {
     use isclose::assert_is_close;
 
-    use crate::{PathSegment, Point};
+    use crate::{Angle, PathSegment, Point};
 
     use super::*;
 
@@ -176,4 +155,49 @@ mod tests {
             assert_is_close!(el, ex);
         }
     }
+
+    #[test]
+    fn elliptical_round_rect_to_path() {
+        let rect = EllipticalRoundRect::<()>::new(
+            Point::new(2.0, 4.0),
+            Point::new(6.0, 8.0),
+            Vector::new(1.0, 0.5),
+        );
+        let path = rect.to_path();
+
+        let a = (4.0 / 3.0) * Angle::degrees(90.0 / 4.0).radians.tan();
+        let exp = [
+            PathSegment::<()>::Move(Point::new(2.0, 4.5)),
+            PathSegment::CubicBezier(
+                Vector::new(0.0, -0.5 * a),
+                Vector::new(1.0 - a, -0.5),
+                Vector::new(1.0, -0.5),
+            ),
+            PathSegment::Line(Vector::new(2.0, 0.0)),
+            PathSegment::CubicBezier(
+                Vector::new(a, 0.0),
+                Vector::new(1.0, 0.5 - 0.5 * a),
+                Vector::new(1.0, 0.5),
+            ),
+            PathSegment::Line(Vector::new(0.0, 3.0)),
+            PathSegment::CubicBezier(
+                Vector::new(0.0, 0.5 * a),
+                Vector::new(-(1.0 - a), 0.5),
+                Vector::new(-1.0, 0.5),
+            ),
+            PathSegment::Line(Vector::new(-2.0, 0.0)),
+            PathSegment::CubicBezier(
+                Vector::new(-a, 0.0),
+                Vector::new(-1.0, -(0.5 - 0.5 * a)),
+                Vector::new(-1.0, -0.5),
+            ),
+            PathSegment::Close,
+        ];
+
+        assert_eq!(path.data.len(), exp.len());
+        assert_is_close!(path.bounds, rect.rect());
+        for (el, ex) in path.data.iter().zip(exp) {
+            assert_is_close!(el, ex);
+        }
+    }
 }
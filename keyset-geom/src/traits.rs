@@ -1,9 +1,35 @@
-use crate::{Angle, Point, Rect, Scale, Size, Transform, Vector};
+use std::f32::consts::TAU;
+use std::fmt;
 
-/// Trait to add additional constructor to `Rect`
+use saturate::SaturatingFrom;
+
+use crate::{Angle, Length, Point, Rect, Scale, Size, Transform, Vector};
+
+/// Trait to add additional methods to `Rect`
 pub trait ExtRect<U> {
     /// Create a new `Rect` given a center point and size
     fn from_center_and_size(center: Point<U>, size: Size<U>) -> Self;
+
+    /// Split the rect into left and right parts at the given x coordinate. The coordinate is
+    /// clamped to the rect
+    #[must_use]
+    fn split_at_x(self, x: f32) -> (Self, Self)
+    where
+        Self: Sized;
+
+    /// Split the rect into top and bottom parts at the given y coordinate. The coordinate is
+    /// clamped to the rect
+    #[must_use]
+    fn split_at_y(self, y: f32) -> (Self, Self)
+    where
+        Self: Sized;
+
+    /// Split the rect into a grid of equally sized cells, returned in left-to-right,
+    /// top-to-bottom order. Returns an empty [`Vec`] if `cols` or `rows` is zero
+    #[must_use]
+    fn split_grid(self, cols: usize, rows: usize) -> Vec<Self>
+    where
+        Self: Sized;
 }
 
 impl<U> ExtRect<U> for Rect<U> {
@@ -12,6 +38,102 @@ impl<U> ExtRect<U> for Rect<U> {
         let half_size = size * 0.5;
         Self::new(center - half_size, center + half_size)
     }
+
+    #[inline]
+    fn split_at_x(self, x: f32) -> (Self, Self) {
+        // Don't use f32::clamp since it panics if the rect has a negative width
+        let x = x.max(self.min.x).min(self.max.x);
+        (
+            Self::new(self.min, Point::new(x, self.max.y)),
+            Self::new(Point::new(x, self.min.y), self.max),
+        )
+    }
+
+    #[inline]
+    fn split_at_y(self, y: f32) -> (Self, Self) {
+        // Don't use f32::clamp since it panics if the rect has a negative height
+        let y = y.max(self.min.y).min(self.max.y);
+        (
+            Self::new(self.min, Point::new(self.max.x, y)),
+            Self::new(Point::new(self.min.x, y), self.max),
+        )
+    }
+
+    #[inline]
+    fn split_grid(self, cols: usize, rows: usize) -> Vec<Self> {
+        if cols == 0 || rows == 0 {
+            return Vec::new();
+        }
+
+        // Interpolate each edge so the cells exactly tile the rect without accumulating error
+        let edges = |min: f32, max: f32, n: usize| -> Vec<f32> {
+            (0..=n)
+                .map(|i| min + (max - min) * f32::saturating_from(i) / f32::saturating_from(n))
+                .collect()
+        };
+        let xs = edges(self.min.x, self.max.x, cols);
+        let ys = edges(self.min.y, self.max.y, rows);
+
+        ys.windows(2)
+            .flat_map(|y| {
+                xs.windows(2)
+                    .map(move |x| Self::new(Point::new(x[0], y[0]), Point::new(x[1], y[1])))
+            })
+            .collect()
+    }
+}
+
+/// Trait to add additional methods to `Size`
+///
+/// [`Size::to_vector`], [`Size::area`], and conversion from `(f32, f32)` are already provided by
+/// `Size` itself
+pub trait ExtSize {
+    /// Get the length of the longer side
+    #[must_use]
+    fn max_side(self) -> f32;
+}
+
+impl<U> ExtSize for Size<U> {
+    #[inline]
+    fn max_side(self) -> f32 {
+        self.width.max(self.height)
+    }
+}
+
+/// Trait to add additional methods to `Point`
+pub trait ExtPoint<U> {
+    /// Snap the point to the nearest multiple of `grid` on each axis
+    ///
+    /// Values exactly halfway between two grid increments are rounded to the even multiple
+    /// (round-half-to-even), so repeated snapping does not introduce a directional bias. The point
+    /// is returned unchanged if `grid` is not positive and finite
+    #[must_use]
+    fn snap(self, grid: Length<U>) -> Self;
+
+    /// Get the point halfway between this point and `other`
+    ///
+    /// For linear interpolation at other positions use [`Point::lerp`]
+    #[must_use]
+    fn midpoint(self, other: Self) -> Self;
+}
+
+impl<U> ExtPoint<U> for Point<U> {
+    #[inline]
+    fn snap(self, grid: Length<U>) -> Self {
+        let grid = grid.get();
+        if !grid.is_finite() || grid <= 0.0 {
+            return self;
+        }
+        Self::new(
+            (self.x / grid).round_ties_even() * grid,
+            (self.y / grid).round_ties_even() * grid,
+        )
+    }
+
+    #[inline]
+    fn midpoint(self, other: Self) -> Self {
+        self.lerp(other, 0.5)
+    }
 }
 
 /// Trait to rotate a `Vector`
@@ -49,6 +171,63 @@ impl<U> ExtVec<Angle, U> for Vector<U> {
     }
 }
 
+/// Trait to add additional constructors and accessors to `Angle`
+///
+/// As with [`Angle::degrees`] and [`Angle::radians`], positive angles are clockwise since the
+/// y axis points downwards
+pub trait ExtAngle: Sized {
+    /// Create a new `Angle` from a number of full turns
+    #[must_use]
+    fn turns(turns: f32) -> Self;
+
+    /// Get the angle in radians
+    #[must_use]
+    fn to_radians(self) -> f32;
+
+    /// Get the angle as a number of full turns
+    #[must_use]
+    fn to_turns(self) -> f32;
+
+    /// Get a value which displays the angle in degrees, for example `15.00°`. Degrees are shown
+    /// to two decimal places unless the formatter specifies a precision
+    #[must_use]
+    fn display(self) -> DisplayAngle;
+}
+
+impl ExtAngle for Angle {
+    #[inline]
+    fn turns(turns: f32) -> Self {
+        Self::radians(turns * TAU)
+    }
+
+    #[inline]
+    fn to_radians(self) -> f32 {
+        self.radians
+    }
+
+    #[inline]
+    fn to_turns(self) -> f32 {
+        self.radians / TAU
+    }
+
+    #[inline]
+    fn display(self) -> DisplayAngle {
+        DisplayAngle(self)
+    }
+}
+
+/// Helper struct for displaying an [`Angle`] in degrees, returned by [`ExtAngle::display`]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayAngle(Angle);
+
+impl fmt::Display for DisplayAngle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(2);
+        write!(f, "{:.precision$}°", self.0.to_degrees())
+    }
+}
+
 /// Trait to allow conversion from a [`Scale<U, V>`] to a [`Transform<U, V>`]
 pub trait ToTransform<U, V> {
     /// Convert a [`Scale<U, V>`] to a [`Transform<U, V>`]
@@ -62,11 +241,40 @@ impl<U, V> ToTransform<U, V> for Scale<U, V> {
     }
 }
 
+/// Trait to add additional constructors to `Transform`
+pub trait ExtTransform<U> {
+    /// Create a new `Transform` rotating by `angle` about the point `origin`
+    #[must_use]
+    fn rotate_about(origin: Point<U>, angle: Angle) -> Self;
+
+    /// Create a new `Transform` which scales uniformly by `scale` about the origin, then rotates
+    /// by `rotate` about the origin, then translates by `translate`
+    #[must_use]
+    fn from_parts(translate: Vector<U>, rotate: Angle, scale: f32) -> Self;
+}
+
+impl<U> ExtTransform<U> for Transform<U, U> {
+    #[inline]
+    fn rotate_about(origin: Point<U>, angle: Angle) -> Self {
+        Self::translation(-origin.x, -origin.y)
+            .then_rotate(angle)
+            .then_translate(origin.to_vector())
+    }
+
+    #[inline]
+    fn from_parts(translate: Vector<U>, rotate: Angle, scale: f32) -> Self {
+        Self::scale(scale, scale)
+            .then_rotate(rotate)
+            .then_translate(translate)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use isclose::assert_is_close;
 
     use super::*;
+    use crate::SideOffsets;
 
     #[test]
     fn rect_from_center_and_size() {
@@ -76,6 +284,112 @@ mod tests {
         assert_is_close!(rect, exp);
     }
 
+    #[test]
+    fn rect_split_at() {
+        let rect = Rect::<()>::new(Point::new(1.0, 2.0), Point::new(4.0, 6.0));
+
+        let (left, right) = rect.split_at_x(2.0);
+        assert_is_close!(left, Rect::new(Point::new(1.0, 2.0), Point::new(2.0, 6.0)));
+        assert_is_close!(right, Rect::new(Point::new(2.0, 2.0), Point::new(4.0, 6.0)));
+
+        let (top, bottom) = rect.split_at_y(5.0);
+        assert_is_close!(top, Rect::new(Point::new(1.0, 2.0), Point::new(4.0, 5.0)));
+        assert_is_close!(
+            bottom,
+            Rect::new(Point::new(1.0, 5.0), Point::new(4.0, 6.0))
+        );
+
+        let (left, right) = rect.split_at_x(10.0);
+        assert_is_close!(left, rect);
+        assert!(right.is_empty());
+
+        // Negative sized rects don't panic
+        let rect = Rect::<()>::new(Point::new(4.0, 6.0), Point::new(1.0, 2.0));
+        let (left, right) = rect.split_at_x(2.0);
+        assert!(left.is_empty());
+        assert!(right.is_empty());
+        let (top, bottom) = rect.split_at_y(5.0);
+        assert!(top.is_empty());
+        assert!(bottom.is_empty());
+    }
+
+    #[test]
+    fn rect_split_grid() {
+        let rect = Rect::<()>::new(Point::new(1.0, 2.0), Point::new(4.0, 6.0));
+
+        let cells = rect.split_grid(3, 3);
+        assert_eq!(cells.len(), 9);
+        assert_is_close!(cells[0].min, rect.min);
+        assert_is_close!(cells[8].max, rect.max);
+        assert_is_close!(cells[4].center(), rect.center());
+
+        let area: f32 = cells.iter().map(Rect::area).sum();
+        assert_is_close!(area, rect.area());
+
+        assert!(rect.split_grid(0, 3).is_empty());
+    }
+
+    #[test]
+    fn size_conversions() {
+        let size = Size::<()>::from((1.5, 2.0));
+
+        assert_is_close!(size, Size::new(1.5, 2.0));
+        assert_is_close!(size.to_vector(), Vector::new(1.5, 2.0));
+        assert_is_close!(size.area(), 3.0);
+    }
+
+    #[test]
+    fn size_max_side() {
+        assert_is_close!(Size::<()>::new(1.5, 2.0).max_side(), 2.0);
+        assert_is_close!(Size::<()>::new(6.25, 1.0).max_side(), 6.25);
+        assert_is_close!(Size::<()>::splat(1.0).max_side(), 1.0);
+    }
+
+    #[test]
+    fn point_snap() {
+        let point = Point::<()>::new(0.9999, 2.1);
+        let exp = Point::new(1.0, 2.0);
+
+        assert_is_close!(point.snap(Length::new(0.25)), exp);
+
+        // Round half to even
+        let point = Point::<()>::new(0.125, 0.375);
+        let exp = Point::new(0.0, 0.5);
+
+        assert_is_close!(point.snap(Length::new(0.25)), exp);
+
+        // Degenerate grids leave the point unchanged
+        for grid in [0.0, -0.25, f32::NAN, f32::INFINITY] {
+            assert_eq!(point.snap(Length::new(grid)), point);
+        }
+    }
+
+    #[test]
+    fn point_lerp() {
+        let (a, b) = (Point::<()>::new(1.0, 2.0), Point::new(3.0, -2.0));
+
+        assert_is_close!(a.lerp(b, 0.0), a);
+        assert_is_close!(a.lerp(b, 0.5), Point::new(2.0, 0.0));
+        assert_is_close!(a.lerp(b, 1.0), b);
+    }
+
+    #[test]
+    fn point_midpoint() {
+        let (a, b) = (Point::<()>::new(1.0, 2.0), Point::new(3.0, -2.0));
+
+        assert_is_close!(a.midpoint(b), Point::new(2.0, 0.0));
+        assert_is_close!(a.midpoint(a), a);
+    }
+
+    #[test]
+    fn vector_lerp() {
+        let (a, b) = (Vector::<()>::new(1.0, 2.0), Vector::new(3.0, -2.0));
+
+        assert_is_close!(a.lerp(b, 0.0), a);
+        assert_is_close!(a.lerp(b, 0.5), Vector::new(2.0, 0.0));
+        assert_is_close!(a.lerp(b, 1.0), b);
+    }
+
     #[test]
     fn vector_rotate() {
         let vector = Vector::<()>::new(1.0, 0.0);
@@ -100,6 +414,76 @@ mod tests {
         assert_is_close!(vector.neg_y(), exp);
     }
 
+    #[test]
+    fn angle_units() {
+        let angle = Angle::turns(0.25);
+
+        assert_is_close!(angle.to_degrees(), 90.0);
+        assert_is_close!(angle.to_radians(), std::f32::consts::FRAC_PI_2);
+        assert_is_close!(angle.to_turns(), 0.25);
+
+        assert_is_close!(Angle::degrees(-45.0).to_turns(), -0.125);
+        assert_is_close!(Angle::radians(TAU).to_degrees(), 360.0);
+        assert_is_close!(
+            Angle::turns(Angle::degrees(30.0).to_turns()).to_degrees(),
+            30.0
+        );
+
+        // Clockwise-positive, same as Angle::degrees
+        assert_is_close!(
+            Vector::<()>::new(1.0, 0.0).rotate(Angle::turns(0.25)),
+            Vector::new(0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn angle_ops() {
+        let angle = Angle::degrees(30.0);
+
+        assert_is_close!((angle + Angle::degrees(15.0)).to_degrees(), 45.0);
+        assert_is_close!((angle - Angle::degrees(45.0)).to_degrees(), -15.0);
+        assert_is_close!((-angle).to_degrees(), -30.0);
+        assert_is_close!((angle * 2.0).to_degrees(), 60.0);
+        assert_is_close!((angle / 2.0).to_degrees(), 15.0);
+
+        // Arithmetic doesn't wrap around, but angles can be normalised explicitly
+        let angle = Angle::degrees(270.0) + Angle::degrees(180.0);
+        assert_is_close!(angle.to_degrees(), 450.0);
+        assert_is_close!(angle.signed().to_degrees(), 90.0);
+        assert_is_close!((-angle).positive().to_degrees(), 270.0);
+    }
+
+    #[test]
+    fn side_offsets_ops() {
+        let offsets = SideOffsets::<()>::new(1.0, 2.0, 3.0, 4.0);
+        let rect = Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 20.0));
+
+        assert_is_close!(
+            rect.inner_box(offsets),
+            Rect::new(Point::new(4.0, 1.0), Point::new(8.0, 17.0))
+        );
+        assert_is_close!(rect.inner_box(offsets).outer_box(offsets), rect);
+        assert_is_close!(offsets.horizontal(), 6.0);
+        assert_is_close!(offsets.vertical(), 4.0);
+        assert_is_close!(
+            offsets + SideOffsets::new_all_same(1.0),
+            SideOffsets::new(2.0, 3.0, 4.0, 5.0)
+        );
+        assert_is_close!(offsets * 2.0, SideOffsets::new(2.0, 4.0, 6.0, 8.0));
+
+        // Negative offsets grow the rect
+        assert_is_close!(
+            rect.inner_box(-offsets),
+            Rect::new(Point::new(-4.0, -1.0), Point::new(12.0, 23.0))
+        );
+    }
+
+    #[test]
+    fn angle_display() {
+        assert_eq!(format!("{}", Angle::degrees(15.0).display()), "15.00°");
+        assert_eq!(format!("{:.0}", Angle::turns(-0.5).display()), "-180°");
+    }
+
     #[test]
     fn scale_to_transform() {
         let scale = Scale::<(), ()>::new(2.0);
@@ -107,4 +491,39 @@ mod tests {
 
         assert_is_close!(scale.to_transform(), exp);
     }
+
+    #[test]
+    fn transform_rotate_about() {
+        let origin = Point::<()>::new(1.0, 2.0);
+        let transform = Transform::rotate_about(origin, Angle::degrees(90.0));
+
+        assert_is_close!(transform.transform_point(origin), origin);
+        assert_is_close!(
+            transform.transform_point(Point::new(2.0, 2.0)),
+            Point::new(1.0, 3.0)
+        );
+        assert_is_close!(
+            transform.transform_point(Point::new(1.0, 0.0)),
+            Point::new(3.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn transform_from_parts() {
+        let transform =
+            Transform::<(), ()>::from_parts(Vector::new(1.0, 2.0), Angle::degrees(90.0), 2.0);
+
+        assert_is_close!(
+            transform.transform_point(Point::zero()),
+            Point::new(1.0, 2.0)
+        );
+        assert_is_close!(
+            transform.transform_point(Point::new(1.0, 0.0)),
+            Point::new(1.0, 4.0)
+        );
+        assert_is_close!(
+            transform.transform_point(Point::new(0.0, 1.0)),
+            Point::new(-1.0, 2.0)
+        );
+    }
 }
@@ -0,0 +1,140 @@
+use geom::{Point, Size};
+use saturate::SaturatingFrom;
+
+use crate::{Key, Shape};
+
+// Keys at least this wide are treated as spacebars
+const MIN_SPACE_WIDTH: f32 = 4.0;
+
+// Key widths for each row of a standard ANSI 60% layout
+const ANSI_60_ROWS: [&[f32]; 5] = [
+    &[
+        1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0,
+    ],
+    &[
+        1.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.5,
+    ],
+    &[
+        1.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.25,
+    ],
+    &[2.25, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.75],
+    &[1.25, 1.25, 1.25, 6.25, 1.25, 1.25, 1.25, 1.25],
+];
+
+/// Generate a grid of `rows` &times; `cols` keys, for example to scaffold a layout before adjusting
+/// it to match an existing keyboard
+///
+/// `sizes` contains the width in units of each key in left-to-right, top-to-bottom order. Keys
+/// without a corresponding size are 1u wide. Each row starts at the left edge, so wider keys push
+/// the rest of their row to the right. Keys 4u or wider are spacebars
+#[must_use]
+pub fn grid_from_dimensions(rows: u8, cols: u8, sizes: &[f32]) -> Vec<Key> {
+    let mut sizes = sizes.iter().copied();
+    (0..rows)
+        .flat_map(|row| {
+            let widths: Vec<_> = (0..cols).map(|_| sizes.next().unwrap_or(1.0)).collect();
+            row_keys(f32::from(row), &widths)
+        })
+        .collect()
+}
+
+/// Generate an ortholinear grid of `rows` &times; `cols` 1u keys
+#[inline]
+#[must_use]
+pub fn ortho(rows: u8, cols: u8) -> Vec<Key> {
+    grid_from_dimensions(rows, cols, &[])
+}
+
+/// Generate a standard staggered ANSI 60% layout
+///
+/// The layout has 61 blank keys, with a 6.25u spacebar and 1.25u bottom row modifiers
+#[must_use]
+pub fn staggered_ansi_60() -> Vec<Key> {
+    ANSI_60_ROWS
+        .iter()
+        .enumerate()
+        .flat_map(|(row, widths)| row_keys(f32::saturating_from(row), widths))
+        .collect()
+}
+
+// Lay out a single row of keys with the given widths, starting at the left edge
+fn row_keys(y: f32, widths: &[f32]) -> Vec<Key> {
+    let mut x = 0.0;
+    widths
+        .iter()
+        .map(|&width| {
+            let size = Size::new(width, 1.0);
+            let key = Key {
+                position: Point::new(x, y),
+                shape: if width >= MIN_SPACE_WIDTH {
+                    Shape::Space(size)
+                } else {
+                    Shape::Normal(size)
+                },
+                ..Key::new()
+            };
+            x += width;
+            key
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn test_grid_from_dimensions() {
+        let keys = grid_from_dimensions(2, 3, &[1.5, 1.0, 6.25]);
+
+        assert_eq!(keys.len(), 6);
+        let positions: Vec<_> = keys.iter().map(|key| key.position).collect();
+        let expected = [
+            (0.0, 0.0),
+            (1.5, 0.0),
+            (2.5, 0.0),
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (2.0, 1.0),
+        ]
+        .map(Point::from);
+        for (position, expected) in positions.iter().zip(expected) {
+            assert_is_close!(*position, expected);
+        }
+        assert!(keys[2].is_space());
+        assert!(!keys[0].is_space());
+        assert_is_close!(keys[0].shape.outer_rect().width(), 1.5);
+
+        assert!(grid_from_dimensions(0, 3, &[]).is_empty());
+        assert!(grid_from_dimensions(3, 0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_ortho() {
+        let keys = ortho(4, 12);
+
+        assert_eq!(keys.len(), 48);
+        assert_is_close!(keys[13].position, Point::new(1.0, 1.0));
+        assert_is_close!(keys[47].position, Point::new(11.0, 3.0));
+    }
+
+    #[test]
+    fn test_staggered_ansi_60() {
+        let keys = staggered_ansi_60();
+
+        assert_eq!(keys.len(), 61);
+        assert_eq!(keys.iter().filter(|key| key.is_space()).count(), 1);
+
+        // Every row is 15u wide
+        for row in 0_u8..5 {
+            let right = keys
+                .iter()
+                .filter(|key| key.position.y.to_bits() == f32::from(row).to_bits())
+                .map(|key| key.position.x + key.shape.outer_rect().width())
+                .fold(0.0, f32::max);
+            assert_is_close!(right, 15.0);
+        }
+    }
+}
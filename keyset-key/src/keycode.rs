@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use color::Color;
+
+use crate::{Key, Legend};
+
+// The legend size and colour used for legends created from keycodes. These match KLE's defaults
+const DEFAULT_SIZE_IDX: usize = 3;
+const DEFAULT_COLOR: Color = Color::new(0.0, 0.0, 0.0);
+
+// Labels for QMK's basic keycodes other than letters, digits, and function keys
+const QMK_LABELS: [(&str, &str); 62] = [
+    ("KC_ENTER", "Enter"),
+    ("KC_ESCAPE", "Esc"),
+    ("KC_BACKSPACE", "Backspace"),
+    ("KC_TAB", "Tab"),
+    ("KC_SPACE", ""),
+    ("KC_MINUS", "_\n-"),
+    ("KC_EQUAL", "+\n="),
+    ("KC_LEFT_BRACKET", "{\n["),
+    ("KC_RIGHT_BRACKET", "}\n]"),
+    ("KC_BACKSLASH", "|\n\\"),
+    ("KC_NONUS_HASH", "~\n#"),
+    ("KC_SEMICOLON", ":\n;"),
+    ("KC_QUOTE", "\"\n'"),
+    ("KC_GRAVE", "~\n`"),
+    ("KC_COMMA", "<\n,"),
+    ("KC_DOT", ">\n."),
+    ("KC_SLASH", "?\n/"),
+    ("KC_NONUS_BACKSLASH", "|\n\\"),
+    ("KC_CAPS_LOCK", "Caps Lock"),
+    ("KC_PRINT_SCREEN", "PrtSc"),
+    ("KC_SCROLL_LOCK", "Scroll Lock"),
+    ("KC_PAUSE", "Pause"),
+    ("KC_INSERT", "Insert"),
+    ("KC_HOME", "Home"),
+    ("KC_PAGE_UP", "PgUp"),
+    ("KC_DELETE", "Delete"),
+    ("KC_END", "End"),
+    ("KC_PAGE_DOWN", "PgDn"),
+    ("KC_RIGHT", "→"),
+    ("KC_LEFT", "←"),
+    ("KC_DOWN", "↓"),
+    ("KC_UP", "↑"),
+    ("KC_NUM_LOCK", "Num Lock"),
+    ("KC_KP_SLASH", "/"),
+    ("KC_KP_ASTERISK", "*"),
+    ("KC_KP_MINUS", "-"),
+    ("KC_KP_PLUS", "+"),
+    ("KC_KP_ENTER", "Enter"),
+    ("KC_KP_1", "1"),
+    ("KC_KP_2", "2"),
+    ("KC_KP_3", "3"),
+    ("KC_KP_4", "4"),
+    ("KC_KP_5", "5"),
+    ("KC_KP_6", "6"),
+    ("KC_KP_7", "7"),
+    ("KC_KP_8", "8"),
+    ("KC_KP_9", "9"),
+    ("KC_KP_0", "0"),
+    ("KC_KP_DOT", "."),
+    ("KC_APPLICATION", "Menu"),
+    ("KC_LEFT_CTRL", "Ctrl"),
+    ("KC_LEFT_SHIFT", "Shift"),
+    ("KC_LEFT_ALT", "Alt"),
+    ("KC_LEFT_GUI", "Win"),
+    ("KC_RIGHT_CTRL", "Ctrl"),
+    ("KC_RIGHT_SHIFT", "Shift"),
+    ("KC_RIGHT_ALT", "Alt"),
+    ("KC_RIGHT_GUI", "Win"),
+    ("KC_NO", ""),
+    ("XXXXXXX", ""),
+    ("KC_TRANSPARENT", ""),
+    ("_______", ""),
+];
+
+// Short aliases for QMK keycodes, mapping to their full names
+const QMK_ALIASES: [(&str, &str); 42] = [
+    ("KC_ENT", "KC_ENTER"),
+    ("KC_ESC", "KC_ESCAPE"),
+    ("KC_BSPC", "KC_BACKSPACE"),
+    ("KC_SPC", "KC_SPACE"),
+    ("KC_MINS", "KC_MINUS"),
+    ("KC_EQL", "KC_EQUAL"),
+    ("KC_LBRC", "KC_LEFT_BRACKET"),
+    ("KC_RBRC", "KC_RIGHT_BRACKET"),
+    ("KC_BSLS", "KC_BACKSLASH"),
+    ("KC_NUHS", "KC_NONUS_HASH"),
+    ("KC_SCLN", "KC_SEMICOLON"),
+    ("KC_QUOT", "KC_QUOTE"),
+    ("KC_GRV", "KC_GRAVE"),
+    ("KC_COMM", "KC_COMMA"),
+    ("KC_SLSH", "KC_SLASH"),
+    ("KC_NUBS", "KC_NONUS_BACKSLASH"),
+    ("KC_CAPS", "KC_CAPS_LOCK"),
+    ("KC_PSCR", "KC_PRINT_SCREEN"),
+    ("KC_SCRL", "KC_SCROLL_LOCK"),
+    ("KC_PAUS", "KC_PAUSE"),
+    ("KC_INS", "KC_INSERT"),
+    ("KC_PGUP", "KC_PAGE_UP"),
+    ("KC_DEL", "KC_DELETE"),
+    ("KC_PGDN", "KC_PAGE_DOWN"),
+    ("KC_RGHT", "KC_RIGHT"),
+    ("KC_NUM", "KC_NUM_LOCK"),
+    ("KC_PSLS", "KC_KP_SLASH"),
+    ("KC_PAST", "KC_KP_ASTERISK"),
+    ("KC_PMNS", "KC_KP_MINUS"),
+    ("KC_PPLS", "KC_KP_PLUS"),
+    ("KC_PENT", "KC_KP_ENTER"),
+    ("KC_PDOT", "KC_KP_DOT"),
+    ("KC_APP", "KC_APPLICATION"),
+    ("KC_LCTL", "KC_LEFT_CTRL"),
+    ("KC_LSFT", "KC_LEFT_SHIFT"),
+    ("KC_LALT", "KC_LEFT_ALT"),
+    ("KC_LGUI", "KC_LEFT_GUI"),
+    ("KC_RCTL", "KC_RIGHT_CTRL"),
+    ("KC_RSFT", "KC_RIGHT_SHIFT"),
+    ("KC_RALT", "KC_RIGHT_ALT"),
+    ("KC_RGUI", "KC_RIGHT_GUI"),
+    ("KC_TRNS", "KC_TRANSPARENT"),
+];
+
+// Labels for digit keys, with their shifted symbols on a US layout
+const QMK_DIGITS: [(char, char); 10] = [
+    ('1', '!'),
+    ('2', '@'),
+    ('3', '#'),
+    ('4', '$'),
+    ('5', '%'),
+    ('6', '^'),
+    ('7', '&'),
+    ('8', '*'),
+    ('9', '('),
+    ('0', ')'),
+];
+
+/// A mapping from firmware keycodes to legend labels, used by [`apply_keycodes`]
+///
+/// Labels can contain multiple lines separated by `\n`
+#[derive(Debug, Clone, Default)]
+pub struct KeycodeLabelMap(HashMap<String, String>);
+
+impl KeycodeLabelMap {
+    /// Create a new empty map
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a map of labels for QMK's basic keycodes, including their short aliases
+    ///
+    /// Labels are based on a US ANSI layout. No-op and transparent keycodes map to an empty label
+    #[must_use]
+    pub fn qmk() -> Self {
+        let mut map = Self::new();
+
+        for letter in 'A'..='Z' {
+            map.insert(format!("KC_{letter}"), letter.to_string());
+        }
+        for (digit, shifted) in QMK_DIGITS {
+            map.insert(format!("KC_{digit}"), format!("{shifted}\n{digit}"));
+        }
+        for n in 1..=24 {
+            map.insert(format!("KC_F{n}"), format!("F{n}"));
+        }
+        for (keycode, label) in QMK_LABELS {
+            map.insert(keycode, label);
+        }
+        for (alias, keycode) in QMK_ALIASES {
+            if let Some(label) = map.get(keycode).map(str::to_owned) {
+                map.insert(alias, label);
+            }
+        }
+
+        map
+    }
+
+    /// Add or replace the label for a keycode
+    #[inline]
+    pub fn insert(&mut self, keycode: impl Into<String>, label: impl Into<String>) {
+        self.0.insert(keycode.into(), label.into());
+    }
+
+    /// Get the label for a keycode, if it is in the map
+    #[inline]
+    #[must_use]
+    pub fn get(&self, keycode: &str) -> Option<&str> {
+        self.0.get(keycode).map(String::as_str)
+    }
+}
+
+/// Set each key's centre legend from the corresponding firmware keycode
+///
+/// Keycodes are looked up in `map`, and keycodes missing from the map are used as-is with any
+/// `KC_` prefix removed. Keys whose keycode maps to an empty label have their centre legend
+/// cleared. Other legends are left unchanged, as are any keys without a corresponding keycode
+pub fn apply_keycodes(keys: &mut [Key], keycodes: &[&str], map: &KeycodeLabelMap) {
+    for (key, &keycode) in keys.iter_mut().zip(keycodes) {
+        let keycode = keycode.trim();
+        let label = map
+            .get(keycode)
+            .unwrap_or_else(|| keycode.strip_prefix("KC_").unwrap_or(keycode));
+
+        key.legends[4] = (!label.is_empty()).then(|| {
+            let text = label.lines().collect::<Vec<_>>().join("<br>");
+            Legend::new(&text, DEFAULT_SIZE_IDX, DEFAULT_COLOR)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keycode_label_map_qmk() {
+        let map = KeycodeLabelMap::qmk();
+
+        assert_eq!(map.get("KC_A"), Some("A"));
+        assert_eq!(map.get("KC_1"), Some("!\n1"));
+        assert_eq!(map.get("KC_F12"), Some("F12"));
+        assert_eq!(map.get("KC_ESC"), Some("Esc"));
+        assert_eq!(map.get("KC_ESCAPE"), Some("Esc"));
+        assert_eq!(map.get("KC_LSFT"), Some("Shift"));
+        assert_eq!(map.get("KC_TRNS"), Some(""));
+        assert_eq!(map.get("KC_FOO"), None);
+    }
+
+    #[test]
+    fn test_apply_keycodes() {
+        let mut keys = vec![Key::example(); 5];
+        let mut map = KeycodeLabelMap::qmk();
+        map.insert("MO(1)", "Fn");
+
+        apply_keycodes(&mut keys, &["KC_ESC", "KC_1", "MO(1)", "KC_NO"], &map);
+
+        let center: Vec<_> = keys
+            .iter()
+            .map(|key| {
+                key.legends[4]
+                    .as_ref()
+                    .map(|l| l.text.lines().collect::<Vec<_>>())
+            })
+            .collect();
+        assert_eq!(
+            center,
+            [
+                Some(vec!["Esc"]),
+                Some(vec!["!", "1"]),
+                Some(vec!["Fn"]),
+                None,
+                None,
+            ]
+        );
+        // Other legends are unchanged
+        assert!(keys[0].legends[0].is_some());
+
+        let mut keys = [Key::example()];
+        apply_keycodes(&mut keys, &["KC_MUTE"], &KeycodeLabelMap::new());
+        assert_eq!(
+            keys[0].legends[4].as_ref().unwrap().text.to_string(),
+            "MUTE"
+        );
+    }
+}
@@ -1,22 +1,39 @@
 //! Load KLE layouts from JSON files
 
 mod error;
+mod warning;
 
-use geom::{Point, Size};
+use geom::{Angle, Point, Size};
 use kle_serial::f32 as kle;
+use log::warn;
 
-use crate::{Homing, Key, Legend, Shape, Text};
+use crate::{layout, Homing, Key, Layout, Legend, Shape, Text};
 pub use error::{Error, Result};
+pub use warning::Warning;
+
+// The dimensions of a stepped caps lock key in KLE, as [w, h, x2, y2, w2, h2]
+const STEP_CAPS: [f32; 6] = [1.25, 1.0, 0.0, 0.0, 1.75, 1.0];
+
+fn is_close<const N: usize>(a: &[f32; N], b: &[f32; N]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (b - a).abs() < 1e-2)
+}
+
+fn is_stepped_caps(key: &kle::Key) -> bool {
+    let dims = [
+        key.width,
+        key.height,
+        key.x2,
+        key.y2,
+        key.width2,
+        key.height2,
+    ];
+    is_close(&dims, &STEP_CAPS)
+}
 
 fn shape_from_kle(key: &kle::Key) -> Result<Shape> {
-    const STEP_CAPS: [f32; 6] = [1.25, 1.0, 0.0, 0.0, 1.75, 1.0];
     const ISO_VERT: [f32; 6] = [1.25, 2.0, -0.25, 0.0, 1.5, 1.0];
     const ISO_HORIZ: [f32; 6] = [1.5, 1.0, 0.25, 0.0, 1.25, 2.0];
 
-    fn is_close<const N: usize>(a: &[f32; N], b: &[f32; N]) -> bool {
-        a.iter().zip(b).all(|(a, b)| (b - a).abs() < 1e-2)
-    }
-
     let &kle::Key {
         width: w,
         height: h,
@@ -46,7 +63,7 @@ fn shape_from_kle(key: &kle::Key) -> Result<Shape> {
         Ok(Shape::None(Size::new(w, h)))
     } else if is_normal {
         Ok(Shape::Normal(Size::new(w, h)))
-    } else if is_close(&dims, &STEP_CAPS) {
+    } else if is_stepped_caps(key) {
         Ok(Shape::SteppedCaps)
     } else if is_close(&dims, &ISO_VERT) {
         Ok(Shape::IsoVertical)
@@ -64,6 +81,15 @@ fn shape_from_kle(key: &kle::Key) -> Result<Shape> {
     }
 }
 
+fn row_from_kle(key: &kle::Key) -> Option<u8> {
+    // KLE profiles are usually given as e.g. "SA R1" or "DCS R4"
+    key.profile.split_whitespace().find_map(|s| {
+        s.strip_prefix(['R', 'r'])
+            .and_then(|row| row.parse().ok())
+            .filter(|&row| row > 0)
+    })
+}
+
 impl From<kle::Legend> for Legend {
     #[inline]
     fn from(legend: kle::Legend) -> Self {
@@ -71,7 +97,11 @@ impl From<kle::Legend> for Legend {
         Self {
             text: Text::parse_from(&text),
             size_idx: size,
+            height: None,
             color: color.rgb().into(),
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         }
     }
 }
@@ -82,6 +112,7 @@ impl TryFrom<kle::Key> for Key {
     fn try_from(mut key: kle::Key) -> Result<Self> {
         let position = Point::new(key.x + key.x2.min(0.0), key.y + key.y2.min(0.0));
         let shape = shape_from_kle(&key)?;
+        let row = row_from_kle(&key);
         let color = key.color.rgb().into();
         let legends = {
             let mut arr = <[Option<kle::Legend>; 9]>::default();
@@ -93,29 +124,119 @@ impl TryFrom<kle::Key> for Key {
             position,
             shape,
             color,
+            top_color: None,
             legends,
+            row,
+            z_order: 0,
             __non_exhaustive: super::NonExhaustive,
         })
     }
 }
 
+fn warning_from_kle(key_index: usize, key: &kle::Key) -> Option<Warning> {
+    (key.rotation.abs() > 1e-3).then(|| Warning::UnsupportedRotation {
+        key_index,
+        angle: Angle::degrees(key.rotation),
+    })
+}
+
+fn step_warning(key_index: usize, key: &kle::Key) -> Option<Warning> {
+    (key.stepped && !is_stepped_caps(key)).then(|| Warning::UnsupportedStep {
+        key_index,
+        size: Size::new(key.width, key.height),
+    })
+}
+
 /// Loads a KLE layout from a JSON string into a [`Box<[Key]>`]
 ///
+/// Any [`Warning`]s encountered while loading the layout are logged. Use
+/// [`from_json_with_warnings`] to handle these programmatically instead
+///
 /// # Errors
 ///
 /// If an invalid or unsupported JSON string is encountered, this will return an [`Error`]
 #[inline]
 pub fn from_json(json: &str) -> Result<Box<[Key]>> {
+    let (keys, warnings) = from_json_with_warnings(json)?;
+    for warning in warnings {
+        warn!("{warning}");
+    }
+    Ok(keys)
+}
+
+/// Loads a KLE layout from a JSON string into a [`Layout`]
+///
+/// This is equivalent to [`from_json`] but returns a [`Layout`] rather than a [`Box<[Key]>`]
+///
+/// # Errors
+///
+/// If an invalid or unsupported JSON string is encountered, this will return an [`Error`]
+#[inline]
+pub fn layout_from_json(json: &str) -> Result<Layout> {
+    from_json(json).map(Layout::from)
+}
+
+/// Loads a KLE layout from a JSON string into a [`Box<[Key]>`], also returning any [`Warning`]s
+/// encountered while loading the layout
+///
+/// # Errors
+///
+/// If an invalid or unsupported JSON string is encountered, this will return an [`Error`]
+#[inline]
+pub fn from_json_with_warnings(json: &str) -> Result<(Box<[Key]>, Vec<Warning>)> {
     let key_iter: kle::KeyIterator = serde_json::from_str(json)?;
-    key_iter.map(Key::try_from).collect()
+
+    let mut warnings = Vec::new();
+    let keys = key_iter
+        .enumerate()
+        .map(|(i, key)| {
+            warnings.extend(warning_from_kle(i, &key));
+            warnings.extend(step_warning(i, &key));
+            Key::try_from(key)
+        })
+        .collect::<Result<Box<_>>>()?;
+    warnings.extend(layout::validate(&keys).into_iter().map(Warning::Layout));
+
+    Ok((keys, warnings))
+}
+
+/// Loads a single key from a KLE legend string, as would appear in a KLE layout's JSON
+///
+/// The legend string is newline-delimited and is realigned in the same way as in [`from_json`].
+/// An optional JSON object of KLE properties (e.g. `{"a": 4, "w": 1.5}`) can be given, otherwise
+/// KLE's default properties are used
+///
+/// # Errors
+///
+/// If `props` is not a valid JSON object or describes an unsupported key, this will return an
+/// [`Error`]
+#[inline]
+pub fn key_from_str(legends: &str, props: Option<&str>) -> Result<Key> {
+    let mut row: Vec<serde_json::Value> = Vec::with_capacity(2);
+    if let Some(props) = props {
+        let props: serde_json::Map<String, serde_json::Value> = serde_json::from_str(props)?;
+        row.push(props.into());
+    }
+    row.push(legends.into());
+
+    let mut key_iter: kle::KeyIterator =
+        serde_json::from_value(serde_json::Value::Array(vec![row.into()]))?;
+    let key = key_iter
+        .next()
+        .unwrap_or_else(|| unreachable!("a row with one legend string should yield one key"));
+
+    Key::try_from(key)
 }
 
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use geom::Rect;
     use indoc::indoc;
     use isclose::{assert_is_close, IsClose};
 
+    use crate::LayoutWarning;
+
     use super::*;
 
     #[test]
@@ -228,6 +349,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn key_row_from_kle() {
+        let rows = ["", "SA R1", "DCS r4", "space", "R0", "Rx"].map(|profile| {
+            row_from_kle(&kle::Key {
+                profile: profile.into(),
+                ..Default::default()
+            })
+        });
+
+        assert_eq!(rows, [None, Some(1), Some(4), None, None, None]);
+    }
+
     #[test]
     fn kle_from_json() {
         let result1 = from_json(indoc!(
@@ -276,4 +409,117 @@ mod tests {
 
         assert_eq!(result2.len(), 1);
     }
+
+    #[test]
+    fn kle_layout_from_json() {
+        let layout = layout_from_json(r#"[["A", "B"], ["C"]]"#).unwrap();
+
+        assert_eq!(layout.len(), 3);
+        assert_is_close!(
+            layout.bounds(),
+            Rect::new(Point::origin(), Point::new(2.0, 2.0))
+        );
+
+        assert!(layout_from_json("invalid").is_err());
+    }
+
+    #[test]
+    fn kle_from_json_with_warnings() {
+        let (keys, warnings) = from_json_with_warnings(indoc!(
+            r#"
+            [
+                [
+                    "A",
+                    {
+                        "r": 15,
+                        "rx": 1,
+                        "ry": 1
+                    },
+                    "B"
+                ]
+            ]
+            "#,
+        ))
+        .unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            Warning::UnsupportedRotation { key_index: 1, angle }
+                if angle.is_close(Angle::degrees(15.0))
+        );
+
+        let (_, warnings) =
+            from_json_with_warnings(r#"[[{"n": true}, "F", {"n": true}, "J", "K"]]"#).unwrap();
+        assert!(warnings.is_empty());
+
+        let (keys, warnings) = from_json_with_warnings(
+            r#"[[{"n": true}, "F", {"n": true}, "J"], [{"n": true}, "K", "L", {"n": true}, "M"]]"#,
+        )
+        .unwrap();
+
+        assert_eq!(keys.len(), 5);
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            Warning::Layout(LayoutWarning::TooManyHomingKeys { ref key_indices })
+                if **key_indices == [0, 1, 2, 4]
+        );
+    }
+
+    #[test]
+    fn kle_stepped_caps() {
+        // The caps lock key from the README's example layout
+        let (keys, warnings) = from_json_with_warnings(
+            r#"[[{"w": 1.25, "w2": 1.75, "l": true}, "Caps<br>Lock", "A"]]"#,
+        )
+        .unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_matches!(keys[0].shape, Shape::SteppedCaps);
+        assert_eq!(
+            keys[0].legends[0]
+                .as_ref()
+                .unwrap()
+                .text
+                .lines()
+                .collect::<Vec<_>>(),
+            ["Caps", "Lock"]
+        );
+        assert_is_close!(keys[1].position, Point::new(1.25, 0.0));
+        assert!(warnings.is_empty());
+
+        let (keys, warnings) =
+            from_json_with_warnings(r#"[["A", {"w": 1.5, "l": true}, "Tab"]]"#).unwrap();
+
+        assert_matches!(keys[1].shape, Shape::Normal(size) if size.is_close(Size::new(1.5, 1.0)));
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            Warning::UnsupportedStep { key_index: 1, size } if size.is_close(Size::new(1.5, 1.0))
+        );
+    }
+
+    #[test]
+    fn kle_key_from_str() {
+        let key = key_from_str("A\nB", None).unwrap();
+
+        assert_is_close!(key.position, Point::new(0.0, 0.0));
+        assert_matches!(key.shape, Shape::Normal(size) if size.is_close(Size::new(1.0, 1.0)));
+        assert_eq!(key.legends[0].as_ref().unwrap().text.to_string(), "A");
+        assert_eq!(key.legends[6].as_ref().unwrap().text.to_string(), "B");
+
+        let key = key_from_str("A\nB", Some(r#"{"a": 5, "w": 1.5}"#)).unwrap();
+
+        assert_matches!(key.shape, Shape::Normal(size) if size.is_close(Size::new(1.5, 1.0)));
+        assert_eq!(key.legends[1].as_ref().unwrap().text.to_string(), "A");
+        assert_eq!(key.legends[7].as_ref().unwrap().text.to_string(), "B");
+
+        assert_matches!(key_from_str("A", Some("[]")), Err(Error::JsonParseError(_)));
+        assert_matches!(
+            key_from_str("A", Some(r#"{"w": 1.5, "w2": 2}"#)),
+            Err(Error::UnsupportedKeySize { .. })
+        );
+    }
 }
@@ -0,0 +1,88 @@
+use std::fmt;
+
+use geom::{Angle, ExtAngle, Size, Unit};
+
+use crate::LayoutWarning;
+
+/// A non-fatal issue encountered while loading a KLE layout
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The key is rotated, which is not yet supported by `keyset`. The key is placed as if it
+    /// were not rotated
+    UnsupportedRotation {
+        /// The index of the key in the layout
+        key_index: usize,
+        /// The rotation that could not be applied. Positive angles are clockwise
+        angle: Angle,
+    },
+    /// The key has KLE's stepped flag set, but its dimensions don't match a stepped caps lock key,
+    /// i.e. a 1.25u key with a 1.75u step. The key is drawn without a step
+    UnsupportedStep {
+        /// The index of the key in the layout
+        key_index: usize,
+        /// The size of the key
+        size: Size<Unit>,
+    },
+    /// The loaded layout has a likely mistake, as found by
+    /// [`Layout::validate`](crate::Layout::validate)
+    Layout(LayoutWarning),
+}
+
+impl fmt::Display for Warning {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnsupportedRotation { key_index, angle } => write!(
+                f,
+                "unsupported rotation of {} for key {key_index}. Note rotated keys are \
+                currently drawn without rotation",
+                angle.display()
+            ),
+            Self::UnsupportedStep { key_index, size } => write!(
+                f,
+                "unsupported stepped key of size {}u \u{d7} {}u for key {key_index}, only 1.25u \
+                caps lock keys with a 1.75u step are supported. Note the key is drawn without a \
+                step",
+                size.width, size.height
+            ),
+            Self::Layout(ref warning) => warning.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_fmt() {
+        let unsupported_rotation = Warning::UnsupportedRotation {
+            key_index: 3,
+            angle: Angle::degrees(15.0),
+        };
+        assert_eq!(
+            format!("{unsupported_rotation}"),
+            "unsupported rotation of 15.00° for key 3. Note rotated keys are currently drawn \
+            without rotation"
+        );
+
+        let unsupported_step = Warning::UnsupportedStep {
+            key_index: 2,
+            size: Size::new(1.5, 1.0),
+        };
+        assert_eq!(
+            format!("{unsupported_step}"),
+            "unsupported stepped key of size 1.5u \u{d7} 1u for key 2, only 1.25u caps lock keys \
+            with a 1.75u step are supported. Note the key is drawn without a step"
+        );
+
+        let too_many_homing_keys = Warning::Layout(LayoutWarning::TooManyHomingKeys {
+            key_indices: [1, 4, 7].into(),
+        });
+        assert_eq!(
+            format!("{too_many_homing_keys}"),
+            "found 3 homing keys, expected at most 2. Homing keys are at indices [1, 4, 7]"
+        );
+    }
+}
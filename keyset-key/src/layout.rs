@@ -0,0 +1,319 @@
+use std::fmt;
+
+use geom::{Point, Rect, Unit};
+
+use crate::Key;
+
+/// The number of homing keys expected in a layout, i.e. one for each index finger on the F and J
+/// keys. Layouts with more homing keys than this produce a [`LayoutWarning::TooManyHomingKeys`]
+pub const MAX_HOMING_KEYS: usize = 2;
+
+/// A likely mistake in a layout found by [`Layout::validate`]. Layouts with warnings can still be
+/// drawn
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum LayoutWarning {
+    /// The layout has more homing keys than expected. This usually means a stray homing flag was
+    /// applied to many keys. The keys are still drawn as homing keys
+    TooManyHomingKeys {
+        /// The indices of the homing keys in the layout. The number of homing keys is the length
+        /// of this slice
+        key_indices: Box<[usize]>,
+    },
+}
+
+impl fmt::Display for LayoutWarning {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::TooManyHomingKeys { ref key_indices } => write!(
+                f,
+                "found {} homing keys, expected at most {MAX_HOMING_KEYS}. Homing keys are at \
+                indices {key_indices:?}",
+                key_indices.len()
+            ),
+        }
+    }
+}
+
+pub fn validate(keys: &[Key]) -> Vec<LayoutWarning> {
+    let key_indices: Box<[usize]> = keys
+        .iter()
+        .enumerate()
+        .filter(|&(_, key)| key.is_homing())
+        .map(|(i, _)| i)
+        .collect();
+
+    (key_indices.len() > MAX_HOMING_KEYS)
+        .then_some(LayoutWarning::TooManyHomingKeys { key_indices })
+        .into_iter()
+        .collect()
+}
+
+/// A keyboard layout, i.e. a list of keys
+///
+/// This is a thin wrapper around a [`Vec<Key>`] providing layout-level helpers. It implements
+/// [`AsRef<[Key]>`](AsRef) and [`AsMut<[Key]>`](AsMut) so it can be used anywhere a slice of keys
+/// is expected
+#[derive(Debug, Clone, Default)]
+pub struct Layout(Vec<Key>);
+
+impl Layout {
+    /// Create a new empty layout
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Returns the number of keys in the layout
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the layout contains no keys
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the keys in the layout
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, Key> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator that allows modifying each key in the layout
+    #[inline]
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Key> {
+        self.0.iter_mut()
+    }
+
+    /// Returns the bounding rectangle of all keys in the layout, based on each key's
+    /// [`Shape::outer_rect`](crate::Shape::outer_rect). An empty layout has an empty rectangle at
+    /// the origin
+    #[inline]
+    #[must_use]
+    pub fn bounds(&self) -> Rect<Unit> {
+        self.0
+            .iter()
+            .map(|key| key.shape.outer_rect().translate(key.position.to_vector()))
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_else(|| Rect::new(Point::origin(), Point::origin()))
+    }
+
+    /// Moves all keys so the top left corner of the layout's [`bounds`](Self::bounds) is at the
+    /// origin
+    #[inline]
+    pub fn normalize(&mut self) {
+        let offset = self.bounds().min.to_vector();
+        for key in &mut self.0 {
+            key.position -= offset;
+        }
+    }
+
+    /// Checks the layout for likely mistakes, returning a [`LayoutWarning`] for each one found
+    #[inline]
+    #[must_use]
+    pub fn validate(&self) -> Vec<LayoutWarning> {
+        validate(&self.0)
+    }
+
+    /// Consumes the layout, returning the underlying [`Vec<Key>`]
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Vec<Key> {
+        self.0
+    }
+}
+
+impl AsRef<[Key]> for Layout {
+    #[inline]
+    fn as_ref(&self) -> &[Key] {
+        &self.0
+    }
+}
+
+impl AsMut<[Key]> for Layout {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [Key] {
+        &mut self.0
+    }
+}
+
+impl From<Vec<Key>> for Layout {
+    #[inline]
+    fn from(keys: Vec<Key>) -> Self {
+        Self(keys)
+    }
+}
+
+impl From<Box<[Key]>> for Layout {
+    #[inline]
+    fn from(keys: Box<[Key]>) -> Self {
+        Self(keys.into_vec())
+    }
+}
+
+impl From<Layout> for Vec<Key> {
+    #[inline]
+    fn from(layout: Layout) -> Self {
+        layout.0
+    }
+}
+
+impl FromIterator<Key> for Layout {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = Key>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Layout {
+    type Item = Key;
+    type IntoIter = std::vec::IntoIter<Key>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Layout {
+    type Item = &'a Key;
+    type IntoIter = std::slice::Iter<'a, Key>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Layout {
+    type Item = &'a mut Key;
+    type IntoIter = std::slice::IterMut<'a, Key>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geom::Size;
+    use isclose::assert_is_close;
+
+    use assert_matches::assert_matches;
+
+    use crate::{Homing, Shape};
+
+    use super::*;
+
+    fn layout() -> Layout {
+        [
+            Key {
+                position: Point::new(1.0, 2.0),
+                ..Key::example()
+            },
+            Key {
+                position: Point::new(2.0, 2.5),
+                shape: Shape::Normal(Size::new(2.0, 1.0)),
+                ..Key::example()
+            },
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn layout_new() {
+        let layout = Layout::new();
+
+        assert!(layout.is_empty());
+        assert_eq!(layout.len(), 0);
+        assert_eq!(layout.iter().count(), 0);
+    }
+
+    #[test]
+    fn layout_from() {
+        let layout = Layout::from(vec![Key::example(); 3]);
+        assert_eq!(layout.len(), 3);
+
+        let layout = Layout::from(vec![Key::example(); 2].into_boxed_slice());
+        assert_eq!(layout.as_ref().len(), 2);
+
+        let keys: Vec<_> = layout.into();
+        assert_eq!(keys.len(), 2);
+    }
+
+    #[test]
+    fn layout_iter() {
+        let mut layout = layout();
+
+        assert_eq!(layout.iter().count(), 2);
+        assert_eq!((&layout).into_iter().count(), 2);
+
+        for key in &mut layout {
+            key.z_order = 1;
+        }
+        assert!(layout.iter().all(|key| key.z_order == 1));
+        assert_eq!(layout.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn layout_bounds() {
+        assert_is_close!(
+            layout().bounds(),
+            Rect::new(Point::new(1.0, 2.0), Point::new(4.0, 3.5))
+        );
+        assert_is_close!(Layout::new().bounds(), Rect::zero());
+    }
+
+    #[test]
+    fn layout_normalize() {
+        let mut layout = layout();
+        layout.normalize();
+
+        assert_is_close!(
+            layout.bounds(),
+            Rect::new(Point::origin(), Point::new(3.0, 1.5))
+        );
+        assert_is_close!(layout.iter().next().unwrap().position, Point::origin());
+    }
+
+    #[test]
+    fn layout_validate() {
+        let homing = Key {
+            shape: Shape::Homing(Some(Homing::Bar)),
+            ..Key::example()
+        };
+
+        assert!(layout().validate().is_empty());
+        assert!(Layout::from(vec![homing.clone(); MAX_HOMING_KEYS])
+            .validate()
+            .is_empty());
+
+        let layout = Layout::from(vec![homing.clone(), Key::example(), homing.clone(), homing]);
+        let warnings = layout.validate();
+        assert_eq!(warnings.len(), 1);
+        assert_matches!(
+            warnings[0],
+            LayoutWarning::TooManyHomingKeys { ref key_indices } if **key_indices == [0, 2, 3]
+        );
+    }
+
+    #[test]
+    fn layout_warning_fmt() {
+        let too_many_homing_keys = LayoutWarning::TooManyHomingKeys {
+            key_indices: [1, 4, 7].into(),
+        };
+        assert_eq!(
+            format!("{too_many_homing_keys}"),
+            "found 3 homing keys, expected at most 2. Homing keys are at indices [1, 4, 7]"
+        );
+    }
+}
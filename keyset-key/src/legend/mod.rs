@@ -1,20 +1,80 @@
+use std::borrow::Borrow;
 use std::ops::{Index, IndexMut};
 
 use color::Color;
+use geom::{Dot, Length, Path};
+use isclose::IsClose;
 
 pub use text::Text;
 
 mod text;
 
+/// Parameters for a coloured chip drawn behind a legend, for example to highlight an accent legend
+/// or a modifier indicator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChipParams {
+    /// The colour of the chip
+    pub color: Color,
+    /// The corner radius of the chip. This is limited to half the chip's height or width, so a
+    /// large radius gives a pill-shaped chip
+    pub radius: Length<Dot>,
+    /// The padding between the legend's bounds and the edge of the chip
+    pub padding: Length<Dot>,
+}
+
+impl IsClose<f32> for ChipParams {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.color.is_close_tol(other.color, rel_tol, abs_tol)
+            && self
+                .radius
+                .get()
+                .is_close_tol(other.radius.get(), rel_tol, abs_tol)
+            && self
+                .padding
+                .get()
+                .is_close_tol(other.padding.get(), rel_tol, abs_tol)
+    }
+}
+
 /// A single legend
+///
+/// Equality compares heights and paths exactly, use [`IsClose`] to compare legends with a
+/// tolerance. Rotations are compared modulo a full turn, so a legend with four quarter turns is
+/// equal to an unrotated one
 #[derive(Debug, Clone)]
 pub struct Legend {
     /// The legend text
     pub text: Text,
     /// The legend size
     pub size_idx: usize,
+    /// An explicit cap height for the legend. When set this takes precedence over the profile's
+    /// text height for [`Self::size_idx`], although the profile's text margin for the size index is
+    /// still used. Defaults to [`None`]
+    pub height: Option<Length<Dot>>,
     /// The legend colour
     pub color: Color,
+    /// The number of clockwise quarter turns the legend is rotated by. Negative values rotate the
+    /// legend anticlockwise
+    pub quarter_turns: i8,
+    /// A pre-rendered path for the legend, for example from text shaped by another library. When
+    /// set this takes precedence over [`Self::text`] and no font is used to draw the legend. The
+    /// path is rotated by [`Self::quarter_turns`] and aligned within the profile's text margin for
+    /// [`Self::size_idx`], but is otherwise drawn as is. Defaults to [`None`]
+    pub path: Option<Path<Dot>>,
+    /// An optional chip drawn behind the legend, sized to fit the legend's bounds. Defaults to
+    /// [`None`]
+    pub chip: Option<ChipParams>,
 }
 
 impl Legend {
@@ -25,13 +85,73 @@ impl Legend {
         Self {
             text: Text::parse_from(text),
             size_idx,
+            height: None,
             color,
+            quarter_turns: 0,
+            path: None,
+            chip: None,
         }
     }
 }
 
+impl PartialEq for Legend {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+            && self.size_idx == other.size_idx
+            && self.height == other.height
+            && self.color == other.color
+            && self.quarter_turns.rem_euclid(4) == other.quarter_turns.rem_euclid(4)
+            && self.path == other.path
+            && self.chip == other.chip
+    }
+}
+
+impl IsClose<f32> for Legend {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.text.lines().eq(other.text.lines())
+            && self.size_idx == other.size_idx
+            && match (self.height, other.height) {
+                (Some(height), Some(other)) => {
+                    height.get().is_close_tol(other.get(), rel_tol, abs_tol)
+                }
+                (None, None) => true,
+                _ => false,
+            }
+            && self.color.is_close_tol(other.color, rel_tol, abs_tol)
+            && self.quarter_turns.rem_euclid(4) == other.quarter_turns.rem_euclid(4)
+            && match (self.path.as_ref(), other.path.as_ref()) {
+                (Some(path), Some(other)) => {
+                    path.len() == other.len()
+                        && path
+                            .iter()
+                            .zip(other.iter())
+                            .all(|(s, o)| s.is_close_tol(o, rel_tol, abs_tol))
+                }
+                (None, None) => true,
+                _ => false,
+            }
+            && match (self.chip, other.chip) {
+                (Some(chip), Some(other)) => chip.is_close_tol(other, rel_tol, abs_tol),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
 /// A set of legends for a key
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Legends([Option<Legend>; 9]);
 
 impl Legends {
@@ -56,6 +176,122 @@ impl Legends {
     pub fn iter(&self) -> std::slice::Iter<'_, Option<Legend>> {
         self.0.iter()
     }
+
+    /// Create a compact textual representation of the legends' text, for example `"Q||||||||"`
+    /// for a key with only a top-left legend
+    ///
+    /// The 9 legend slots are separated by `|` in left-to-right, top-to-bottom order, with blank
+    /// slots left empty. Lines are joined with `<br>`, and any `|` or `\` in a legend is escaped
+    /// with a `\`. Only the legend text is included, not the size or colour
+    #[must_use]
+    pub fn to_compact_string(&self) -> String {
+        let slots: Vec<_> = self
+            .iter()
+            .map(|legend| {
+                legend.as_ref().map_or_else(String::new, |legend| {
+                    legend
+                        .text
+                        .lines()
+                        .collect::<Vec<_>>()
+                        .join("<br>")
+                        .replace('\\', "\\\\")
+                        .replace('|', "\\|")
+                })
+            })
+            .collect();
+        slots.join("|")
+    }
+
+    /// Parse legends from the compact representation produced by [`Legends::to_compact_string`]
+    ///
+    /// Legends use KLE's default size and colour. Empty or missing slots are left blank and any
+    /// slots after the ninth are ignored
+    #[must_use]
+    pub fn from_compact_string(string: &str) -> Self {
+        const DEFAULT_SIZE_IDX: usize = 3;
+        const DEFAULT_COLOR: Color = Color::new(0.0, 0.0, 0.0);
+
+        let mut slots = Vec::new();
+        let mut slot = String::new();
+        let mut chars = string.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '|' => slots.push(std::mem::take(&mut slot)),
+                '\\' => slot.extend(chars.next()),
+                char => slot.push(char),
+            }
+        }
+        slots.push(slot);
+
+        let mut legends = Self::default();
+        for (legend, slot) in legends.0.iter_mut().zip(slots) {
+            *legend =
+                (!slot.is_empty()).then(|| Legend::new(&slot, DEFAULT_SIZE_IDX, DEFAULT_COLOR));
+        }
+        legends
+    }
+
+    /// Remove legends which are identical to an earlier legend, keeping only the first occurrence
+    /// in left-to-right, top-to-bottom order. Legends are considered identical if their text,
+    /// size index, and colour all match exactly, regardless of any other properties
+    #[inline]
+    pub fn dedupe(&mut self) {
+        for i in 1..self.0.len() {
+            let (before, after) = self.0.split_at_mut(i);
+            if let Some(ref legend) = after[0] {
+                if before.iter().flatten().any(|other| {
+                    other.text == legend.text
+                        && other.size_idx == legend.size_idx
+                        && other.color == legend.color
+                }) {
+                    after[0] = None;
+                }
+            }
+        }
+    }
+
+    /// Transform the text of each legend using `f`, for example to change its case or translate it
+    ///
+    /// `f` is called for each line of each present legend in left-to-right, top-to-bottom order.
+    /// Legends' sizes and colours are left unchanged
+    #[inline]
+    pub fn map_text(&mut self, mut f: impl FnMut(&str) -> String) {
+        for legend in self.0.iter_mut().flatten() {
+            legend.text.map_lines(&mut f);
+        }
+    }
+
+    /// Returns `true` if the only legend is in the centre slot
+    #[inline]
+    #[must_use]
+    pub fn is_single_center(&self) -> bool {
+        self.iter()
+            .enumerate()
+            .all(|(i, legend)| legend.is_some() == (i == 4))
+    }
+}
+
+impl IsClose<f32> for Legends {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.iter()
+            .zip(other.iter())
+            .all(|(s, o)| match (s.as_ref(), o.as_ref()) {
+                (Some(s), Some(o)) => s.is_close_tol(o, rel_tol, abs_tol),
+                (None, None) => true,
+                _ => false,
+            })
+    }
 }
 
 impl From<[Option<Legend>; 9]> for Legends {
@@ -138,6 +374,7 @@ impl IndexMut<(usize, usize)> for Legends {
 
 #[cfg(test)]
 pub mod tests {
+    use geom::{Point, Rect, ToPath};
     use isclose::assert_is_close;
 
     use super::*;
@@ -151,6 +388,98 @@ pub mod tests {
         assert_is_close!(legend.color, Color::new(0.0, 0.2, 0.4));
     }
 
+    #[test]
+    fn legend_is_close() {
+        let legend = Legend::new("test", 4, Color::new(0.0, 0.2, 0.4));
+
+        assert!(legend.is_close(Legend::new("test", 4, Color::new(0.0, 0.2, 0.4 + 1e-7))));
+        assert!(!legend.is_close(Legend::new("test2", 4, Color::new(0.0, 0.2, 0.4))));
+        assert!(!legend.is_close(Legend::new("test", 5, Color::new(0.0, 0.2, 0.4))));
+        assert!(!legend.is_close(Legend::new("test", 4, Color::new(0.0, 0.2, 0.5))));
+        assert!(!legend.is_close(Legend {
+            height: Some(Length::new(100.0)),
+            ..legend.clone()
+        }));
+
+        let chip = ChipParams {
+            color: Color::new(1.0, 0.0, 0.0),
+            radius: Length::new(50.0),
+            padding: Length::new(20.0),
+        };
+        let with_chip = Legend {
+            chip: Some(chip),
+            ..legend.clone()
+        };
+        assert!(!legend.is_close(&with_chip));
+        assert!(with_chip.is_close(Legend {
+            chip: Some(ChipParams {
+                padding: Length::new(20.0 + 1e-6),
+                ..chip
+            }),
+            ..legend.clone()
+        }));
+        assert!(!with_chip.is_close(Legend {
+            chip: Some(ChipParams {
+                radius: Length::new(10.0),
+                ..chip
+            }),
+            ..legend.clone()
+        }));
+
+        let path = Rect::new(Point::new(0.0, 0.0), Point::new(100.0, 100.0)).to_path();
+        let with_path = Legend {
+            path: Some(path.clone()),
+            ..legend.clone()
+        };
+        assert!(!legend.is_close(&with_path));
+        assert!(with_path.is_close(Legend {
+            path: Some(path),
+            ..legend
+        }));
+    }
+
+    #[test]
+    fn legend_partial_eq() {
+        let legend = Legend::new("test", 4, Color::new(0.0, 0.2, 0.4));
+
+        assert_eq!(legend, Legend::new("test", 4, Color::new(0.0, 0.2, 0.4)));
+        assert_ne!(legend, Legend::new("test2", 4, Color::new(0.0, 0.2, 0.4)));
+        assert_ne!(
+            legend,
+            Legend {
+                quarter_turns: 1,
+                ..legend.clone()
+            }
+        );
+        assert_eq!(
+            Legend {
+                quarter_turns: 3,
+                ..legend.clone()
+            },
+            Legend {
+                quarter_turns: -1,
+                ..legend
+            }
+        );
+
+        assert_eq!(Legends::example(), Legends::example());
+        assert_ne!(Legends::example(), Legends::default());
+    }
+
+    #[test]
+    fn legends_is_close() {
+        let legends = Legends::example();
+        assert!(legends.is_close(Legends::example()));
+
+        let mut other = Legends::example();
+        other[1] = Some(Legend::new("A", 4, Color::new(0.0, 0.0, 0.0)));
+        assert!(!legends.is_close(&other));
+
+        let mut other = Legends::example();
+        other[0] = None;
+        assert!(!legends.is_close(&other));
+    }
+
     #[test]
     fn legends_example() {
         let legends = Legends::example();
@@ -172,6 +501,75 @@ pub mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn legends_compact_string() {
+        let legends = Legends::example();
+        assert_eq!(legends.to_compact_string(), "!||¹||||1||¡");
+
+        let mut legends = Legends::default();
+        legends[0] = Some(Legend::new("Q", 4, Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(legends.to_compact_string(), "Q||||||||");
+
+        legends[4] = Some(Legend::new("Two<br>lines", 4, Color::new(0.0, 0.0, 0.0)));
+        legends[8] = Some(Legend::new("|\\", 4, Color::new(0.0, 0.0, 0.0)));
+        let string = legends.to_compact_string();
+        assert_eq!(string, "Q||||Two<br>lines||||\\|\\\\");
+
+        let parsed = Legends::from_compact_string(&string);
+        assert_eq!(parsed.to_compact_string(), string);
+        assert_eq!(parsed[0].as_ref().unwrap().text.to_string(), "Q");
+        assert_eq!(
+            parsed[4].as_ref().unwrap().text.lines().collect::<Vec<_>>(),
+            ["Two", "lines"]
+        );
+        assert_eq!(parsed[8].as_ref().unwrap().text.to_string(), "|\\");
+        assert!(parsed[1].is_none());
+
+        let parsed = Legends::from_compact_string("A|B");
+        assert_eq!(parsed.to_compact_string(), "A|B|||||||");
+
+        let parsed = Legends::from_compact_string("");
+        assert!(parsed.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn legends_dedupe() {
+        let mut legends = Legends::from_compact_string("A|B|A||B|A<br>B|||");
+        legends[2] = Some(Legend::new("A", 4, Color::new(0.0, 0.0, 0.0)));
+        legends.dedupe();
+        assert_eq!(legends.to_compact_string(), "A|B|A|||A<br>B|||");
+
+        // Other fields such as the rotation are not compared
+        let mut legends = Legends::from_compact_string("A|A|||||||");
+        legends[1].as_mut().unwrap().quarter_turns = 1;
+        legends.dedupe();
+        assert_eq!(legends.to_compact_string(), "A||||||||");
+
+        let mut legends = Legends::example();
+        legends.dedupe();
+        assert!(legends.is_close(Legends::example()));
+    }
+
+    #[test]
+    fn legends_map_text() {
+        let mut legends = Legends::from_compact_string("a|b<br>c|||||||");
+        let mut count = 0;
+        legends.map_text(|text| {
+            count += 1;
+            text.to_uppercase()
+        });
+        assert_eq!(legends.to_compact_string(), "A|B<br>C|||||||");
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn legends_is_single_center() {
+        assert!(Legends::from_compact_string("||||A||||").is_single_center());
+        assert!(!Legends::from_compact_string("A|||||||").is_single_center());
+        assert!(!Legends::from_compact_string("||||A||||B").is_single_center());
+        assert!(!Legends::default().is_single_center());
+    }
+
     #[test]
     fn legends_from() {
         let legends: Legends = [
@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 /// Struct representing a single legend's text. This can be made up of one or
 /// more lines
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Text(Box<[String]>);
 
 impl Display for Text {
@@ -43,6 +43,13 @@ impl Text {
     pub fn lines(&self) -> impl Iterator<Item = &str> {
         self.0.iter().map(String::as_str)
     }
+
+    // Replace each line of the text with the result of `f`
+    pub(super) fn map_lines(&mut self, mut f: impl FnMut(&str) -> String) {
+        for line in &mut self.0 {
+            *line = f(line);
+        }
+    }
 }
 
 #[cfg(test)]
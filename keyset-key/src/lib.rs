@@ -3,17 +3,29 @@
 //!
 //! [keyset]: https://crates.io/crates/keyset
 
+mod grid;
+mod keycode;
+mod layout;
 mod legend;
+mod locale;
 
 #[cfg(feature = "kle")]
 pub mod kle;
 
-use std::fmt;
+use std::borrow::Borrow;
+use std::f32::consts::FRAC_PI_2;
+use std::fmt::{self, Write as _};
 
-pub use legend::{Legend, Legends, Text};
+pub use grid::{grid_from_dimensions, ortho, staggered_ansi_60};
+pub use keycode::{apply_keycodes, KeycodeLabelMap};
+pub use layout::{Layout, LayoutWarning, MAX_HOMING_KEYS};
+pub use legend::{ChipParams, Legend, Legends, Text};
+pub use locale::{apply_locale, LocaleMap};
 
 use color::Color;
-use geom::{Point, Rect, Size, Unit};
+use geom::{Angle, ExtPoint, Length, Mm, Point, Rect, Size, Unit};
+use isclose::IsClose;
+use saturate::SaturatingFrom;
 
 /// The type of homing used on a homing key
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,7 +39,9 @@ pub enum Homing {
 }
 
 /// The shape of a key
-#[derive(Debug, Clone, Copy)]
+///
+/// Equality compares sizes exactly, use [`IsClose`] to compare shapes with a tolerance
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Shape {
     /// Not a *key* per se, but only a legend. This is usually used for labels and is the same as a
     /// decal in KLE
@@ -86,13 +100,70 @@ impl Shape {
             Self::IsoHorizontal => Rect::from_origin_and_size(Point::origin(), Size::new(1.5, 1.0)),
         }
     }
+
+    /// Whether the shape is a homing key of any homing type
+    #[inline]
+    #[must_use]
+    pub const fn is_homing(self) -> bool {
+        matches!(self, Self::Homing(..))
+    }
+
+    /// Whether the shape is a spacebar
+    #[inline]
+    #[must_use]
+    pub const fn is_space(self) -> bool {
+        matches!(self, Self::Space(..))
+    }
+
+    /// Whether the shape is a decal, i.e. [`Shape::None`]
+    #[inline]
+    #[must_use]
+    pub const fn is_decal(self) -> bool {
+        matches!(self, Self::None(..))
+    }
+
+    /// Whether the shape is an ISO enter with either legend alignment
+    #[inline]
+    #[must_use]
+    pub const fn is_iso_enter(self) -> bool {
+        matches!(self, Self::IsoVertical | Self::IsoHorizontal)
+    }
 }
 
-#[derive(Clone, Copy)]
+impl IsClose<f32> for Shape {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        match (*self, *other) {
+            (Self::None(ref s), Self::None(ref o))
+            | (Self::Normal(ref s), Self::Normal(ref o))
+            | (Self::Space(ref s), Self::Space(ref o)) => s.is_close_tol(o, rel_tol, abs_tol),
+            (Self::Homing(s), Self::Homing(o)) => s == o,
+            (Self::SteppedCaps, Self::SteppedCaps)
+            | (Self::IsoVertical, Self::IsoVertical)
+            | (Self::IsoHorizontal, Self::IsoHorizontal) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 struct NonExhaustive;
 
 /// A key
-#[derive(Clone)]
+///
+/// Equality compares positions, sizes, and colours exactly, use [`IsClose`] to compare keys with
+/// a tolerance
+#[derive(Clone, PartialEq)]
 pub struct Key {
     /// The position of the key
     pub position: Point<Unit>,
@@ -100,8 +171,18 @@ pub struct Key {
     pub shape: Shape,
     /// The key's colour
     pub color: Color,
+    /// The colour of the key's top surface, for example for a doubleshot keycap with a different
+    /// colour top. If this is [`None`] the key's [`color`](Self::color) is used
+    pub top_color: Option<Color>,
     /// The key's legends
     pub legends: Legends,
+    /// The key's row in a sculpted profile, where 1 is R1. If this is [`None`] the profile's
+    /// default top surface is used
+    pub row: Option<u8>,
+    /// The order in which overlapping keys are drawn. Keys with a higher z-order are drawn on top
+    /// of keys with a lower z-order, while keys with equal z-order are drawn in their original
+    /// order
+    pub z_order: i32,
     /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
     /// `..Default::default()` functional update syntax
     #[allow(private_interfaces)]
@@ -115,7 +196,10 @@ impl fmt::Debug for Key {
         dbg.field("position", &self.position)
             .field("shape", &self.shape)
             .field("color", &self.color)
-            .field("legends", &self.legends);
+            .field("top_color", &self.top_color)
+            .field("legends", &self.legends)
+            .field("row", &self.row)
+            .field("z_order", &self.z_order);
 
         #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
         dbg.field("__non_exhaustive", &"NonExhaustive");
@@ -141,6 +225,34 @@ impl Key {
             ..Self::default()
         }
     }
+
+    /// Whether the key is a homing key. See [`Shape::is_homing`]
+    #[inline]
+    #[must_use]
+    pub const fn is_homing(&self) -> bool {
+        self.shape.is_homing()
+    }
+
+    /// Whether the key is a spacebar. See [`Shape::is_space`]
+    #[inline]
+    #[must_use]
+    pub const fn is_space(&self) -> bool {
+        self.shape.is_space()
+    }
+
+    /// Whether the key is a decal. See [`Shape::is_decal`]
+    #[inline]
+    #[must_use]
+    pub const fn is_decal(&self) -> bool {
+        self.shape.is_decal()
+    }
+
+    /// Whether the key is an ISO enter. See [`Shape::is_iso_enter`]
+    #[inline]
+    #[must_use]
+    pub const fn is_iso_enter(&self) -> bool {
+        self.shape.is_iso_enter()
+    }
 }
 
 impl Default for Key {
@@ -150,15 +262,229 @@ impl Default for Key {
             position: Point::origin(),
             shape: Shape::Normal(Size::new(1.0, 1.0)),
             color: Color::new(0.8, 0.8, 0.8),
+            top_color: None,
             legends: Legends::default(),
+            row: None,
+            z_order: 0,
             __non_exhaustive: NonExhaustive,
         }
     }
 }
 
+impl IsClose<f32> for Key {
+    const ABS_TOL: f32 = <f32 as IsClose>::ABS_TOL;
+    const REL_TOL: f32 = <f32 as IsClose>::REL_TOL;
+
+    #[inline]
+    fn is_close_tol(
+        &self,
+        other: impl Borrow<Self>,
+        rel_tol: impl Borrow<f32>,
+        abs_tol: impl Borrow<f32>,
+    ) -> bool {
+        let (other, rel_tol, abs_tol): (&Self, &f32, &f32) =
+            (other.borrow(), rel_tol.borrow(), abs_tol.borrow());
+        self.position.is_close_tol(other.position, rel_tol, abs_tol)
+            && self.shape.is_close_tol(other.shape, rel_tol, abs_tol)
+            && self.color.is_close_tol(other.color, rel_tol, abs_tol)
+            && match (self.top_color, other.top_color) {
+                (Some(color), Some(other)) => color.is_close_tol(other, rel_tol, abs_tol),
+                (None, None) => true,
+                _ => false,
+            }
+            && self.legends.is_close_tol(&other.legends, rel_tol, abs_tol)
+            && self.row == other.row
+            && self.z_order == other.z_order
+    }
+}
+
+/// The default grid used when snapping key positions, equal to 0.25u
+pub const DEFAULT_SNAP_GRID: Length<Unit> = Length::new(0.25);
+
+/// Snaps the position of each key to the nearest multiple of `grid`, typically
+/// [`DEFAULT_SNAP_GRID`]
+///
+/// This is useful for cleaning up small floating point errors in layouts that were generated
+/// programmatically, for example after mirroring or rotating keys. Positions exactly halfway
+/// between two grid increments are rounded to the even multiple (round-half-to-even)
+///
+/// Positions are left unchanged if `grid` is not positive and finite
+#[inline]
+pub fn snap_positions(keys: &mut [Key], grid: Length<Unit>) {
+    for key in keys {
+        key.position = key.position.snap(grid);
+    }
+}
+
+/// Recolours keys using the given rule
+///
+/// The rule is called for each key, and the key's colour is set to the returned colour. Keys for
+/// which the rule returns [`None`] are left unchanged. This can be used to colour keys by function
+/// group, e.g. modifiers and alphas, or to create heatmaps
+#[inline]
+pub fn recolor(keys: &mut [Key], rule: impl Fn(&Key) -> Option<Color>) {
+    for key in keys {
+        if let Some(color) = rule(key) {
+            key.color = color;
+        }
+    }
+}
+
+/// Transforms the text of every legend on every key using `f`
+///
+/// See [`Legends::map_text`] for details
+#[inline]
+pub fn map_legends(keys: &mut [Key], mut f: impl FnMut(&str) -> String) {
+    for key in keys {
+        key.legends.map_text(&mut f);
+    }
+}
+
+/// Nudges the position of each key by a pseudo-random offset of up to `max_offset` in each
+/// direction, and rotates it by a pseudo-random angle of up to `max_rotation` in either direction
+///
+/// The offsets and angles are generated from `seed` so the same seed and layout always give the
+/// same result, which is useful for stylised presentation renders.
+///
+/// Arbitrary rotation requires rotation support on [`Key`], which isn't currently available. Until
+/// then each key's angle is rounded to the nearest quarter turn and applied to its legends using
+/// [`Legend::quarter_turns`], so angles of less than 45° leave the legends unrotated
+#[inline]
+pub fn scatter(keys: &mut [Key], seed: u64, max_offset: Length<Unit>, max_rotation: Angle) {
+    // SplitMix64, see <https://prng.di.unimi.it/splitmix64.c>
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // Scale the top 16 bits to [-1, 1]
+        (f32::from(u16::saturating_from(z >> 48)) / f32::from(u16::MAX)).mul_add(2.0, -1.0)
+    };
+
+    for key in keys {
+        let offset = Size::new(next(), next()) * max_offset.get();
+        key.position += offset;
+
+        let angle = (max_rotation * next()).signed();
+        let quarter_turns = i8::saturating_from((angle.radians / FRAC_PI_2).round());
+        for i in 0..9 {
+            if let Some(ref mut legend) = key.legends[i] {
+                // Quarter turns are taken modulo 4, so wrapping doesn't change the rotation
+                legend.quarter_turns = legend.quarter_turns.wrapping_add(quarter_turns);
+            }
+        }
+    }
+}
+
+/// The maximum number of rows or columns in a grid returned by [`to_grid`]
+pub const MAX_GRID_SIZE: usize = 1024;
+
+/// Expands a layout into a dense grid of cells of size `cell`, indexed as `grid[row][column]`
+///
+/// The grid starts at the origin and extends to cover all keys. Each key is assigned to every cell
+/// whose centre lies within its [`Shape::outer_rect`], so fractionally positioned or sized keys are
+/// assigned to the cells they mostly cover. A key too small to cover any cell centre is assigned to
+/// the cell containing its own centre. Where keys overlap a cell is assigned to the key which
+/// comes first in `keys`. Cells with no key are [`None`], as are any parts of keys at negative
+/// positions
+///
+/// The grid is limited to [`MAX_GRID_SIZE`] rows and columns, and any parts of keys beyond this are
+/// not included. An empty grid is returned if `cell` is not positive and finite
+#[must_use]
+pub fn to_grid(keys: &[Key], cell: Length<Unit>) -> Vec<Vec<Option<&Key>>> {
+    // Small tolerance so floating point errors don't add an extra row or column
+    const TOLERANCE: f32 = 1e-3;
+
+    let cell = cell.get();
+    if !(cell.is_finite() && cell > 0.0) {
+        return Vec::new();
+    }
+
+    let rects: Vec<_> = keys
+        .iter()
+        .map(|key| key.shape.outer_rect().translate(key.position.to_vector()) / cell)
+        .collect();
+
+    let max = rects
+        .iter()
+        .fold(Point::origin(), |max: Point<Unit>, rect| max.max(rect.max));
+    let cols = usize::saturating_from((max.x - TOLERANCE).ceil()).min(MAX_GRID_SIZE);
+    let rows = usize::saturating_from((max.y - TOLERANCE).ceil()).min(MAX_GRID_SIZE);
+
+    let mut grid = vec![vec![None; cols]; rows];
+    for (key, rect) in keys.iter().zip(rects) {
+        let (min, max) = (rect.min.floor(), rect.max.ceil());
+        let (col_start, col_end) = (usize::saturating_from(min.x), usize::saturating_from(max.x));
+        let (row_start, row_end) = (usize::saturating_from(min.y), usize::saturating_from(max.y));
+        let mut assigned = false;
+
+        for (row, cells) in grid.iter_mut().enumerate().take(row_end).skip(row_start) {
+            for (col, cell) in cells.iter_mut().enumerate().take(col_end).skip(col_start) {
+                let center = Point::new(f32::saturating_from(col), f32::saturating_from(row))
+                    + Size::splat(0.5);
+                if rect.contains(center) {
+                    assigned = true;
+                    cell.get_or_insert(key);
+                }
+            }
+        }
+
+        if !assigned {
+            let center = rect.center().floor();
+            let (row, col) = (
+                usize::saturating_from(center.y),
+                usize::saturating_from(center.x),
+            );
+            if let Some(cell) = grid.get_mut(row).and_then(|r| r.get_mut(col)) {
+                cell.get_or_insert(key);
+            }
+        }
+    }
+
+    grid
+}
+
+const OPENSCAD_HEADER: &str = "// [x, y, width, height, rotation] for each key, in mm and degrees";
+
+/// Exports the key positions as an [OpenSCAD] array named `keys`, for example for placing switch
+/// cutouts in a 3D-printed case
+///
+/// Each key is written as `[x, y, width, height, rotation]`. All lengths are in millimetres using
+/// the given key pitch, typically the profile's `unit_pitch`. `x` and `y` are the position of the
+/// key's top left corner, and `width` and `height` are the size of the key's outer bounding
+/// rectangle. The y-axis points up in [OpenSCAD], so `y` is negated. `rotation` is in degrees,
+/// but keys don't currently support rotation so it is always zero
+///
+/// [OpenSCAD]: https://openscad.org/
+#[must_use]
+pub fn to_openscad(keys: &[Key], pitch: Length<Mm>) -> String {
+    // Round to 3 d.p. to avoid floating point noise. Adding zero turns -0 into 0
+    let round = |value: f32| (value * 1e3).round() / 1e3 + 0.0;
+
+    let rows = keys.iter().fold(String::new(), |mut rows, key| {
+        let size = key.shape.outer_rect().size() * pitch.get();
+        let (x, y) = (key.position.x * pitch.get(), -key.position.y * pitch.get());
+        // Writing to a String can't fail
+        let _ = writeln!(
+            rows,
+            "    [{}, {}, {}, {}, 0],",
+            round(x),
+            round(y),
+            round(size.width),
+            round(size.height)
+        );
+        rows
+    });
+
+    format!("{OPENSCAD_HEADER}\nkeys = [\n{rows}];\n")
+}
+
 #[cfg(test)]
-pub mod tests {
+mod tests {
     use assert_matches::assert_matches;
+    use indoc::indoc;
 
     use super::*;
 
@@ -226,6 +552,102 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn shape_predicates() {
+        let size = Size::new(2.25, 1.0);
+        // (shape, is_homing, is_space, is_decal, is_iso_enter)
+        let shapes = [
+            (Shape::None(size), false, false, true, false),
+            (Shape::Normal(size), false, false, false, false),
+            (Shape::Space(size), false, true, false, false),
+            (Shape::Homing(None), true, false, false, false),
+            (Shape::Homing(Some(Homing::Bump)), true, false, false, false),
+            (Shape::SteppedCaps, false, false, false, false),
+            (Shape::IsoVertical, false, false, false, true),
+            (Shape::IsoHorizontal, false, false, false, true),
+        ];
+
+        for (shape, is_homing, is_space, is_decal, is_iso_enter) in shapes {
+            assert_eq!(shape.is_homing(), is_homing, "{shape:?}");
+            assert_eq!(shape.is_space(), is_space, "{shape:?}");
+            assert_eq!(shape.is_decal(), is_decal, "{shape:?}");
+            assert_eq!(shape.is_iso_enter(), is_iso_enter, "{shape:?}");
+
+            let key = Key {
+                shape,
+                ..Key::new()
+            };
+            assert_eq!(key.is_homing(), is_homing, "{shape:?}");
+            assert_eq!(key.is_space(), is_space, "{shape:?}");
+            assert_eq!(key.is_decal(), is_decal, "{shape:?}");
+            assert_eq!(key.is_iso_enter(), is_iso_enter, "{shape:?}");
+        }
+    }
+
+    #[test]
+    fn shape_is_close() {
+        let size = Size::new(2.25, 1.0);
+
+        assert!(Shape::Normal(size).is_close(Shape::Normal(size + Size::splat(1e-7))));
+        assert!(!Shape::Normal(size).is_close(Shape::Normal(Size::splat(1.0))));
+        assert!(!Shape::Normal(size).is_close(Shape::Space(size)));
+        assert!(Shape::Homing(Some(Homing::Bar)).is_close(Shape::Homing(Some(Homing::Bar))));
+        assert!(!Shape::Homing(Some(Homing::Bar)).is_close(Shape::Homing(None)));
+        assert!(Shape::IsoVertical.is_close(Shape::IsoVertical));
+        assert!(!Shape::IsoVertical.is_close(Shape::IsoHorizontal));
+    }
+
+    #[test]
+    fn key_is_close() {
+        let key = Key::example();
+
+        assert!(key.is_close(Key {
+            position: Point::new(1e-7, 0.0),
+            ..Key::example()
+        }));
+        assert!(!key.is_close(Key::new()));
+        assert!(!key.is_close(Key {
+            color: Color::new(0.1, 0.2, 0.3),
+            ..Key::example()
+        }));
+        assert!(!key.is_close(Key {
+            top_color: Some(Color::new(0.1, 0.2, 0.3)),
+            ..Key::example()
+        }));
+        assert!(!key.is_close(Key {
+            row: Some(1),
+            ..Key::example()
+        }));
+    }
+
+    #[test]
+    fn key_partial_eq() {
+        assert_eq!(Key::example(), Key::example());
+        assert_eq!(
+            Shape::Space(Size::new(6.25, 1.0)),
+            Shape::Space(Size::new(6.25, 1.0))
+        );
+        assert_ne!(
+            Shape::Space(Size::new(6.25, 1.0)),
+            Shape::Normal(Size::new(6.25, 1.0))
+        );
+        assert_ne!(Key::example(), Key::new());
+        assert_ne!(
+            Key::example(),
+            Key {
+                position: Point::new(1e-3, 0.0),
+                ..Key::example()
+            }
+        );
+        assert_ne!(
+            Key::example(),
+            Key {
+                z_order: 1,
+                ..Key::example()
+            }
+        );
+    }
+
     #[test]
     fn key_debug() {
         let key = Key::new();
@@ -233,11 +655,15 @@ pub mod tests {
         assert_eq!(
             format!("{key:?}"),
             format!(
-                "Key {{ position: {:?}, shape: {:?}, color: {:?}, legends: {:?} }}",
+                "Key {{ position: {:?}, shape: {:?}, color: {:?}, top_color: {:?}, legends: {:?}, \
+                    row: {:?}, z_order: {:?} }}",
                 Point::<Unit>::origin(),
                 Shape::Normal(Size::splat(1.0)),
                 Color::new(0.8, 0.8, 0.8),
+                None::<Color>,
                 Legends::default(),
+                None::<u8>,
+                0,
             )
         );
     }
@@ -252,6 +678,8 @@ pub mod tests {
         for legend in key.legends {
             assert!(legend.is_none());
         }
+        assert_eq!(key.row, None);
+        assert_eq!(key.z_order, 0);
     }
 
     #[test]
@@ -266,4 +694,231 @@ pub mod tests {
             assert_eq!(legend.is_some(), is_some);
         }
     }
+
+    #[test]
+    fn key_snap_positions() {
+        let mut keys = [Key::new(), Key::new()];
+        keys[0].position = Point::new(0.9999, 1.0001);
+        keys[1].position = Point::new(2.374, 0.125);
+
+        snap_positions(&mut keys, DEFAULT_SNAP_GRID);
+
+        assert_eq!(keys[0].position, Point::new(1.0, 1.0));
+        assert_eq!(keys[1].position, Point::new(2.25, 0.0));
+
+        keys[1].position = Point::new(2.374, 0.125);
+        for grid in [0.0, -0.25, f32::NAN] {
+            snap_positions(&mut keys, Length::new(grid));
+
+            assert_eq!(keys[0].position, Point::new(1.0, 1.0));
+            assert_eq!(keys[1].position, Point::new(2.374, 0.125));
+        }
+    }
+
+    #[test]
+    fn key_recolor() {
+        let mut keys = [Key::new(), Key::example(), Key::new()];
+        let red = Color::new(1.0, 0.0, 0.0);
+
+        recolor(&mut keys, |key| key.legends[0].is_some().then_some(red));
+
+        assert_eq!(keys[0].color, Color::new(0.8, 0.8, 0.8));
+        assert_eq!(keys[1].color, red);
+        assert_eq!(keys[2].color, Color::new(0.8, 0.8, 0.8));
+    }
+
+    #[test]
+    fn key_map_legends() {
+        let mut keys = [Key::new(), Key::example()];
+        keys[0].legends = Legends::from_compact_string("a||b<br>c||||||");
+
+        map_legends(&mut keys, str::to_uppercase);
+
+        assert_eq!(keys[0].legends.to_compact_string(), "A||B<br>C||||||");
+        assert_eq!(keys[1].legends.to_compact_string(), "!||¹||||1||¡");
+    }
+
+    #[test]
+    fn key_scatter() {
+        let positions = [
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(2.0, 1.0),
+        ];
+        let mut keys = positions.map(|position| Key {
+            position,
+            ..Key::new()
+        });
+        let max_offset = Length::new(0.1);
+
+        scatter(&mut keys, 42, max_offset, Angle::zero());
+        for (key, position) in keys.iter().zip(positions) {
+            let offset = key.position - position;
+            assert!(offset.x.abs() <= max_offset.get());
+            assert!(offset.y.abs() <= max_offset.get());
+        }
+        assert!(keys
+            .iter()
+            .zip(positions)
+            .any(|(key, position)| !key.position.is_close(position)));
+
+        let mut same = positions.map(|position| Key {
+            position,
+            ..Key::new()
+        });
+        scatter(&mut same, 42, max_offset, Angle::zero());
+        for (key, other) in keys.iter().zip(&same) {
+            assert_eq!(key.position, other.position);
+        }
+
+        let mut different = positions.map(|position| Key {
+            position,
+            ..Key::new()
+        });
+        scatter(&mut different, 43, max_offset, Angle::zero());
+        assert!(keys
+            .iter()
+            .zip(&different)
+            .any(|(key, other)| key.position != other.position));
+
+        // Legends are unrotated with no rotation, and rotated by whole quarter turns otherwise
+        let example = Key::example();
+        assert!(keys
+            .iter()
+            .flat_map(|key| key.legends.iter().flatten())
+            .all(|legend| legend.quarter_turns == 0));
+
+        let mut rotated = [(); 8].map(|()| Key::example());
+        scatter(&mut rotated, 42, Length::new(0.0), Angle::degrees(180.0));
+        for key in &rotated {
+            assert!(key.position.is_close(example.position));
+            let turns: Vec<_> = key
+                .legends
+                .iter()
+                .zip(&example.legends)
+                .filter_map(|(legend, orig)| {
+                    Some(legend.as_ref()?.quarter_turns - orig.as_ref()?.quarter_turns)
+                })
+                .collect();
+            assert!(turns
+                .iter()
+                .all(|&turn| turn == turns[0] && (-2..=2).contains(&turn)));
+        }
+        assert!(rotated
+            .iter()
+            .any(|key| !key.legends.is_close(&example.legends)));
+
+        let mut same = [(); 8].map(|()| Key::example());
+        scatter(&mut same, 42, Length::new(0.0), Angle::degrees(180.0));
+        assert!(rotated
+            .iter()
+            .zip(&same)
+            .all(|(key, other)| key.legends.is_close(&other.legends)));
+
+        let mut small = [(); 8].map(|()| Key::example());
+        scatter(&mut small, 42, Length::new(0.0), Angle::degrees(40.0));
+        assert!(small
+            .iter()
+            .all(|key| key.legends.is_close(&example.legends)));
+    }
+
+    #[test]
+    fn key_to_openscad() {
+        let keys = [
+            Key::new(),
+            Key {
+                position: Point::new(1.0, 0.5),
+                shape: Shape::Space(Size::new(6.25, 1.0)),
+                ..Key::new()
+            },
+            Key {
+                position: Point::new(2.0, 1.0),
+                shape: Shape::IsoVertical,
+                ..Key::new()
+            },
+        ];
+
+        assert_eq!(
+            to_openscad(&keys, Length::new(19.05)),
+            indoc! {"
+                // [x, y, width, height, rotation] for each key, in mm and degrees
+                keys = [
+                    [0, 0, 19.05, 19.05, 0],
+                    [19.05, -9.525, 119.062, 19.05, 0],
+                    [38.1, -19.05, 28.575, 38.1, 0],
+                ];
+            "}
+        );
+        assert_eq!(
+            to_openscad(&keys[1..2], Length::new(18.0)),
+            format!("{OPENSCAD_HEADER}\nkeys = [\n    [18, -9, 112.5, 18, 0],\n];\n")
+        );
+        assert_eq!(
+            to_openscad(&[], Length::new(19.05)),
+            format!("{OPENSCAD_HEADER}\nkeys = [\n];\n")
+        );
+    }
+
+    #[test]
+    fn key_to_grid() {
+        let keys = [
+            Key::new(),
+            Key {
+                position: Point::new(1.0, 0.0),
+                shape: Shape::Normal(Size::new(2.0, 1.0)),
+                ..Key::new()
+            },
+            Key {
+                position: Point::new(0.5, 1.0),
+                ..Key::new()
+            },
+            Key {
+                position: Point::new(2.0, 1.0),
+                shape: Shape::Normal(Size::new(0.5, 1.0)),
+                ..Key::new()
+            },
+            Key::new(), // Overlaps keys[0]
+        ];
+
+        let grid = to_grid(&keys, Length::new(1.0));
+        let indices: Vec<Vec<_>> = grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.and_then(|c| keys.iter().position(|k| std::ptr::eq(k, c))))
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            indices,
+            vec![
+                vec![Some(0), Some(1), Some(1)],
+                vec![Some(2), None, Some(3)]
+            ]
+        );
+
+        let grid = to_grid(&keys, Length::new(0.5));
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid[0].len(), 6);
+        assert!(grid[2][0].is_none());
+        assert!(grid[2][1].is_some());
+
+        assert!(to_grid(&[], Length::new(1.0)).is_empty());
+    }
+
+    #[test]
+    fn key_to_grid_degenerate() {
+        let keys = [Key::new()];
+
+        assert!(to_grid(&keys, Length::new(0.0)).is_empty());
+        assert!(to_grid(&keys, Length::new(-1.0)).is_empty());
+        assert!(to_grid(&keys, Length::new(f32::NAN)).is_empty());
+        assert!(to_grid(&keys, Length::new(f32::INFINITY)).is_empty());
+
+        let grid = to_grid(&keys, Length::new(f32::MIN_POSITIVE));
+        assert_eq!(grid.len(), MAX_GRID_SIZE);
+        assert!(grid.iter().all(|row| row.len() == MAX_GRID_SIZE));
+        assert!(grid.iter().flatten().all(Option::is_some));
+    }
 }
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::{Key, Legend, Text};
+
+// Substitutions for a UK ISO layout, keyed by US ANSI legends
+const UK_LEGENDS: [(&str, &str); 5] = [
+    ("@\n2", "\"\n2"),
+    ("#\n3", "£\n3"),
+    ("\"\n'", "@\n'"),
+    ("~\n`", "¬\n`"),
+    ("|\n\\", "~\n#"),
+];
+
+// Substitutions for a German ISO (QWERTZ) layout, keyed by US ANSI legends
+const DE_LEGENDS: [(&str, &str); 20] = [
+    ("@\n2", "\"\n2"),
+    ("#\n3", "§\n3"),
+    ("^\n6", "&\n6"),
+    ("&\n7", "/\n7"),
+    ("*\n8", "(\n8"),
+    ("(\n9", ")\n9"),
+    (")\n0", "=\n0"),
+    ("_\n-", "?\nß"),
+    ("+\n=", "`\n´"),
+    ("~\n`", "°\n^"),
+    ("{\n[", "Ü"),
+    ("}\n]", "*\n+"),
+    ("|\n\\", "'\n#"),
+    (":\n;", "Ö"),
+    ("\"\n'", "Ä"),
+    ("<\n,", ";\n,"),
+    (">\n.", ":\n."),
+    ("?\n/", "_\n-"),
+    ("Y", "Z"),
+    ("Z", "Y"),
+];
+
+/// A mapping from a key's US legends to their equivalent in another locale, used by
+/// [`apply_locale`]
+///
+/// A key's legends are identified by the lines of all its legends in left-to-right, top-to-bottom
+/// order, separated by `\n`. For example the US `2` key with a shifted `@` is `"@\n2"`
+#[derive(Debug, Clone, Default)]
+pub struct LocaleMap(HashMap<String, String>);
+
+impl LocaleMap {
+    /// Create a new empty map
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a map for a US ANSI layout
+    ///
+    /// Legends are assumed to be US already, so this map is empty
+    #[inline]
+    #[must_use]
+    pub fn us() -> Self {
+        Self::new()
+    }
+
+    /// Create a map for a UK ISO layout
+    #[must_use]
+    pub fn uk() -> Self {
+        let mut map = Self::new();
+        for (us, uk) in UK_LEGENDS {
+            map.insert(us, uk);
+        }
+        map
+    }
+
+    /// Create a map for a German ISO (QWERTZ) layout
+    #[must_use]
+    pub fn de() -> Self {
+        let mut map = Self::new();
+        for (us, de) in DE_LEGENDS {
+            map.insert(us, de);
+        }
+        map
+    }
+
+    /// Add or replace the substitution for a key's legends
+    #[inline]
+    pub fn insert(&mut self, legends: impl Into<String>, replacement: impl Into<String>) {
+        self.0.insert(legends.into(), replacement.into());
+    }
+
+    /// Get the substitution for a key's legends, if it is in the map
+    #[inline]
+    #[must_use]
+    pub fn get(&self, legends: &str) -> Option<&str> {
+        self.0.get(legends).map(String::as_str)
+    }
+}
+
+/// Substitute each key's legends with their equivalent in `locale`
+///
+/// If the replacement has the same number of lines as the key's legends, each line is replaced in
+/// place, keeping the legends' positions, sizes, and colours. Otherwise the replacement is placed
+/// in the key's first legend and any other legends are cleared. Keys whose legends are not in the
+/// map are left unchanged
+pub fn apply_locale(keys: &mut [Key], locale: &LocaleMap) {
+    for key in keys {
+        let lines: Vec<_> = key
+            .legends
+            .iter()
+            .flatten()
+            .flat_map(|legend| legend.text.lines())
+            .collect();
+        let Some(replacement) = locale.get(&lines.join("\n")) else {
+            continue;
+        };
+
+        if replacement.lines().count() == lines.len() {
+            let mut replacement = replacement.lines();
+            key.legends
+                .map_text(|line| replacement.next().unwrap_or(line).to_owned());
+        } else {
+            let text = replacement.lines().collect::<Vec<_>>().join("<br>");
+            let mut first = true;
+            for i in 0..9 {
+                if let Some(legend) = key.legends[i].take() {
+                    key.legends[i] = first.then(|| Legend {
+                        text: Text::parse_from(&text),
+                        ..legend
+                    });
+                    first = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use color::Color;
+
+    use super::*;
+    use crate::Legends;
+
+    fn key(legends: &[(usize, &str)]) -> Key {
+        let mut key = Key::new();
+        key.legends = Legends::default();
+        for &(i, text) in legends {
+            key.legends[i] = Some(Legend::new(text, 3, Color::new(0.0, 0.0, 0.0)));
+        }
+        key
+    }
+
+    fn lines(key: &Key) -> Vec<(usize, Vec<&str>)> {
+        key.legends
+            .iter()
+            .enumerate()
+            .filter_map(|(i, legend)| Some((i, legend.as_ref()?.text.lines().collect())))
+            .collect()
+    }
+
+    #[test]
+    fn locale_map_builtin() {
+        assert_eq!(LocaleMap::us().get("@\n2"), None);
+        assert_eq!(LocaleMap::uk().get("@\n2"), Some("\"\n2"));
+        assert_eq!(LocaleMap::uk().get("#\n3"), Some("£\n3"));
+        assert_eq!(LocaleMap::de().get("Z"), Some("Y"));
+        assert_eq!(LocaleMap::de().get("Q"), None);
+    }
+
+    #[test]
+    fn test_apply_locale() {
+        let mut keys = [
+            key(&[(0, "@"), (6, "2")]),
+            key(&[(0, "Q")]),
+            key(&[(0, "{"), (6, "[")]),
+            key(&[(4, "{<br>[")]),
+        ];
+
+        apply_locale(&mut keys, &LocaleMap::uk());
+        assert_eq!(lines(&keys[0]), [(0, vec!["\""]), (6, vec!["2"])]);
+        assert_eq!(lines(&keys[1]), [(0, vec!["Q"])]);
+
+        apply_locale(&mut keys, &LocaleMap::de());
+        // Already UK, so no longer matches the US legends
+        assert_eq!(lines(&keys[0]), [(0, vec!["\""]), (6, vec!["2"])]);
+        assert_eq!(lines(&keys[2]), [(0, vec!["Ü"])]);
+        assert_eq!(lines(&keys[3]), [(4, vec!["Ü"])]);
+    }
+}
@@ -3,47 +3,74 @@ mod error;
 use std::collections::HashMap;
 
 use geom::{
-    Dot, ExtRect, Length, Mm, Point, Rect, SideOffsets, Size, Vector, DOT_PER_MM, DOT_PER_UNIT,
+    Dot, ExtRect, Length, Point, Rect, SideOffsets, Size, Vector, DOT_PER_INCH, DOT_PER_MM,
+    DOT_PER_UNIT, MM_PER_UNIT,
 };
 use serde::de::{Error as _, Unexpected};
 use serde::{Deserialize, Deserializer};
 
-use crate::{BottomSurface, HomingProps, ScoopProps, TextHeight, TextMargin, Type};
+use crate::{
+    BottomSurface, HomingDef, HomingProps, ScoopProps, ScoopShape, TextHeight, TextMargin, Type,
+};
 
 use super::{BarProps, BumpProps, Profile, TopSurface};
 
 pub use error::{Error, Result};
 
+// The raw types below hold lengths in the units of the file being deserialized. They are converted
+// to dots once the units are known, which for a profile is only after the whole file has been read.
+// When deserialized on their own, lengths are always in millimetres
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum RawType {
+    Cylindrical {
+        depth: f32,
+    },
+    Spherical {
+        depth: f32,
+    },
+    #[serde(alias = "chiclet")]
+    Flat,
+}
+
+impl RawType {
+    const fn convert(self, dot_per_unit: f32) -> Type {
+        match self {
+            Self::Cylindrical { depth } => Type::Cylindrical {
+                depth: Length::new(depth * dot_per_unit),
+            },
+            Self::Spherical { depth } => Type::Spherical {
+                depth: Length::new(depth * dot_per_unit),
+            },
+            Self::Flat => Type::Flat,
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Type {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[derive(serde::Deserialize)]
-        #[serde(tag = "type", rename_all = "kebab-case")]
-        enum RawType {
-            Cylindrical {
-                depth: f32,
-            },
-            Spherical {
-                depth: f32,
-            },
-            #[serde(alias = "chiclet")]
-            Flat,
-        }
+        RawType::deserialize(deserializer).map(|typ| typ.convert(DOT_PER_MM.0))
+    }
+}
 
-        RawType::deserialize(deserializer).map(|typ| {
-            // Convert to Length
-            match typ {
-                RawType::Cylindrical { depth } => Self::Cylindrical {
-                    depth: Length::<Mm>::new(depth) * DOT_PER_MM,
-                },
-                RawType::Spherical { depth } => Self::Spherical {
-                    depth: Length::<Mm>::new(depth) * DOT_PER_MM,
-                },
-                RawType::Flat => Self::Flat,
-            }
-        })
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawScoopProps {
+    depth: f32,
+    #[serde(default)]
+    shape: ScoopShape,
+}
+
+impl RawScoopProps {
+    const fn convert(self, dot_per_unit: f32) -> ScoopProps {
+        ScoopProps {
+            depth: Length::new(self.depth * dot_per_unit),
+            shape: self.shape,
+        }
     }
 }
 
@@ -52,18 +79,24 @@ impl<'de> Deserialize<'de> for ScoopProps {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        struct RawScoopProps {
-            depth: f32,
-        }
+        RawScoopProps::deserialize(deserializer).map(|props| props.convert(DOT_PER_MM.0))
+    }
+}
 
-        RawScoopProps::deserialize(deserializer).map(|props| {
-            // Convert to Length
-            Self {
-                depth: Length::<Mm>::new(props.depth) * DOT_PER_MM,
-            }
-        })
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawBarProps {
+    width: f32,
+    height: f32,
+    y_offset: f32,
+}
+
+impl RawBarProps {
+    fn convert(self, dot_per_unit: f32) -> BarProps {
+        BarProps {
+            size: Size::new(self.width, self.height) * dot_per_unit,
+            y_offset: Length::new(self.y_offset * dot_per_unit),
+        }
     }
 }
 
@@ -72,21 +105,23 @@ impl<'de> Deserialize<'de> for BarProps {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        struct RawBarProps {
-            width: f32,
-            height: f32,
-            y_offset: f32,
-        }
+        RawBarProps::deserialize(deserializer).map(|props| props.convert(DOT_PER_MM.0))
+    }
+}
 
-        RawBarProps::deserialize(deserializer).map(|props| {
-            // Convert to Length
-            Self {
-                size: Size::<Mm>::new(props.width, props.height) * DOT_PER_MM,
-                y_offset: Length::<Mm>::new(props.y_offset) * DOT_PER_MM,
-            }
-        })
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawBumpProps {
+    diameter: f32,
+    y_offset: f32,
+}
+
+impl RawBumpProps {
+    const fn convert(self, dot_per_unit: f32) -> BumpProps {
+        BumpProps {
+            diameter: Length::new(self.diameter * dot_per_unit),
+            y_offset: Length::new(self.y_offset * dot_per_unit),
+        }
     }
 }
 
@@ -95,20 +130,64 @@ impl<'de> Deserialize<'de> for BumpProps {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        struct RawBumpProps {
-            diameter: f32,
-            y_offset: f32,
+        RawBumpProps::deserialize(deserializer).map(|props| props.convert(DOT_PER_MM.0))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawHomingProps {
+    #[serde(with = "HomingDef")]
+    default: key::Homing,
+    scoop: RawScoopProps,
+    bar: RawBarProps,
+    bump: RawBumpProps,
+}
+
+impl RawHomingProps {
+    fn convert(self, dot_per_unit: f32) -> HomingProps {
+        HomingProps {
+            default: self.default,
+            scoop: self.scoop.convert(dot_per_unit),
+            bar: self.bar.convert(dot_per_unit),
+            bump: self.bump.convert(dot_per_unit),
         }
+    }
+}
 
-        RawBumpProps::deserialize(deserializer).map(|props| {
-            // Convert to Length
-            Self {
-                diameter: Length::<Mm>::new(props.diameter) * DOT_PER_MM,
-                y_offset: Length::<Mm>::new(props.y_offset) * DOT_PER_MM,
-            }
-        })
+// The radius can be a single value or separate horizontal and vertical radii
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRadius {
+    Uniform(f32),
+    Radii { x: f32, y: f32 },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawTopSurface {
+    width: f32,
+    height: f32,
+    radius: RawRadius,
+    y_offset: f32,
+    #[serde(default)]
+    dish_x_offset: f32,
+    #[serde(default)]
+    dish_y_offset: f32,
+}
+
+impl RawTopSurface {
+    fn convert(self, dot_per_unit: f32) -> TopSurface {
+        let radius = match self.radius {
+            RawRadius::Uniform(radius) => Vector::splat(radius),
+            RawRadius::Radii { x, y } => Vector::new(x, y),
+        };
+        TopSurface {
+            size: Size::new(self.width, self.height) * dot_per_unit,
+            radius: radius * dot_per_unit,
+            y_offset: Length::new(self.y_offset * dot_per_unit),
+            dish_offset: Vector::new(self.dish_x_offset, self.dish_y_offset) * dot_per_unit,
+        }
     }
 }
 
@@ -117,23 +196,24 @@ impl<'de> Deserialize<'de> for TopSurface {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        struct RawTopSurface {
-            width: f32,
-            height: f32,
-            radius: f32,
-            y_offset: f32,
-        }
+        RawTopSurface::deserialize(deserializer).map(|surface| surface.convert(DOT_PER_MM.0))
+    }
+}
 
-        RawTopSurface::deserialize(deserializer).map(|surface| {
-            // Convert to Length
-            Self {
-                size: Size::<Mm>::new(surface.width, surface.height) * DOT_PER_MM,
-                radius: Length::<Mm>::new(surface.radius) * DOT_PER_MM,
-                y_offset: Length::<Mm>::new(surface.y_offset) * DOT_PER_MM,
-            }
-        })
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawBottomSurface {
+    width: f32,
+    height: f32,
+    radius: f32,
+}
+
+impl RawBottomSurface {
+    fn convert(self, dot_per_unit: f32) -> BottomSurface {
+        BottomSurface {
+            size: Size::new(self.width, self.height) * dot_per_unit,
+            radius: Length::new(self.radius * dot_per_unit),
+        }
     }
 }
 
@@ -142,21 +222,7 @@ impl<'de> Deserialize<'de> for BottomSurface {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        struct RawBottomSurface {
-            width: f32,
-            height: f32,
-            radius: f32,
-        }
-
-        RawBottomSurface::deserialize(deserializer).map(|surface| {
-            // Convert to Length
-            Self {
-                size: Size::<Mm>::new(surface.width, surface.height) * DOT_PER_MM,
-                radius: Length::<Mm>::new(surface.radius) * DOT_PER_MM,
-            }
-        })
+        RawBottomSurface::deserialize(deserializer).map(|surface| surface.convert(DOT_PER_MM.0))
     }
 }
 
@@ -171,11 +237,11 @@ struct LegendProps {
 }
 
 impl LegendProps {
-    fn rect(&self, top_offset: Length<Dot>) -> Rect<Dot> {
+    fn rect(&self, top_offset: Length<Dot>, dot_per_unit: f32) -> Rect<Dot> {
         Rect::from_center_and_size(
             Point::new(0.5, 0.5) * DOT_PER_UNIT
-                + Vector::new(0.0, top_offset.get() + self.y_offset),
-            Size::new(self.width, self.height),
+                + Vector::new(0.0, top_offset.get() + self.y_offset * dot_per_unit),
+            Size::new(self.width, self.height) * dot_per_unit,
         )
     }
 }
@@ -192,17 +258,31 @@ where
             let i = s
                 .parse()
                 .map_err(|_| D::Error::invalid_value(Unexpected::Str(&s), &"an integer"))?;
-            let p = LegendProps {
-                size: p.size * DOT_PER_MM.0,
-                width: p.width * DOT_PER_MM.0,
-                height: p.height * DOT_PER_MM.0,
-                y_offset: p.y_offset * DOT_PER_MM.0,
-            };
             Ok((i, p))
         })
         .collect()
 }
 
+/// The units used for lengths in a profile file
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Units {
+    #[default]
+    Mm,
+    Inch,
+    U,
+}
+
+impl Units {
+    const fn dot_per_unit(self) -> f32 {
+        match self {
+            Self::Mm => DOT_PER_MM.0,
+            Self::Inch => DOT_PER_INCH.0,
+            Self::U => DOT_PER_UNIT.0,
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Profile {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
@@ -211,29 +291,41 @@ impl<'de> Deserialize<'de> for Profile {
         #[derive(Deserialize)]
         struct RawProfileData {
             #[serde(flatten)]
-            typ: Type,
-            bottom: BottomSurface,
-            top: TopSurface,
+            typ: RawType,
+            #[serde(default)]
+            units: Units,
+            bottom: RawBottomSurface,
+            top: RawTopSurface,
+            #[serde(default)]
+            rows: Option<[RawTopSurface; Profile::NUM_ROWS]>,
+            #[serde(default, rename = "space-top")]
+            space_top: Option<RawTopSurface>,
             #[serde(deserialize_with = "deserialize_legend_map")]
             legend: HashMap<usize, LegendProps>,
-            homing: HomingProps,
+            homing: RawHomingProps,
+            #[serde(default, rename = "space-indicator")]
+            space_indicator: Option<RawBarProps>,
+            #[serde(default, rename = "unit-pitch")]
+            unit_pitch: Option<f32>,
         }
 
-        let raw_data: RawProfileData = RawProfileData::deserialize(deserializer)?;
+        let raw_data = RawProfileData::deserialize(deserializer)?;
+        let dot_per_unit = raw_data.units.dot_per_unit();
+        let top = raw_data.top.convert(dot_per_unit);
 
         let (heights, offsets): (HashMap<_, _>, HashMap<_, _>) = raw_data
             .legend
             .into_iter()
             .map(|(i, props)| {
-                let height = Length::<Dot>::new(props.size);
+                let height = Length::<Dot>::new(props.size * dot_per_unit);
                 let Rect {
                     min: props_min,
                     max: props_max,
-                } = props.rect(raw_data.top.y_offset);
+                } = props.rect(top.y_offset, dot_per_unit);
                 let Rect {
                     min: raw_min,
                     max: raw_max,
-                } = raw_data.top.rect();
+                } = top.rect();
                 let offset =
                     SideOffsets::from_vectors_inner(props_min - raw_min, props_max - raw_max);
                 ((i, height), (i, offset))
@@ -241,12 +333,27 @@ impl<'de> Deserialize<'de> for Profile {
             .unzip();
 
         Ok(Self {
-            typ: raw_data.typ,
-            bottom: raw_data.bottom,
-            top: raw_data.top,
+            typ: raw_data.typ.convert(dot_per_unit),
+            bottom: raw_data.bottom.convert(dot_per_unit),
+            top,
+            rows: raw_data
+                .rows
+                .map(|rows| rows.map(|row| row.convert(dot_per_unit))),
+            space_top: raw_data
+                .space_top
+                .map(|space_top| space_top.convert(dot_per_unit)),
             text_margin: TextMargin::new(&offsets),
             text_height: TextHeight::new(&heights),
-            homing: raw_data.homing,
+            homing: raw_data.homing.convert(dot_per_unit),
+            space_indicator: raw_data
+                .space_indicator
+                .map(|indicator| indicator.convert(dot_per_unit)),
+            // The unit pitch is in millimetres rather than dots
+            unit_pitch: raw_data
+                .unit_pitch
+                .map_or(Length::new(MM_PER_UNIT.0), |pitch| {
+                    Length::new(pitch * dot_per_unit / DOT_PER_MM.0)
+                }),
             __non_exhaustive: super::NonExhaustive,
         })
     }
@@ -255,6 +362,7 @@ impl<'de> Deserialize<'de> for Profile {
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use geom::Mm;
     use isclose::{assert_is_close, IsClose};
 
     use super::*;
@@ -277,6 +385,24 @@ mod tests {
         let scoop_props: ScoopProps = serde_json::from_str(r#"{ "depth": 0.8 }"#).unwrap();
 
         assert_is_close!(scoop_props.depth, Length::<Mm>::new(0.8) * DOT_PER_MM);
+        assert_eq!(scoop_props.shape, ScoopShape::Spherical);
+
+        for (name, shape) in [
+            ("spherical", ScoopShape::Spherical),
+            ("cylindrical", ScoopShape::Cylindrical),
+            ("parabolic", ScoopShape::Parabolic),
+        ] {
+            let scoop_props: ScoopProps =
+                serde_json::from_str(&format!(r#"{{ "depth": 0.8, "shape": "{name}" }}"#)).unwrap();
+            assert_eq!(scoop_props.shape, shape);
+        }
+
+        let scoop_props: ScoopProps = toml::from_str("depth = 1.5\nshape = 'parabolic'").unwrap();
+        assert_eq!(scoop_props.shape, ScoopShape::Parabolic);
+
+        assert!(
+            serde_json::from_str::<ScoopProps>(r#"{ "depth": 0.8, "shape": "cubic" }"#).is_err()
+        );
     }
 
     #[test]
@@ -305,8 +431,37 @@ mod tests {
         .unwrap();
 
         assert_is_close!(surf.size, Size::new(11.81, 13.91) * DOT_PER_MM);
-        assert_is_close!(surf.radius, Length::new(1.52) * DOT_PER_MM);
+        assert_is_close!(surf.radius, Vector::splat(1.52) * DOT_PER_MM);
         assert_is_close!(surf.y_offset, Length::new(-1.62) * DOT_PER_MM);
+        assert_is_close!(surf.dish_offset, Vector::zero());
+
+        let surf: TopSurface = serde_json::from_str(
+            r#"{ "width": 11.81, "height": 13.91, "radius": 1.52, "y-offset": -1.62,
+                "dish-x-offset": 0.5, "dish-y-offset": -0.25 }"#,
+        )
+        .unwrap();
+
+        assert_is_close!(surf.dish_offset, Vector::new(0.5, -0.25) * DOT_PER_MM);
+
+        let surf: TopSurface = serde_json::from_str(
+            r#"{ "width": 11.81, "height": 13.91, "radius": { "x": 1.52, "y": 2.0 },
+                "y-offset": -1.62 }"#,
+        )
+        .unwrap();
+
+        assert_is_close!(surf.radius, Vector::new(1.52, 2.0) * DOT_PER_MM);
+
+        let surf: TopSurface = toml::from_str(
+            "width = 11.81\nheight = 13.91\nradius = { x = 1.52, y = 2.0 }\ny-offset = -1.62",
+        )
+        .unwrap();
+
+        assert_is_close!(surf.radius, Vector::new(1.52, 2.0) * DOT_PER_MM);
+
+        let result = serde_json::from_str::<TopSurface>(
+            r#"{ "width": 11.81, "height": 13.91, "radius": { "x": 1.52 }, "y-offset": -1.62 }"#,
+        );
+        assert!(result.is_err());
     }
 
     #[test]
@@ -6,6 +6,7 @@
 
 #[cfg(feature = "serde")]
 mod de;
+mod measure;
 
 use std::array;
 use std::collections::HashMap;
@@ -13,15 +14,17 @@ use std::fmt;
 use std::sync::OnceLock;
 
 use geom::{
-    Dot, ExtRect, Inch, Length, Mm, Point, Rect, RoundRect, SideOffsets, Size, Unit, Vector,
-    DOT_PER_INCH, DOT_PER_MM, DOT_PER_UNIT,
+    Dot, EllipticalRoundRect, ExtRect, Inch, Length, Mm, Point, Rect, RoundRect, Scale,
+    SideOffsets, Size, Unit, Vector, DOT_PER_INCH, DOT_PER_MM, DOT_PER_UNIT, MM_PER_UNIT,
 };
 use interp::{interp_array, InterpMode};
 use key::Homing;
 use saturate::SaturatingFrom;
 
+pub use measure::{Measurement, ProfileMeasurements};
+
 /// The type of a profile
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Type {
     /// A cylindrical profile, e.g. Cherry or OEM
     Cylindrical {
@@ -59,15 +62,31 @@ impl Default for Type {
     }
 }
 
+/// The shape of the dish of a scooped homing key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ScoopShape {
+    /// A spherical cap, which is shaded evenly from the edge to the centre
+    #[default]
+    Spherical,
+    /// A cylindrical dish curving across the width of the key
+    Cylindrical,
+    /// A paraboloid, which is flat at the centre and gets steeper towards the edge
+    Parabolic,
+}
+
 /// Scooped (a.k.a. deep dish) homing key properties
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ScoopProps {
     /// The depth of the scooped dish
     pub depth: Length<Dot>,
+    /// The shape of the scooped dish
+    pub shape: ScoopShape,
 }
 
 /// Homing bar properties
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BarProps {
     /// The size of the bar
     pub size: Size<Dot>,
@@ -76,7 +95,7 @@ pub struct BarProps {
 }
 
 /// Homing bump (a.k.a. nub or nipple) properties
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BumpProps {
     /// The diameter of the bump
     pub diameter: Length<Dot>,
@@ -98,7 +117,7 @@ enum HomingDef {
 }
 
 /// Homing key properties
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct HomingProps {
     /// The default type of homing key for this profile
@@ -119,6 +138,7 @@ impl Default for HomingProps {
             default: Homing::Bar,
             scoop: ScoopProps {
                 depth: Type::default().depth() * 2.0, // 2x the regular depth
+                shape: ScoopShape::Spherical,
             },
             bar: BarProps {
                 size: Size::<Inch>::new(0.15, 0.02) * DOT_PER_INCH,
@@ -134,7 +154,7 @@ impl Default for HomingProps {
 
 /// Text height mapping. This maps a [`usize`] index (used by KLE for example)
 /// to a [`Length`] for the height of uppercase letter
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextHeight([Length<Dot>; Self::NUM_HEIGHTS]);
 
 impl TextHeight {
@@ -191,7 +211,7 @@ impl Default for TextHeight {
 
 /// Text margin mapping. This maps a [`usize`] index (used by KLE for example)
 /// to a [`SideOffsets`] for the text alignment relative to the key top
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextMargin([SideOffsets<Dot>; Self::NUM_RECTS]);
 
 impl TextMargin {
@@ -251,14 +271,16 @@ impl Default for TextMargin {
 }
 
 /// A key top surface
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TopSurface {
     /// The size of the key top
     pub size: Size<Dot>,
-    /// The corner radius for the key top
-    pub radius: Length<Dot>,
+    /// The horizontal and vertical corner radii for the key top
+    pub radius: Vector<Dot>,
     /// The offset of the key top relative to the key bottom
     pub y_offset: Length<Dot>,
+    /// The offset of the centre of the key's dish relative to the centre of the key top
+    pub dish_offset: Vector<Dot>,
 }
 
 impl TopSurface {
@@ -269,8 +291,8 @@ impl TopSurface {
         )
     }
 
-    pub(crate) fn round_rect(&self) -> RoundRect<Dot> {
-        RoundRect::from_rect(self.rect(), self.radius)
+    pub(crate) fn round_rect(&self) -> EllipticalRoundRect<Dot> {
+        EllipticalRoundRect::from_rect_and_radii(self.rect(), self.radius)
     }
 }
 
@@ -279,14 +301,15 @@ impl Default for TopSurface {
     fn default() -> Self {
         Self {
             size: Size::<Unit>::new(0.660, 0.735) * DOT_PER_UNIT,
-            radius: Length::<Unit>::new(0.065) * DOT_PER_UNIT,
+            radius: Vector::<Unit>::splat(0.065) * DOT_PER_UNIT,
             y_offset: Length::<Unit>::new(-0.0775) * DOT_PER_UNIT,
+            dish_offset: Vector::zero(),
         }
     }
 }
 
 /// A key bottom surface
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BottomSurface {
     /// The size of the key bottom
     pub size: Size<Dot>,
@@ -314,11 +337,14 @@ impl Default for BottomSurface {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 struct NonExhaustive;
 
 /// A keyboard profile
-#[derive(Clone)]
+///
+/// Equality compares all dimensions exactly, so profiles which differ only by floating point
+/// rounding are not equal
+#[derive(Clone, PartialEq)]
 pub struct Profile {
     /// The type of profile
     pub typ: Type,
@@ -326,12 +352,26 @@ pub struct Profile {
     pub bottom: BottomSurface,
     /// The shape of the top surface
     pub top: TopSurface,
+    /// Per-row top surfaces for sculpted profiles, from row 1 (R1) to row 4 (R4). If this is
+    /// [`None`] all rows use [`Profile::top`]
+    pub rows: Option<[TopSurface; Self::NUM_ROWS]>,
+    /// The top surface used for spacebars, which are often lower or have a different dish to other
+    /// keys. If this is [`None`] spacebars use the same top surface as other keys
+    pub space_top: Option<TopSurface>,
     /// The margin mapping for legend text alignment
     pub text_margin: TextMargin,
     /// The legend text size mapping
     pub text_height: TextHeight,
     /// Homing properties
     pub homing: HomingProps,
+    /// A cosmetic bar drawn on spacebars to make them easier to recognise, for example to
+    /// represent a groove or texture. This is drawn like a homing bar but only on
+    /// [`Shape::Space`](key::Shape::Space) keys. Defaults to [`None`]
+    pub space_indicator: Option<BarProps>,
+    /// The distance between the centres of adjacent 1u keys. This affects the spacing between keys
+    /// but not the size of the keycaps themselves, which is given by [`BottomSurface::size`].
+    /// Defaults to the standard 19.05mm
+    pub unit_pitch: Length<Mm>,
     /// Hidden field to enforce non-exhaustive struct while still allowing instantiation using
     /// `..Default::default()` functional update syntax
     #[allow(private_interfaces)]
@@ -345,9 +385,13 @@ impl fmt::Debug for Profile {
         dbg.field("typ", &self.typ)
             .field("bottom", &self.bottom)
             .field("top", &self.top)
+            .field("rows", &self.rows)
+            .field("space_top", &self.space_top)
             .field("text_margin", &self.text_margin)
             .field("text_height", &self.text_height)
-            .field("homing", &self.homing);
+            .field("homing", &self.homing)
+            .field("space_indicator", &self.space_indicator)
+            .field("unit_pitch", &self.unit_pitch);
 
         #[cfg(clippy)] // Suppress clippy::missing_fields_in_debug but only for this one field
         dbg.field("__non_exhaustive", &"NonExhaustive");
@@ -357,8 +401,14 @@ impl fmt::Debug for Profile {
 }
 
 impl Profile {
+    /// The number of rows in a sculpted profile
+    pub const NUM_ROWS: usize = 4;
+
     /// Load a profile from a TOML configuration file
     ///
+    /// Lengths are given in millimetres unless the file sets a top-level `units` key to `"inch"`
+    /// or `"u"` (key units)
+    ///
     /// # Errors
     ///
     /// If there was an error parsing the file
@@ -374,6 +424,9 @@ impl Profile {
 
     /// Load a profile from a JSON configuration file
     ///
+    /// Lengths are given in millimetres unless the file sets a top-level `"units"` key to
+    /// `"inch"` or `"u"` (key units)
+    ///
     /// # Errors
     ///
     /// If there was an error parsing the file
@@ -383,42 +436,199 @@ impl Profile {
         serde_json::from_str(s).map_err(de::Error::from)
     }
 
+    /// Get the top surface for a key in the given row, where 1 is R1. For sculpted profiles this
+    /// is the row's top surface from [`Profile::rows`]. If [`Profile::rows`] is [`None`] or the
+    /// row is not valid this is [`Profile::top`]
+    #[inline]
+    #[must_use]
+    pub fn row_top(&self, row: Option<u8>) -> &TopSurface {
+        self.rows
+            .as_ref()
+            .zip(row)
+            .and_then(|(rows, row)| {
+                usize::from(row)
+                    .checked_sub(1)
+                    .and_then(|idx| rows.get(idx))
+            })
+            .unwrap_or(&self.top)
+    }
+
+    /// Get the top surface for a spacebar in the given row. This is [`Profile::space_top`] if it is
+    /// set, otherwise the row's top surface as given by [`Profile::row_top`]
+    #[inline]
+    #[must_use]
+    pub fn spacebar_top(&self, row: Option<u8>) -> &TopSurface {
+        self.space_top.as_ref().unwrap_or_else(|| self.row_top(row))
+    }
+
+    /// Get the number of [`Dot`]s per key unit for this profile's [`Profile::unit_pitch`]
+    #[inline]
+    #[must_use]
+    pub fn dot_per_unit(&self) -> Scale<Unit, Dot> {
+        // Scale DOT_PER_UNIT rather than using DOT_PER_MM so the default pitch is exact
+        DOT_PER_UNIT * Scale::new(self.unit_pitch.get() / MM_PER_UNIT.get())
+    }
+
     /// Get the key top rectangle for a given key size
+    ///
+    /// If the top surface has different horizontal and vertical radii the smaller of the two is
+    /// used. Use [`Profile::top_with_size_elliptical`] to get both radii
     #[inline]
     #[must_use]
     pub fn top_with_size(&self, size: Size<Unit>) -> RoundRect<Dot> {
-        let RoundRect { min, max, radius } = self.top.round_rect();
-        let max = max + (size - Size::splat(1.0)) * DOT_PER_UNIT;
-        RoundRect::new(min, max, radius)
+        self.top_with_rect(Rect::from_size(size))
     }
 
     /// Get the key top rectangle for a given key rect
+    ///
+    /// If the top surface has different horizontal and vertical radii the smaller of the two is
+    /// used. Use [`Profile::top_with_rect_elliptical`] to get both radii
     #[inline]
     #[must_use]
     pub fn top_with_rect(&self, rect: Rect<Unit>) -> RoundRect<Dot> {
-        let RoundRect { min, max, radius } = self.top.round_rect();
-        let min = min + rect.min.to_vector() * DOT_PER_UNIT;
-        let max = max + (rect.max.to_vector() - Vector::splat(1.0)) * DOT_PER_UNIT;
-        RoundRect::new(min, max, radius)
+        self.top_surface_with_rect(&self.top, rect)
+    }
+
+    /// Get the key top rectangle of the given top surface for a given key size. This is used with
+    /// a different surface to [`Profile::top`], for example from [`Profile::row_top`]
+    ///
+    /// If the top surface has different horizontal and vertical radii the smaller of the two is
+    /// used. Use [`Profile::top_surface_with_size_elliptical`] to get both radii
+    #[inline]
+    #[must_use]
+    pub fn top_surface_with_size(&self, top: &TopSurface, size: Size<Unit>) -> RoundRect<Dot> {
+        self.top_surface_with_rect(top, Rect::from_size(size))
+    }
+
+    /// Get the key top rectangle of the given top surface for a given key rect. This is used with
+    /// a different surface to [`Profile::top`], for example from [`Profile::row_top`]
+    ///
+    /// If the top surface has different horizontal and vertical radii the smaller of the two is
+    /// used. Use [`Profile::top_surface_with_rect_elliptical`] to get both radii
+    #[inline]
+    #[must_use]
+    pub fn top_surface_with_rect(&self, top: &TopSurface, rect: Rect<Unit>) -> RoundRect<Dot> {
+        let EllipticalRoundRect { min, max, radii } =
+            self.top_surface_with_rect_elliptical(top, rect);
+        RoundRect::new(min, max, Length::new(radii.x.min(radii.y)))
+    }
+
+    /// Get the key top rectangle with elliptical corners for a given key size
+    #[inline]
+    #[must_use]
+    pub fn top_with_size_elliptical(&self, size: Size<Unit>) -> EllipticalRoundRect<Dot> {
+        self.top_with_rect_elliptical(Rect::from_size(size))
+    }
+
+    /// Get the key top rectangle with elliptical corners for a given key rect
+    #[inline]
+    #[must_use]
+    pub fn top_with_rect_elliptical(&self, rect: Rect<Unit>) -> EllipticalRoundRect<Dot> {
+        self.top_surface_with_rect_elliptical(&self.top, rect)
+    }
+
+    /// Get the key top rectangle with elliptical corners of the given top surface for a given key
+    /// size. This is used with a different surface to [`Profile::top`], for example from
+    /// [`Profile::row_top`]
+    #[inline]
+    #[must_use]
+    pub fn top_surface_with_size_elliptical(
+        &self,
+        top: &TopSurface,
+        size: Size<Unit>,
+    ) -> EllipticalRoundRect<Dot> {
+        self.top_surface_with_rect_elliptical(top, Rect::from_size(size))
+    }
+
+    /// Get the key top rectangle with elliptical corners of the given top surface for a given key
+    /// rect. This is used with a different surface to [`Profile::top`], for example from
+    /// [`Profile::row_top`]
+    #[inline]
+    #[must_use]
+    pub fn top_surface_with_rect_elliptical(
+        &self,
+        top: &TopSurface,
+        rect: Rect<Unit>,
+    ) -> EllipticalRoundRect<Dot> {
+        let surface = top.round_rect();
+        EllipticalRoundRect::from_rect_and_radii(
+            self.surface_with_rect(surface.rect(), rect),
+            surface.radii,
+        )
+    }
+
+    /// Get the centre point of the key's dish for a given key size
+    #[inline]
+    #[must_use]
+    pub fn dish_center_with_size(&self, size: Size<Unit>) -> Point<Dot> {
+        self.dish_center_with_surface(&self.top, size)
+    }
+
+    /// Get the centre point of the key's dish on the given top surface for a given key size
+    #[inline]
+    #[must_use]
+    pub fn dish_center_with_surface(&self, top: &TopSurface, size: Size<Unit>) -> Point<Dot> {
+        self.top_surface_with_size(top, size).center() + top.dish_offset
     }
 
     /// Get the key bottom rectangle for a given key size
     #[inline]
     #[must_use]
     pub fn bottom_with_size(&self, size: Size<Unit>) -> RoundRect<Dot> {
-        let RoundRect { min, max, radius } = self.bottom.round_rect();
-        let max = max + (size - Size::splat(1.0)) * DOT_PER_UNIT;
-        RoundRect::new(min, max, radius)
+        self.bottom_with_rect(Rect::from_size(size))
     }
 
     /// Get the key bottom rectangle for a given key rectangle
     #[inline]
     #[must_use]
     pub fn bottom_with_rect(&self, rect: Rect<Unit>) -> RoundRect<Dot> {
-        let RoundRect { min, max, radius } = self.bottom.round_rect();
-        let min = min + rect.min.to_vector() * DOT_PER_UNIT;
-        let max = max + (rect.max.to_vector() - Vector::splat(1.0)) * DOT_PER_UNIT;
-        RoundRect::new(min, max, radius)
+        let surface = self.bottom.round_rect();
+        RoundRect::from_rect(self.surface_with_rect(surface.rect(), rect), surface.radius)
+    }
+
+    // Stretches a 1u surface to cover the given key rectangle. Surfaces are centred in a square of
+    // DOT_PER_UNIT, so they are also recentred in a square of the profile's unit pitch
+    fn surface_with_rect(&self, surface: Rect<Dot>, rect: Rect<Unit>) -> Rect<Dot> {
+        let Rect { min, max } = surface;
+        let dot_per_unit = self.dot_per_unit();
+        let center_offset = Vector::splat((dot_per_unit.get() - DOT_PER_UNIT.get()) / 2.0);
+        let min = min + center_offset + rect.min.to_vector() * dot_per_unit;
+        let max = max + center_offset + (rect.max.to_vector() - Vector::splat(1.0)) * dot_per_unit;
+        Rect { min, max }
+    }
+
+    /// Get the type of homing used for a homing key, using the profile's default homing type if
+    /// `homing` is [`None`]
+    #[inline]
+    #[must_use]
+    pub const fn resolve_homing(&self, homing: Option<Homing>) -> Homing {
+        match homing {
+            Some(homing) => homing,
+            None => self.homing.default,
+        }
+    }
+
+    /// Get the physical centre and size of each key in millimetres, for example for exporting to
+    /// CAD or firmware tools
+    ///
+    /// Keys are spaced using the profile's [`Profile::unit_pitch`], and the size is that of the
+    /// key's bottom surface footprint. For non-rectangular keys such as ISO enter this is the
+    /// footprint's bounding box. The result contains one entry per key in the same order as
+    /// `keys`, including keys with [`key::Shape::None`]
+    #[inline]
+    #[must_use]
+    pub fn key_positions_mm(&self, keys: &[key::Key]) -> Vec<(Point<Mm>, Size<Mm>)> {
+        let mm_per_dot = DOT_PER_MM.inverse();
+        keys.iter()
+            .map(|key| {
+                let rect = key.shape.outer_rect().translate(key.position.to_vector());
+                let footprint = self.bottom_with_rect(rect).rect();
+                (
+                    footprint.center() * mm_per_dot,
+                    footprint.size() * mm_per_dot,
+                )
+            })
+            .collect()
     }
 }
 
@@ -429,9 +639,13 @@ impl Default for Profile {
             typ: Type::default(),
             bottom: BottomSurface::default(),
             top: TopSurface::default(),
+            rows: None,
+            space_top: None,
             text_margin: TextMargin::default(),
             text_height: TextHeight::default(),
             homing: HomingProps::default(),
+            space_indicator: None,
+            unit_pitch: Length::new(MM_PER_UNIT.0),
             __non_exhaustive: NonExhaustive,
         }
     }
@@ -622,6 +836,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_text_margin_inner_box() {
+        let profile = Profile::default();
+        let top = profile.top_with_size(Size::new(1.0, 1.0)).rect();
+
+        // The legend area is the key top inset by the margin on each side
+        let legend_rect = top.inner_box(profile.text_margin.get(5));
+        assert_is_close!(
+            legend_rect,
+            Rect::new(Point::new(0.220, 0.105), Point::new(0.780, 0.740)) * DOT_PER_UNIT
+        );
+        assert_is_close!(
+            top.width() - legend_rect.width(),
+            profile.text_margin.get(5).horizontal()
+        );
+        assert_is_close!(
+            top.height() - legend_rect.height(),
+            profile.text_margin.get(5).vertical()
+        );
+    }
+
     #[test]
     fn test_top_surface_rect() {
         let surf = TopSurface::default();
@@ -637,10 +872,10 @@ mod tests {
         let surf = TopSurface::default();
         assert_is_close!(
             surf.round_rect(),
-            RoundRect::new(
+            EllipticalRoundRect::new(
                 Point::new(0.170, 0.055) * DOT_PER_UNIT,
                 Point::new(0.830, 0.790) * DOT_PER_UNIT,
-                Length::new(0.065) * DOT_PER_UNIT
+                Vector::splat(0.065) * DOT_PER_UNIT
             )
         );
     }
@@ -649,8 +884,9 @@ mod tests {
     fn test_top_surface_default() {
         let surf = TopSurface::default();
         assert_is_close!(surf.size, Size::new(0.660, 0.735) * DOT_PER_UNIT);
-        assert_is_close!(surf.radius, Length::new(0.065) * DOT_PER_UNIT);
+        assert_is_close!(surf.radius, Vector::splat(0.065) * DOT_PER_UNIT);
         assert_is_close!(surf.y_offset, Length::new(-0.0775) * DOT_PER_UNIT);
+        assert_is_close!(surf.dish_offset, Vector::zero());
     }
 
     #[test]
@@ -682,6 +918,25 @@ mod tests {
         assert_is_close!(surf.radius, Length::new(0.065) * DOT_PER_UNIT);
     }
 
+    #[test]
+    fn profile_partial_eq() {
+        assert_eq!(Profile::default(), Profile::default());
+        assert_ne!(
+            Profile::default(),
+            Profile {
+                typ: Type::Flat,
+                ..Profile::default()
+            }
+        );
+        assert_ne!(
+            Profile::default(),
+            Profile {
+                space_top: Some(TopSurface::default()),
+                ..Profile::default()
+            }
+        );
+    }
+
     #[test]
     fn profile_debug() {
         let profile = Profile::default();
@@ -689,14 +944,19 @@ mod tests {
         assert_eq!(
             format!("{profile:?}"),
             format!(
-                "Profile {{ typ: {:?}, bottom: {:?}, top: {:?}, text_margin: {:?}, \
-                text_height: {:?}, homing: {:?} }}",
+                "Profile {{ typ: {:?}, bottom: {:?}, top: {:?}, rows: {:?}, space_top: {:?}, \
+                text_margin: {:?}, text_height: {:?}, homing: {:?}, space_indicator: {:?}, \
+                unit_pitch: {:?} }}",
                 Type::default(),
                 BottomSurface::default(),
                 TopSurface::default(),
+                None::<[TopSurface; Profile::NUM_ROWS]>,
+                None::<TopSurface>,
                 TextMargin::default(),
                 TextHeight::default(),
                 HomingProps::default(),
+                None::<BarProps>,
+                Length::<Mm>::new(19.05),
             )
         );
     }
@@ -760,8 +1020,10 @@ mod tests {
         assert_is_close!(profile.bottom.radius, Length::new(0.38) * DOT_PER_MM);
 
         assert_is_close!(profile.top.size, Size::new(11.81, 13.91) * DOT_PER_MM);
-        assert_is_close!(profile.top.radius, Length::new(1.52) * DOT_PER_MM);
+        assert_is_close!(profile.top.radius, Vector::splat(1.52) * DOT_PER_MM);
         assert_is_close!(profile.top.y_offset, Length::new(-1.62) * DOT_PER_MM);
+        assert!(profile.rows.is_none());
+        assert!(profile.space_top.is_none());
 
         assert_eq!(profile.text_height.0.len(), 10);
         let expected = [
@@ -906,8 +1168,9 @@ mod tests {
         assert_is_close!(profile.bottom.radius, Length::new(0.38) * DOT_PER_MM);
 
         assert_is_close!(profile.top.size, Size::new(11.81, 13.91) * DOT_PER_MM);
-        assert_is_close!(profile.top.radius, Length::new(1.52) * DOT_PER_MM);
+        assert_is_close!(profile.top.radius, Vector::splat(1.52) * DOT_PER_MM);
         assert_is_close!(profile.top.y_offset, Length::new(-1.62) * DOT_PER_MM);
+        assert!(profile.rows.is_none());
 
         assert_eq!(profile.text_height.0.len(), 10);
         let expected = [
@@ -959,6 +1222,148 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_profile_from_json_with_rows() {
+        use geom::DOT_PER_MM;
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["rows"] = (1_u8..=4)
+            .map(|i| {
+                serde_json::json!({
+                    "width": 11.81,
+                    "height": 13.91,
+                    "radius": 1.52,
+                    "y-offset": -0.5 * f32::from(i),
+                })
+            })
+            .collect();
+
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+
+        let rows = profile.rows.unwrap();
+        for (row, i) in rows.iter().zip(1_u8..=4) {
+            assert_is_close!(row.size, Size::new(11.81, 13.91) * DOT_PER_MM);
+            assert_is_close!(row.y_offset, Length::new(-0.5 * f32::from(i)) * DOT_PER_MM);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_profile_from_json_with_space_top() {
+        use geom::DOT_PER_MM;
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["space-top"] = serde_json::json!({
+            "width": 11.81,
+            "height": 13.91,
+            "radius": 1.52,
+            "y-offset": 0.5,
+        });
+
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+
+        let space_top = profile.space_top.unwrap();
+        assert_is_close!(space_top.size, Size::new(11.81, 13.91) * DOT_PER_MM);
+        assert_is_close!(space_top.y_offset, Length::<Mm>::new(0.5) * DOT_PER_MM);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_profile_from_json_with_space_indicator() {
+        use geom::DOT_PER_MM;
+
+        assert!(Profile::from_json(PROFILE_JSON)
+            .unwrap()
+            .space_indicator
+            .is_none());
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["units"] = serde_json::json!("inch");
+        json["space-indicator"] = serde_json::json!({
+            "width": 2.0,
+            "height": 0.04,
+            "y-offset": 0.1,
+        });
+
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+
+        let indicator = profile.space_indicator.unwrap();
+        assert_is_close!(indicator.size, Size::new(50.8, 1.016) * DOT_PER_MM);
+        assert_is_close!(indicator.y_offset, Length::<Mm>::new(2.54) * DOT_PER_MM);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_profile_from_json_with_units() {
+        use geom::{DOT_PER_INCH, DOT_PER_MM};
+
+        let mm = Profile::from_json(PROFILE_JSON).unwrap();
+
+        for (units, factor) in [
+            ("mm", 1.0),
+            ("inch", DOT_PER_INCH.0 / DOT_PER_MM.0),
+            ("u", DOT_PER_UNIT.0 / DOT_PER_MM.0),
+        ] {
+            let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+            json["units"] = units.into();
+            let profile = Profile::from_json(&json.to_string()).unwrap();
+
+            assert_is_close!(profile.typ.depth(), mm.typ.depth() * factor);
+            assert_is_close!(profile.bottom.size, mm.bottom.size * factor);
+            assert_is_close!(profile.top.size, mm.top.size * factor);
+            assert_is_close!(profile.top.y_offset, mm.top.y_offset * factor);
+            assert_is_close!(profile.text_height.get(5), mm.text_height.get(5) * factor);
+            assert_is_close!(profile.text_margin.get(4), mm.text_margin.get(4) * factor);
+            assert_is_close!(profile.homing.bar.y_offset, mm.homing.bar.y_offset * factor);
+        }
+
+        // The units apply wherever they are in the file, and don't affect anything deserialized
+        // after the profile
+        let inch = DOT_PER_INCH.0 / DOT_PER_MM.0;
+        let body = PROFILE_JSON.trim().strip_prefix('{').unwrap();
+        let first = Profile::from_json(&format!(r#"{{ "units": "inch", {body}"#)).unwrap();
+        let body = PROFILE_JSON.trim().strip_suffix('}').unwrap();
+        let last = Profile::from_json(&format!(r#"{body}, "units": "inch" }}"#)).unwrap();
+        assert_is_close!(first.top.size, mm.top.size * inch);
+        assert_is_close!(first.text_height.get(5), mm.text_height.get(5) * inch);
+        assert_is_close!(last.top.size, mm.top.size * inch);
+        assert_is_close!(last.text_height.get(5), mm.text_height.get(5) * inch);
+        let after = Profile::from_json(PROFILE_JSON).unwrap();
+        assert_is_close!(after.top.size, mm.top.size);
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["units"] = "furlong".into();
+        assert!(Profile::from_json(&json.to_string()).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_profile_from_json_with_unit_pitch() {
+        use geom::DOT_PER_INCH;
+
+        let profile = Profile::from_json(PROFILE_JSON).unwrap();
+        assert_is_close!(profile.unit_pitch, Length::new(19.05));
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["unit-pitch"] = 18.0.into();
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+        assert_is_close!(profile.unit_pitch, Length::new(18.0));
+
+        // The default pitch is unaffected by the units, but an explicit pitch is converted
+        json["units"] = "inch".into();
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+        assert_is_close!(
+            profile.unit_pitch,
+            Length::new(18.0 * DOT_PER_INCH.0 / DOT_PER_MM.0)
+        );
+
+        let mut json: serde_json::Value = serde_json::from_str(PROFILE_JSON).unwrap();
+        json["units"] = "inch".into();
+        let profile = Profile::from_json(&json.to_string()).unwrap();
+        assert_is_close!(profile.unit_pitch, Length::new(19.05));
+    }
+
     #[cfg(feature = "json")]
     #[test]
     fn test_profile_from_invalid_json() {
@@ -974,8 +1379,8 @@ mod tests {
     fn test_profile_with_size() {
         let profile = Profile::default();
 
-        let top = profile.top_with_size(Size::new(1.0, 1.0));
-        let exp = RoundRect::from_center_and_size(
+        let top = profile.top_with_size_elliptical(Size::new(1.0, 1.0));
+        let exp = EllipticalRoundRect::from_center_and_size(
             Point::splat(0.5) * DOT_PER_UNIT + Vector::new(0.0, profile.top.y_offset.0),
             profile.top.size,
             profile.top.radius,
@@ -990,8 +1395,8 @@ mod tests {
         );
         assert_is_close!(bottom, exp);
 
-        let top = profile.top_with_size(Size::new(3.0, 2.0));
-        let exp = RoundRect::from_center_and_size(
+        let top = profile.top_with_size_elliptical(Size::new(3.0, 2.0));
+        let exp = EllipticalRoundRect::from_center_and_size(
             Point::new(1.5, 1.0) * DOT_PER_UNIT + Vector::new(0.0, profile.top.y_offset.0),
             profile.top.size + Size::new(2.0, 1.0) * DOT_PER_UNIT,
             profile.top.radius,
@@ -1005,6 +1410,176 @@ mod tests {
             profile.bottom.radius,
         );
         assert_is_close!(bottom, exp);
+
+        // The circular top uses the smaller radius
+        let profile = Profile {
+            top: TopSurface {
+                radius: Vector::new(40.0, 60.0),
+                ..TopSurface::default()
+            },
+            ..Profile::default()
+        };
+        let top = profile.top_with_size(Size::new(3.0, 2.0));
+        let exp = profile.top_with_size_elliptical(Size::new(3.0, 2.0));
+        assert_is_close!(top.rect(), exp.rect());
+        assert_is_close!(top.radius(), Length::new(40.0));
+    }
+
+    #[test]
+    fn test_profile_with_unit_pitch() {
+        let profile = Profile {
+            unit_pitch: Length::new(18.0),
+            ..Profile::default()
+        };
+        let dot_per_unit = Length::<Unit>::new(18.0 / 19.05) * DOT_PER_UNIT;
+
+        assert_is_close!(profile.dot_per_unit().get(), dot_per_unit.get());
+
+        // Keys are centred in a square of the unit pitch and get wider by the pitch for each unit
+        let bottom = profile.bottom_with_size(Size::new(2.0, 1.0));
+        assert_is_close!(bottom.center(), Point::new(1.0, 0.5) * dot_per_unit.get());
+        assert_is_close!(
+            bottom.size(),
+            profile.bottom.size + Size::new(dot_per_unit.get(), 0.0)
+        );
+
+        let bottom =
+            profile.bottom_with_rect(Rect::new(Point::new(1.0, 0.0), Point::new(2.0, 1.0)));
+        assert_is_close!(bottom.center(), Point::new(1.5, 0.5) * dot_per_unit.get());
+        assert_is_close!(bottom.size(), profile.bottom.size);
+    }
+
+    #[test]
+    fn test_profile_dish_center_with_size() {
+        let mut profile = Profile::default();
+
+        let center = profile.dish_center_with_size(Size::new(1.0, 2.0));
+        let exp = profile.top_with_size(Size::new(1.0, 2.0)).center();
+        assert_is_close!(center, exp);
+
+        profile.top.dish_offset = Vector::new(0.0, -100.0);
+        let center = profile.dish_center_with_size(Size::new(1.0, 2.0));
+        assert_is_close!(center, exp + Vector::new(0.0, -100.0));
+    }
+
+    #[test]
+    fn test_profile_row_top() {
+        let mut profile = Profile::default();
+
+        assert!(std::ptr::eq(profile.row_top(Some(1)), &profile.top));
+
+        profile.rows = Some(array::from_fn(|i| TopSurface {
+            y_offset: profile.top.y_offset - Length::new(10.0 * f32::saturating_from(i)),
+            ..TopSurface::default()
+        }));
+
+        assert!(std::ptr::eq(profile.row_top(None), &profile.top));
+        assert!(std::ptr::eq(profile.row_top(Some(0)), &profile.top));
+        assert!(std::ptr::eq(profile.row_top(Some(5)), &profile.top));
+
+        let row = profile.row_top(Some(3));
+        assert_is_close!(row.y_offset, profile.top.y_offset - Length::new(20.0));
+    }
+
+    #[test]
+    fn test_profile_spacebar_top() {
+        let mut profile = Profile {
+            rows: Some(array::from_fn(|i| TopSurface {
+                y_offset: Length::new(-10.0 * f32::saturating_from(i)),
+                ..TopSurface::default()
+            })),
+            ..Profile::default()
+        };
+
+        // Without a spacebar top surface spacebars use their row's top surface
+        assert!(std::ptr::eq(
+            profile.spacebar_top(Some(4)),
+            profile.row_top(Some(4))
+        ));
+
+        profile.space_top = Some(TopSurface {
+            y_offset: Length::new(-50.0),
+            ..TopSurface::default()
+        });
+        assert_is_close!(profile.spacebar_top(Some(4)).y_offset, Length::new(-50.0));
+    }
+
+    #[test]
+    fn test_profile_top_surface_with_size() {
+        let profile = Profile::default();
+        let top = TopSurface {
+            y_offset: Length::new(-100.0),
+            dish_offset: Vector::new(0.0, 20.0),
+            ..TopSurface::default()
+        };
+
+        let rect = profile.top_surface_with_size(&top, Size::new(2.0, 1.0));
+        let exp = profile.top_with_size(Size::new(2.0, 1.0));
+        assert_is_close!(
+            rect.center(),
+            exp.center() + Vector::new(0.0, -100.0 - profile.top.y_offset.get())
+        );
+        assert_is_close!(rect.size(), exp.size());
+
+        let center = profile.dish_center_with_surface(&top, Size::new(2.0, 1.0));
+        assert_is_close!(center, rect.center() + Vector::new(0.0, 20.0));
+    }
+
+    #[test]
+    fn test_profile_resolve_homing() {
+        for default in [Homing::Scoop, Homing::Bar, Homing::Bump] {
+            let profile = Profile {
+                homing: HomingProps {
+                    default,
+                    ..HomingProps::default()
+                },
+                ..Profile::default()
+            };
+
+            assert_eq!(profile.resolve_homing(None), default);
+            for homing in [Homing::Scoop, Homing::Bar, Homing::Bump] {
+                assert_eq!(profile.resolve_homing(Some(homing)), homing);
+            }
+        }
+    }
+
+    #[test]
+    fn test_profile_key_positions_mm() {
+        let profile = Profile::default();
+        let keys = [
+            key::Key::new(),
+            key::Key {
+                position: Point::new(1.0, 2.0),
+                shape: key::Shape::Space(Size::new(6.25, 1.0)),
+                ..key::Key::new()
+            },
+        ];
+
+        let positions = profile.key_positions_mm(&keys);
+        assert_eq!(positions.len(), 2);
+
+        let (center, size) = positions[0];
+        assert_is_close!(center, Point::new(9.525, 9.525));
+        assert_is_close!(size, Size::splat(0.95 * 19.05));
+
+        let (center, size) = positions[1];
+        assert_is_close!(center, Point::new(19.05 * 4.125, 19.05 * 2.5));
+        assert_is_close!(size, Size::new(19.05 * 6.2, 0.95 * 19.05));
+
+        // Keys are spaced using the unit pitch, but their keycaps are the same size
+        let profile = Profile {
+            unit_pitch: Length::new(18.0),
+            ..Profile::default()
+        };
+        let positions = profile.key_positions_mm(&keys);
+
+        let (center, size) = positions[0];
+        assert_is_close!(center, Point::new(9.0, 9.0));
+        assert_is_close!(size, Size::splat(0.95 * 19.05));
+
+        let (center, size) = positions[1];
+        assert_is_close!(center, Point::new(18.0 * 4.125, 18.0 * 2.5));
+        assert_is_close!(size, Size::new(0.95 * 19.05 + 18.0 * 5.25, 0.95 * 19.05));
     }
 
     #[test]
@@ -1017,8 +1592,9 @@ mod tests {
         assert_is_close!(profile.bottom.radius, Length::new(0.065) * DOT_PER_UNIT);
 
         assert_is_close!(profile.top.size, Size::new(0.660, 0.735) * DOT_PER_UNIT);
-        assert_is_close!(profile.top.radius, Length::new(0.065) * DOT_PER_UNIT);
+        assert_is_close!(profile.top.radius, Vector::splat(0.065) * DOT_PER_UNIT);
         assert_is_close!(profile.top.y_offset, Length::new(-0.0775) * DOT_PER_UNIT);
+        assert!(profile.rows.is_none());
 
         assert_eq!(profile.text_height.0.len(), 10);
         let expected = TextHeight::default();
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use geom::{Length, Mm, Size, Vector, DOT_PER_MM};
+use saturate::SaturatingFrom;
+
+use crate::{Profile, Type};
+
+/// A dimension of a keycap which can be measured, used with [`ProfileMeasurements`]
+///
+/// All measurements are in millimetres
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measurement {
+    /// The width of the key bottom
+    BottomWidth,
+    /// The height (front to back) of the key bottom
+    BottomHeight,
+    /// The corner radius of the key bottom
+    BottomRadius,
+    /// The width of the key top
+    TopWidth,
+    /// The height (front to back) of the key top
+    TopHeight,
+    /// The corner radius of the key top
+    TopRadius,
+    /// The offset of the centre of the key top relative to the centre of the key bottom, where
+    /// negative values are towards the back of the key
+    TopOffset,
+    /// The depth of the key's dish
+    DishDepth,
+}
+
+/// A builder for creating a [`Profile`] from several measured samples of each dimension, for
+/// example when measuring real keycaps with calipers
+///
+/// Each dimension of the resulting profile is the mean of its samples. Dimensions without any
+/// samples fall back to the value in [`Profile::default`]
+#[derive(Debug, Clone, Default)]
+pub struct ProfileMeasurements {
+    typ: Option<Type>,
+    samples: HashMap<Measurement, Vec<Length<Mm>>>,
+}
+
+impl ProfileMeasurements {
+    /// Create a new builder without any measurements
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the type of profile. The depth of the type is replaced by the mean of any
+    /// [`Measurement::DishDepth`] samples
+    #[inline]
+    pub fn typ(&mut self, typ: Type) {
+        self.typ = Some(typ);
+    }
+
+    /// Add a single sample for a dimension
+    #[inline]
+    pub fn add(&mut self, measurement: Measurement, sample: Length<Mm>) {
+        self.samples.entry(measurement).or_default().push(sample);
+    }
+
+    /// Add several samples for a dimension
+    #[inline]
+    pub fn extend(
+        &mut self,
+        measurement: Measurement,
+        samples: impl IntoIterator<Item = Length<Mm>>,
+    ) {
+        self.samples.entry(measurement).or_default().extend(samples);
+    }
+
+    /// The mean of the samples for a dimension, or [`None`] if there are no samples
+    #[must_use]
+    pub fn mean(&self, measurement: Measurement) -> Option<Length<Mm>> {
+        let samples = self.samples.get(&measurement).filter(|s| !s.is_empty())?;
+        let sum: f32 = samples.iter().map(|s| s.get()).sum();
+        Some(Length::new(sum / f32::saturating_from(samples.len())))
+    }
+
+    /// The spread (difference between the largest and smallest sample) of the samples for a
+    /// dimension, or [`None`] if there are no samples
+    ///
+    /// A large spread usually indicates an inconsistent measurement which should be retaken
+    #[must_use]
+    pub fn spread(&self, measurement: Measurement) -> Option<Length<Mm>> {
+        let samples = self.samples.get(&measurement).filter(|s| !s.is_empty())?;
+        let min = samples
+            .iter()
+            .map(|s| s.get())
+            .fold(f32::INFINITY, f32::min);
+        let max = samples
+            .iter()
+            .map(|s| s.get())
+            .fold(f32::NEG_INFINITY, f32::max);
+        Some(Length::new(max - min))
+    }
+
+    /// Build the [`Profile`] using the mean of each dimension's samples
+    ///
+    /// Dimensions without samples use the value from [`Profile::default`], and if only one of a
+    /// surface's width and height is measured the other keeps its default value
+    #[must_use]
+    pub fn build(&self) -> Profile {
+        let default = Profile::default();
+        let mean = |measurement| self.mean(measurement).map(|mean| mean * DOT_PER_MM);
+
+        let mut bottom = default.bottom;
+        bottom.size = Size::new(
+            mean(Measurement::BottomWidth).map_or(bottom.size.width, Length::get),
+            mean(Measurement::BottomHeight).map_or(bottom.size.height, Length::get),
+        );
+        bottom.radius = mean(Measurement::BottomRadius).unwrap_or(bottom.radius);
+
+        let mut top = default.top;
+        top.size = Size::new(
+            mean(Measurement::TopWidth).map_or(top.size.width, Length::get),
+            mean(Measurement::TopHeight).map_or(top.size.height, Length::get),
+        );
+        top.radius = mean(Measurement::TopRadius).map_or(top.radius, |r| Vector::splat(r.get()));
+        top.y_offset = mean(Measurement::TopOffset).unwrap_or(top.y_offset);
+
+        let typ = self.typ.unwrap_or(default.typ);
+        let typ = match (typ, mean(Measurement::DishDepth)) {
+            (Type::Cylindrical { .. }, Some(depth)) => Type::Cylindrical { depth },
+            (Type::Spherical { .. }, Some(depth)) => Type::Spherical { depth },
+            (typ, _) => typ,
+        };
+
+        Profile {
+            typ,
+            bottom,
+            top,
+            ..default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use isclose::assert_is_close;
+
+    use super::*;
+
+    #[test]
+    fn profile_measurements_stats() {
+        let mut measurements = ProfileMeasurements::new();
+        measurements.extend(Measurement::TopWidth, [12.5, 12.7, 12.6].map(Length::new));
+
+        assert_is_close!(
+            measurements.mean(Measurement::TopWidth).unwrap(),
+            Length::new(12.6)
+        );
+        assert_is_close!(
+            measurements.spread(Measurement::TopWidth).unwrap(),
+            Length::new(0.2)
+        );
+        assert!(measurements.mean(Measurement::TopHeight).is_none());
+        assert!(measurements.spread(Measurement::TopHeight).is_none());
+    }
+
+    #[test]
+    fn profile_measurements_build() {
+        let mut measurements = ProfileMeasurements::new();
+        measurements.typ(Type::Spherical {
+            depth: Length::new(0.0),
+        });
+        measurements.extend(Measurement::BottomWidth, [18.1, 18.3].map(Length::new));
+        measurements.add(Measurement::TopRadius, Length::new(1.0));
+        measurements.extend(Measurement::DishDepth, [0.8, 1.0, 1.2].map(Length::new));
+
+        let profile = measurements.build();
+        let default = Profile::default();
+
+        assert_is_close!(profile.bottom.size.width, 18.2 * DOT_PER_MM.get());
+        assert_is_close!(profile.bottom.size.height, default.bottom.size.height);
+        assert_is_close!(profile.top.radius, Vector::splat(DOT_PER_MM.get()));
+        assert_is_close!(profile.top.size, default.top.size);
+        assert_matches!(profile.typ, Type::Spherical { .. });
+        assert_is_close!(profile.typ.depth(), Length::new(1.0) * DOT_PER_MM);
+
+        let profile = ProfileMeasurements::new().build();
+        assert_eq!(profile, default);
+    }
+}
@@ -49,7 +49,7 @@
 //! // Set drawing options
 //! let options = drawing::Options {
 //!     profile: &profile,
-//!     font: &font,
+//!     font: Some(&font),
 //!     ..Default::default()
 //! };
 //!
@@ -69,5 +69,5 @@
 pub use color::Color;
 pub use drawing::{self, Drawing};
 pub use font::{self, Font};
-pub use key::{self, kle, Key};
+pub use key::{self, kle, Key, Layout};
 pub use profile::{self, Profile};